@@ -5,7 +5,7 @@ use std::sync::{Arc, Mutex};
 use crate::{display_info, logging, platform, profiles, settings_io, single_instance};
 use crate::settings::Settings;
 use crate::AppState;
-use rustframe_capture::capture::create_capture_engine;
+use rustframe_capture::capture::{create_capture_engine, CaptureMetrics, FollowZoom};
 
 lazy_static! {
     // Global flag to track if cleanup has been performed
@@ -187,6 +187,8 @@ pub(crate) fn build_app_state(
         render_thread_stop: Arc::new(Mutex::new(false)),
         render_thread_handle: Arc::new(Mutex::new(None)),
         monitors: Arc::new(Mutex::new(Vec::new())),
+        zoom: Arc::new(Mutex::new(FollowZoom::default())),
+        metrics: Arc::new(Mutex::new(CaptureMetrics::new())),
     }
 }
 
@@ -1,7 +1,7 @@
 use lazy_static::lazy_static;
 use std::sync::{Arc, Mutex};
 
-use rustframe_capture::capture::CaptureEngine;
+use rustframe_capture::capture::{CaptureEngine, SharedCaptureMetrics, ZoomControl};
 
 use crate::destination_window::DestinationWindow;
 use crate::hollow_border::HollowBorder;
@@ -33,4 +33,8 @@ pub(crate) struct AppState {
     pub(crate) render_thread_stop: Arc<Mutex<bool>>,
     pub(crate) render_thread_handle: Arc<Mutex<Option<std::thread::JoinHandle<()>>>>,
     pub(crate) monitors: Arc<Mutex<Vec<MonitorInfo>>>,
+    /// Follow-cursor zoom shared with the running capture's pipeline
+    pub(crate) zoom: ZoomControl,
+    /// Stage latencies of the running capture, reset when a capture starts
+    pub(crate) metrics: SharedCaptureMetrics,
 }
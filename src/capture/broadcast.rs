@@ -0,0 +1,181 @@
+// capture/broadcast.rs - Multi-Consumer Frame Fan-Out
+//
+// One capture session can feed several consumers (preview, recorder, network
+// sink). Each published frame is wrapped in an Arc once and handed to every
+// subscriber's own bounded queue, so a slow consumer only ever drops frames from
+// its own queue instead of stalling the others.
+
+use super::delivery::{bounded, DropPolicy, FrameReceiver, FrameSender};
+use super::CaptureFrame;
+use std::sync::{Arc, Mutex};
+
+/// A frame shared read-only between subscribers
+pub type SharedFrame = Arc<CaptureFrame>;
+
+/// Fans captured frames out to any number of subscribers
+///
+/// Cloning is cheap and every clone publishes to the same subscribers. Only CPU
+/// pixels are broadcast: a `gpu_texture` is released by whoever renders it,
+/// which only works with a single owner, so `publish` releases it instead.
+#[derive(Clone, Default)]
+pub struct FrameBroadcaster {
+    subscribers: Arc<Mutex<Vec<Arc<FrameSender<SharedFrame>>>>>,
+}
+
+impl FrameBroadcaster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscriber with its own queue depth and drop policy
+    ///
+    /// `DropPolicy::Block` pauses publishing for everyone while this subscriber's
+    /// queue is full, so reserve it for consumers that must see every frame.
+    /// Dropping the receiver unsubscribes.
+    pub fn subscribe(&self, depth: usize, policy: DropPolicy) -> FrameReceiver<SharedFrame> {
+        let (sender, receiver) = bounded(depth, policy);
+        self.subscribers.lock().unwrap().push(Arc::new(sender));
+        receiver
+    }
+
+    /// Number of subscribers that have not been pruned yet
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+
+    /// Share `frame` with every subscriber, returning how many are still listening
+    ///
+    /// The frame's `gpu_texture`, if any, is released and not passed on.
+    pub fn publish(&self, mut frame: CaptureFrame) -> usize {
+        if let Some(texture) = frame.gpu_texture.take() {
+            texture.release();
+        }
+
+        // Send outside the lock so a blocked subscriber doesn't hold up `subscribe`
+        let subscribers = self.subscribers.lock().unwrap().clone();
+        if subscribers.is_empty() {
+            return 0;
+        }
+
+        let frame = Arc::new(frame);
+        let disconnected: Vec<_> = subscribers
+            .iter()
+            .filter(|sender| !sender.send(Arc::clone(&frame)))
+            .collect();
+
+        let mut current = self.subscribers.lock().unwrap();
+        if !disconnected.is_empty() {
+            current.retain(|sender| !disconnected.iter().any(|gone| Arc::ptr_eq(sender, gone)));
+        }
+        current.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::delivery::{FramePump, FrameSink};
+    use crate::capture::{CaptureEngine, CaptureRect, SyntheticCaptureEngine, SyntheticPattern};
+    use std::time::Duration;
+
+    fn frame(value: u8) -> CaptureFrame {
+        CaptureFrame::new(vec![value; 4], 1, 1)
+    }
+
+    #[test]
+    fn subscribers_share_one_allocation() {
+        let broadcaster = FrameBroadcaster::new();
+        let preview = broadcaster.subscribe(1, DropPolicy::DropOldest);
+        let recorder = broadcaster.subscribe(4, DropPolicy::DropNewest);
+
+        assert_eq!(broadcaster.publish(frame(7)), 2);
+
+        let a = preview.try_recv().unwrap();
+        let b = recorder.try_recv().unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(a.data[0], 7);
+    }
+
+    #[test]
+    fn slow_subscriber_only_drops_its_own_frames() {
+        let broadcaster = FrameBroadcaster::new();
+        let preview = broadcaster.subscribe(1, DropPolicy::DropOldest);
+        let recorder = broadcaster.subscribe(8, DropPolicy::DropNewest);
+        let network = broadcaster.subscribe(2, DropPolicy::DropNewest);
+
+        for value in 0..5 {
+            broadcaster.publish(frame(value));
+        }
+
+        // Preview keeps only the latest frame
+        assert_eq!(preview.try_recv().unwrap().data[0], 4);
+        assert_eq!(preview.dropped(), 4);
+        // Recorder has room for everything
+        let recorded: Vec<u8> = std::iter::from_fn(|| recorder.try_recv())
+            .map(|f| f.data[0])
+            .collect();
+        assert_eq!(recorded, vec![0, 1, 2, 3, 4]);
+        assert_eq!(recorder.dropped(), 0);
+        // Network sink keeps the first two and discards the rest
+        assert_eq!(network.len(), 2);
+        assert_eq!(network.dropped(), 3);
+    }
+
+    #[test]
+    fn dropped_receivers_are_pruned() {
+        let broadcaster = FrameBroadcaster::new();
+        let keep = broadcaster.subscribe(1, DropPolicy::DropOldest);
+        let gone = broadcaster.subscribe(1, DropPolicy::Block);
+        drop(gone);
+
+        assert_eq!(broadcaster.subscriber_count(), 2);
+        assert_eq!(broadcaster.publish(frame(1)), 1);
+        assert_eq!(broadcaster.subscriber_count(), 1);
+        assert!(keep.try_recv().is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn gpu_textures_are_not_shared() {
+        use crate::capture::GpuTextureHandle;
+
+        let broadcaster = FrameBroadcaster::new();
+        let preview = broadcaster.subscribe(1, DropPolicy::DropOldest);
+        let mut gpu_frame = frame(3);
+        gpu_frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+            fd: -1,
+            width: 1,
+            height: 1,
+            stride: 4,
+            format: 0,
+        });
+
+        assert_eq!(broadcaster.publish(gpu_frame), 1);
+        let shared = preview.try_recv().unwrap();
+        assert!(shared.gpu_texture.is_none());
+        assert_eq!(shared.data[0], 3);
+    }
+
+    #[test]
+    fn pump_feeds_broadcaster() {
+        let mut engine = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        engine
+            .start(CaptureRect::new(0, 0, 8, 8), false, None)
+            .unwrap();
+        let engine = Arc::new(Mutex::new(Some(Box::new(engine) as Box<dyn CaptureEngine>)));
+
+        let broadcaster = FrameBroadcaster::new();
+        let first = broadcaster.subscribe(2, DropPolicy::DropOldest);
+        let second = broadcaster.subscribe(2, DropPolicy::DropOldest);
+        let pump = FramePump::start(
+            engine,
+            FrameSink::Broadcast(broadcaster.clone()),
+            Duration::from_millis(1),
+        );
+
+        let a = first.recv_timeout(Duration::from_secs(2)).unwrap();
+        let b = second.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!((a.width, b.width), (8, 8));
+        pump.stop();
+    }
+}
@@ -0,0 +1,382 @@
+// capture/clicks.rs - Click Highlight Compositor
+//
+// Draws dissolving click markers onto frames. Platform code only has to collect
+// timestamped clicks and translate them into frame pixels; how a click looks
+// (per-button color, ripple/ring/filled style, fade over the dissolve time) is
+// decided here, identically on every OS. GPU renderers that cannot touch the
+// pixels can ask for the same markers via `ClickHighlighter::marks`.
+
+use super::pipeline::FrameProcessor;
+use super::{convert, CaptureFrame, PixelFormat};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Mouse button that produced a click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClickButton {
+    Left,
+    Right,
+    Middle,
+}
+
+/// A click in frame pixels, relative to the frame's top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClickEvent {
+    pub x: i32,
+    pub y: i32,
+    pub button: ClickButton,
+    pub timestamp: Instant,
+}
+
+/// How a click is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClickHighlightStyle {
+    /// Ring that expands outwards while it fades
+    Ripple,
+    /// Fixed-size ring
+    Ring,
+    /// Solid dot with a soft edge
+    #[default]
+    Filled,
+}
+
+/// One click as it should appear at a given moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClickMark {
+    pub x: f32,
+    pub y: f32,
+    /// Outer radius in frame pixels
+    pub radius: f32,
+    /// [R, G, B, A] including the configured alpha
+    pub color: [u8; 4],
+    /// Fade factor, 1.0 right after the click down to 0.0 when dissolved
+    pub fade: f32,
+    pub style: ClickHighlightStyle,
+}
+
+/// Click highlight appearance, usually built from the `click_highlight_*` settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClickHighlighter {
+    style: ClickHighlightStyle,
+    /// Colors for left, right and middle clicks as [R, G, B, A]
+    colors: [[u8; 4]; 3],
+    radius: f32,
+    dissolve: Duration,
+}
+
+impl ClickHighlighter {
+    /// Same `color` for every button, fading out over `dissolve`
+    pub fn new(color: [u8; 4], radius: f32, dissolve: Duration) -> Self {
+        Self {
+            style: ClickHighlightStyle::default(),
+            colors: [color; 3],
+            radius,
+            dissolve,
+        }
+    }
+
+    pub fn with_style(mut self, style: ClickHighlightStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_button_color(mut self, button: ClickButton, color: [u8; 4]) -> Self {
+        self.colors[button as usize] = color;
+        self
+    }
+
+    pub fn color_for(&self, button: ClickButton) -> [u8; 4] {
+        self.colors[button as usize]
+    }
+
+    /// How long a click stays visible
+    pub fn dissolve(&self) -> Duration {
+        self.dissolve
+    }
+
+    /// Marker for `click` at `now`, or None once it has dissolved
+    pub fn mark(&self, click: &ClickEvent, now: Instant) -> Option<ClickMark> {
+        let age = now.saturating_duration_since(click.timestamp);
+        if age >= self.dissolve || self.radius <= 0.0 {
+            return None;
+        }
+        let progress = age.as_secs_f32() / self.dissolve.as_secs_f32();
+        let radius = match self.style {
+            ClickHighlightStyle::Ripple => self.radius * (0.3 + 0.7 * progress),
+            _ => self.radius,
+        };
+        Some(ClickMark {
+            x: click.x as f32,
+            y: click.y as f32,
+            radius,
+            color: self.color_for(click.button),
+            fade: 1.0 - progress,
+            style: self.style,
+        })
+    }
+
+    /// Markers for every click still visible at `now`
+    pub fn marks(&self, clicks: &[ClickEvent], now: Instant) -> Vec<ClickMark> {
+        clicks
+            .iter()
+            .filter_map(|click| self.mark(click, now))
+            .collect()
+    }
+
+    /// Blend the visible clicks into a packed frame; planar frames become BGRA
+    pub fn draw(
+        &self,
+        frame: &mut CaptureFrame,
+        clicks: &[ClickEvent],
+        now: Instant,
+    ) -> Result<()> {
+        let marks = self.marks(clicks, now);
+        if marks.is_empty() {
+            return Ok(());
+        }
+        if frame.pixel_format.is_planar() {
+            convert::convert_frame(frame, PixelFormat::Bgra8)?;
+        }
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+        for mark in &marks {
+            draw_mark(frame, mark);
+        }
+        Ok(())
+    }
+}
+
+impl ClickMark {
+    /// Opacity of the marker at `distance` pixels from its center, before color alpha and fade
+    pub fn coverage(&self, distance: f32) -> f32 {
+        let radius = self.radius;
+        if distance > radius {
+            return 0.0;
+        }
+        match self.style {
+            ClickHighlightStyle::Filled => {
+                let inner = (radius * 0.4).max(4.0);
+                if distance <= inner || radius <= inner {
+                    1.0
+                } else {
+                    1.0 - (distance - inner) / (radius - inner)
+                }
+            }
+            ClickHighlightStyle::Ring | ClickHighlightStyle::Ripple => {
+                let thickness = (radius * 0.2).max(2.0);
+                // Half a pixel of anti-aliasing on both edges of the band
+                let inside = (distance - (radius - thickness) + 0.5).clamp(0.0, 1.0);
+                let outside = (radius - distance + 0.5).clamp(0.0, 1.0);
+                inside.min(outside)
+            }
+        }
+    }
+}
+
+pub(crate) fn draw_mark(frame: &mut CaptureFrame, mark: &ClickMark) {
+    let bpp = frame.pixel_format.bytes_per_pixel() as usize;
+    let (r, b) = match frame.pixel_format {
+        PixelFormat::Bgra8 => (2, 0),
+        _ => (0, 2),
+    };
+    let base_alpha = mark.color[3] as f32 / 255.0 * mark.fade;
+
+    let reach = mark.radius.ceil() as i64;
+    let (cx, cy) = (mark.x as i64, mark.y as i64);
+    let (x0, x1) = (
+        (cx - reach).max(0),
+        (cx + reach).min(frame.width as i64 - 1),
+    );
+    let (y0, y1) = (
+        (cy - reach).max(0),
+        (cy + reach).min(frame.height as i64 - 1),
+    );
+
+    for py in y0..=y1 {
+        for px in x0..=x1 {
+            let (dx, dy) = ((px - cx) as f32, (py - cy) as f32);
+            let alpha = base_alpha * mark.coverage((dx * dx + dy * dy).sqrt());
+            if alpha <= 0.0 {
+                continue;
+            }
+            let offset = py as usize * frame.stride as usize + px as usize * bpp;
+            let dst = &mut frame.data[offset..offset + bpp];
+            for (channel, value) in [(r, mark.color[0]), (1, mark.color[1]), (b, mark.color[2])] {
+                let mixed = value as f32 * alpha + dst[channel] as f32 * (1.0 - alpha);
+                dst[channel] = mixed.round() as u8;
+            }
+        }
+    }
+}
+
+/// Source of clicks for a frame, already translated into that frame's pixels
+pub type ClickSource = Box<dyn FnMut(&CaptureFrame) -> Vec<ClickEvent> + Send>;
+
+/// Pipeline stage drawing clicks reported by a `ClickSource`
+pub struct ClickHighlightProcessor {
+    highlighter: ClickHighlighter,
+    source: ClickSource,
+}
+
+impl ClickHighlightProcessor {
+    pub fn new(highlighter: ClickHighlighter, source: ClickSource) -> Self {
+        Self {
+            highlighter,
+            source,
+        }
+    }
+}
+
+impl FrameProcessor for ClickHighlightProcessor {
+    fn name(&self) -> &str {
+        "click_highlight"
+    }
+
+    fn is_active(&self, frame: &CaptureFrame) -> bool {
+        // GPU renderers draw their own marks over the texture
+        frame.gpu_texture.is_none()
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        let clicks = (self.source)(frame);
+        self.highlighter.draw(frame, &clicks, Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn black(size: u32) -> CaptureFrame {
+        CaptureFrame::new(vec![0; (size * size * 4) as usize], size, size)
+    }
+
+    fn click(x: i32, y: i32, button: ClickButton, at: Instant) -> ClickEvent {
+        ClickEvent {
+            x,
+            y,
+            button,
+            timestamp: at,
+        }
+    }
+
+    fn bgra(frame: &CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y * frame.stride + x * 4) as usize;
+        frame.data[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn clicks_fade_over_dissolve_time() {
+        let highlighter = ClickHighlighter::new(RED, 10.0, Duration::from_millis(300));
+        let start = Instant::now();
+        let event = click(5, 5, ClickButton::Left, start);
+
+        assert_eq!(highlighter.mark(&event, start).unwrap().fade, 1.0);
+        let half = highlighter
+            .mark(&event, start + Duration::from_millis(150))
+            .unwrap();
+        assert!((half.fade - 0.5).abs() < 1e-3);
+        assert!(highlighter
+            .mark(&event, start + Duration::from_millis(300))
+            .is_none());
+    }
+
+    #[test]
+    fn buttons_use_their_own_colors() {
+        let highlighter = ClickHighlighter::new(RED, 6.0, Duration::from_secs(1))
+            .with_button_color(ClickButton::Right, BLUE);
+        let now = Instant::now();
+        let mut frame = black(40);
+        highlighter
+            .draw(
+                &mut frame,
+                &[
+                    click(10, 10, ClickButton::Left, now),
+                    click(30, 30, ClickButton::Right, now),
+                    click(10, 30, ClickButton::Middle, now),
+                ],
+                now,
+            )
+            .unwrap();
+
+        assert_eq!(bgra(&frame, 10, 10), [0, 0, 255, 0]);
+        assert_eq!(bgra(&frame, 30, 30), [255, 0, 0, 0]);
+        // Middle falls back to the shared color
+        assert_eq!(bgra(&frame, 10, 30), [0, 0, 255, 0]);
+        assert_eq!(bgra(&frame, 20, 20), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn ring_leaves_center_untouched() {
+        let now = Instant::now();
+        let mut frame = black(41);
+        ClickHighlighter::new(RED, 15.0, Duration::from_secs(1))
+            .with_style(ClickHighlightStyle::Ring)
+            .draw(&mut frame, &[click(20, 20, ClickButton::Left, now)], now)
+            .unwrap();
+
+        assert_eq!(bgra(&frame, 20, 20), [0, 0, 0, 0]);
+        assert_eq!(bgra(&frame, 20 + 13, 20), [0, 0, 255, 0]);
+        assert_eq!(bgra(&frame, 20 + 17, 20), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn ripple_grows_while_fading() {
+        let highlighter = ClickHighlighter::new(RED, 20.0, Duration::from_millis(100))
+            .with_style(ClickHighlightStyle::Ripple);
+        let start = Instant::now();
+        let event = click(0, 0, ClickButton::Left, start);
+        let early = highlighter.mark(&event, start).unwrap();
+        let late = highlighter
+            .mark(&event, start + Duration::from_millis(80))
+            .unwrap();
+        assert!(late.radius > early.radius);
+        assert!(late.fade < early.fade);
+    }
+
+    #[test]
+    fn clicks_near_edges_are_clipped() {
+        let now = Instant::now();
+        let mut frame = black(8).with_pixel_format(PixelFormat::Rgba8);
+        ClickHighlighter::new([0, 255, 0, 128], 10.0, Duration::from_secs(1))
+            .draw(
+                &mut frame,
+                &[
+                    click(-3, 0, ClickButton::Left, now),
+                    click(100, 100, ClickButton::Left, now),
+                ],
+                now,
+            )
+            .unwrap();
+        // Half-transparent green over black, RGBA order
+        assert_eq!(bgra(&frame, 0, 0), [0, 128, 0, 0]);
+    }
+
+    #[test]
+    fn processor_pulls_clicks_per_frame() {
+        let highlighter = ClickHighlighter::new(RED, 4.0, Duration::from_secs(1));
+        let mut stage = ClickHighlightProcessor::new(
+            highlighter,
+            Box::new(|frame: &CaptureFrame| {
+                vec![click(
+                    frame.width as i32 / 2,
+                    frame.height as i32 / 2,
+                    ClickButton::Left,
+                    Instant::now(),
+                )]
+            }),
+        );
+        let mut frame = black(16);
+        stage.process(&mut frame).unwrap();
+        assert_eq!(bgra(&frame, 8, 8), [0, 0, 255, 0]);
+    }
+}
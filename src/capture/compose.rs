@@ -0,0 +1,618 @@
+// capture/compose.rs - Multi-Region Composition
+//
+// Captures several screen regions at once and arranges them on one output
+// canvas, e.g. an editor and a terminal side by side in a single shared feed.
+// Each region has its own capture engine; `MultiRegionCaptureEngine` is itself
+// a `CaptureEngine`, so the pump, pipeline and destination window treat the
+// composed canvas like any other frame.
+//
+// Side-by-side and stacked layouts keep every region at its native size (text
+// stays pixel-exact) and align them to the top or left edge. The primary region
+// always lands at the canvas origin, so region-relative positions (redaction
+// zones, clicks, the zoom pointer) still fit the composed frame.
+// Picture-in-picture shows the first region full size with the others scaled
+// down into a corner.
+
+use super::redact::pixel_bytes;
+use super::{
+    convert, CaptureEngine, CaptureFrame, CaptureRect, CaptureStats, CursorStyle, FitMode,
+    FramePool, FrameScaler, FrameStatsTracker, OverlayPosition, PixelFormat,
+};
+use crate::window_filter::WindowIdentifier;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// How regions are arranged on the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CompositeLayout {
+    /// Left to right, in region order
+    #[default]
+    SideBySide,
+    /// Top to bottom, in region order
+    Stacked,
+    /// First region fills the canvas, the others are inset in a corner
+    PictureInPicture,
+}
+
+/// Canvas arrangement, usually from the `composition` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompositeSettings {
+    pub layout: CompositeLayout,
+    /// Pixels between regions; for picture-in-picture also the inset margin
+    pub gap: u32,
+    /// Canvas fill as [R, G, B, A]
+    pub background: [u8; 4],
+    /// Picture-in-picture inset width as a fraction of the main region's width
+    pub pip_scale: f32,
+    /// Corner the picture-in-picture insets are anchored to
+    pub pip_position: OverlayPosition,
+}
+
+impl Default for CompositeSettings {
+    fn default() -> Self {
+        Self {
+            layout: CompositeLayout::default(),
+            gap: 8,
+            background: [0, 0, 0, 255],
+            pip_scale: 0.25,
+            pip_position: OverlayPosition::BottomRight,
+        }
+    }
+}
+
+/// Canvas size and where each region lands on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompositePlan {
+    pub width: u32,
+    pub height: u32,
+    /// One cell per region, in region order
+    pub cells: Vec<CaptureRect>,
+}
+
+/// Arranges region frames on a canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionCompositor {
+    settings: CompositeSettings,
+}
+
+impl RegionCompositor {
+    pub fn new(settings: CompositeSettings) -> Self {
+        Self { settings }
+    }
+
+    pub fn settings(&self) -> &CompositeSettings {
+        &self.settings
+    }
+
+    /// Place regions of the given sizes; an empty list gives an empty canvas
+    pub fn plan(&self, sizes: &[(u32, u32)]) -> CompositePlan {
+        let gap = self.settings.gap;
+        let total_gaps = gap * sizes.len().saturating_sub(1) as u32;
+        match self.settings.layout {
+            CompositeLayout::SideBySide => {
+                let width = sizes.iter().map(|s| s.0).sum::<u32>() + total_gaps;
+                let height = sizes.iter().map(|s| s.1).max().unwrap_or(0);
+                let mut x = 0;
+                let cells = sizes
+                    .iter()
+                    .map(|&(w, h)| {
+                        let cell = CaptureRect::new(x, 0, w, h);
+                        x += (w + gap) as i32;
+                        cell
+                    })
+                    .collect();
+                CompositePlan {
+                    width,
+                    height,
+                    cells,
+                }
+            }
+            CompositeLayout::Stacked => {
+                let width = sizes.iter().map(|s| s.0).max().unwrap_or(0);
+                let height = sizes.iter().map(|s| s.1).sum::<u32>() + total_gaps;
+                let mut y = 0;
+                let cells = sizes
+                    .iter()
+                    .map(|&(w, h)| {
+                        let cell = CaptureRect::new(0, y, w, h);
+                        y += (h + gap) as i32;
+                        cell
+                    })
+                    .collect();
+                CompositePlan {
+                    width,
+                    height,
+                    cells,
+                }
+            }
+            CompositeLayout::PictureInPicture => self.plan_picture_in_picture(sizes),
+        }
+    }
+
+    fn plan_picture_in_picture(&self, sizes: &[(u32, u32)]) -> CompositePlan {
+        let (width, height) = match sizes.first() {
+            Some(&size) => size,
+            None => {
+                return CompositePlan {
+                    width: 0,
+                    height: 0,
+                    cells: Vec::new(),
+                }
+            }
+        };
+        let gap = self.settings.gap as i32;
+        let inset_width = ((width as f32 * self.settings.pip_scale.clamp(0.0, 1.0)).round() as u32)
+            .clamp(1, width);
+
+        // Insets keep their aspect ratio and stack away from the anchored edge
+        let insets: Vec<(u32, u32)> = sizes[1..]
+            .iter()
+            .map(|&(w, h)| {
+                let scaled = (h as f64 * inset_width as f64 / w.max(1) as f64).round() as u32;
+                (inset_width, scaled.clamp(1, height))
+            })
+            .collect();
+        let column_height =
+            insets.iter().map(|s| s.1 as i32).sum::<i32>() + gap * (insets.len() as i32 - 1).max(0);
+
+        let (left, top) = match self.settings.pip_position {
+            OverlayPosition::TopLeft => (gap, gap),
+            OverlayPosition::TopRight => (width as i32 - inset_width as i32 - gap, gap),
+            OverlayPosition::BottomLeft => (gap, height as i32 - column_height - gap),
+            OverlayPosition::BottomRight => (
+                width as i32 - inset_width as i32 - gap,
+                height as i32 - column_height - gap,
+            ),
+            OverlayPosition::Center => (
+                (width as i32 - inset_width as i32) / 2,
+                (height as i32 - column_height) / 2,
+            ),
+        };
+
+        let mut cells = vec![CaptureRect::new(0, 0, width, height)];
+        let mut y = top;
+        for (w, h) in insets {
+            cells.push(CaptureRect::new(left, y, w, h));
+            y += h as i32 + gap;
+        }
+        CompositePlan {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Draw `frames` (in region order, `None` for regions without a frame yet)
+    /// onto a BGRA canvas sized for `sizes`
+    ///
+    /// The canvas takes its offset from the first region's frame and its
+    /// timestamp from the newest frame; damage is unknown.
+    pub fn compose(
+        &self,
+        sizes: &[(u32, u32)],
+        frames: &[Option<&CaptureFrame>],
+    ) -> Result<CaptureFrame> {
+        if sizes.len() != frames.len() {
+            bail!("{} region sizes for {} frames", sizes.len(), frames.len());
+        }
+        let plan = self.plan(sizes);
+        if plan.width == 0 || plan.height == 0 {
+            bail!("Nothing to compose");
+        }
+
+        let canvas_size = (plan.width, plan.height);
+        let mut data = filled_canvas(canvas_size, self.settings.background);
+        for (frame, cell) in frames.iter().zip(&plan.cells) {
+            if let Some(frame) = frame {
+                blit(&mut data, canvas_size, cell, frame)?;
+            }
+        }
+
+        let mut canvas = CaptureFrame::new(data, plan.width, plan.height);
+        if let Some(first) = frames.first().copied().flatten() {
+            canvas = canvas.with_offset(first.offset_x, first.offset_y);
+        }
+        if let Some(newest) = frames.iter().flatten().map(|f| f.captured_at).max() {
+            canvas.captured_at = newest;
+        }
+        Ok(canvas)
+    }
+}
+
+/// BGRA canvas of `size` filled with `rgba`
+pub(super) fn filled_canvas(size: (u32, u32), rgba: [u8; 4]) -> Vec<u8> {
+    let mut data = FramePool::global().take(size.0 as usize * size.1 as usize * 4);
+    let fill = pixel_bytes(rgba, PixelFormat::Bgra8);
+    for pixel in data.chunks_exact_mut(4) {
+        pixel.copy_from_slice(&fill);
+    }
+    data
+}
+
+/// Copy `frame` into `cell` of a BGRA canvas, scaling it when the sizes differ
+pub(super) fn blit(
+    canvas: &mut [u8],
+    canvas_size: (u32, u32),
+    cell: &CaptureRect,
+    frame: &CaptureFrame,
+) -> Result<()> {
+    let scaled;
+    let frame = if (frame.width, frame.height) != (cell.width, cell.height) {
+        scaled = FrameScaler::new(cell.width, cell.height)
+            .with_fit(FitMode::Stretch)
+            .scale(frame)?;
+        &scaled
+    } else {
+        frame
+    };
+
+    let converted;
+    let (src, src_stride) = if frame.pixel_format == PixelFormat::Bgra8 {
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+        (frame.data.as_slice(), frame.stride as usize)
+    } else {
+        converted = convert::to_packed(frame, PixelFormat::Bgra8)?;
+        (converted.as_slice(), frame.width as usize * 4)
+    };
+
+    // Cells are planned inside the canvas, but clip anyway for odd inset sizes
+    let bounds = CaptureRect::new(0, 0, canvas_size.0, canvas_size.1);
+    let visible = match cell.intersect(&bounds) {
+        Some(visible) => visible,
+        None => return Ok(()),
+    };
+    let skip = visible.relative_to(cell);
+    let stride = canvas_size.0 as usize * 4;
+    let row_bytes = visible.width as usize * 4;
+    for row in 0..visible.height as usize {
+        let src_start = (row + skip.y as usize) * src_stride + skip.x as usize * 4;
+        let dst_start = (row + visible.y as usize) * stride + visible.x as usize * 4;
+        canvas[dst_start..dst_start + row_bytes]
+            .copy_from_slice(&src[src_start..src_start + row_bytes]);
+    }
+    Ok(())
+}
+
+struct RegionSource {
+    engine: Box<dyn CaptureEngine>,
+    region: CaptureRect,
+    /// Latest frame, reused while the engine has nothing newer
+    last: Option<CaptureFrame>,
+}
+
+/// Capture engine that runs one engine per region and composes their frames
+///
+/// The first region is the primary one: `start` and `update_region` move it
+/// (so the hollow border drives it as usual) while the others stay where they
+/// were added.
+pub struct MultiRegionCaptureEngine {
+    compositor: RegionCompositor,
+    sources: Vec<RegionSource>,
+    is_active: bool,
+    frame_stats: FrameStatsTracker,
+}
+
+impl MultiRegionCaptureEngine {
+    /// Compose with `primary` as the first region
+    pub fn new(primary: Box<dyn CaptureEngine>, settings: CompositeSettings) -> Self {
+        Self {
+            compositor: RegionCompositor::new(settings),
+            sources: vec![RegionSource {
+                engine: primary,
+                region: CaptureRect::new(0, 0, 0, 0),
+                last: None,
+            }],
+            is_active: false,
+            frame_stats: FrameStatsTracker::new(),
+        }
+    }
+
+    /// Add a region captured by its own engine, placed after the existing ones
+    pub fn with_region(mut self, engine: Box<dyn CaptureEngine>, region: CaptureRect) -> Self {
+        self.sources.push(RegionSource {
+            engine,
+            region,
+            last: None,
+        });
+        self
+    }
+
+    /// All regions, primary first
+    pub fn regions(&self) -> Vec<CaptureRect> {
+        self.sources.iter().map(|s| s.region).collect()
+    }
+
+    pub fn compositor(&self) -> &RegionCompositor {
+        &self.compositor
+    }
+}
+
+impl CaptureEngine for MultiRegionCaptureEngine {
+    fn start(
+        &mut self,
+        region: CaptureRect,
+        show_cursor: bool,
+        excluded_windows: Option<Vec<WindowIdentifier>>,
+    ) -> Result<()> {
+        self.sources[0].region = region;
+        for index in 0..self.sources.len() {
+            let source = &mut self.sources[index];
+            source.last = None;
+            if let Err(e) =
+                source
+                    .engine
+                    .start(source.region, show_cursor, excluded_windows.clone())
+            {
+                for started in &mut self.sources[..index] {
+                    started.engine.stop();
+                }
+                return Err(e.context(format!("Failed to start region {}", index + 1)));
+            }
+        }
+        self.frame_stats.reset();
+        self.is_active = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        for source in &mut self.sources {
+            source.engine.stop();
+            if let Some(frame) = source.last.take() {
+                FramePool::global().recycle(frame.data);
+            }
+        }
+        self.is_active = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn has_new_frame(&self) -> bool {
+        self.is_active && self.sources.iter().any(|s| s.engine.has_new_frame())
+    }
+
+    fn get_frame(&mut self) -> Option<CaptureFrame> {
+        if !self.is_active {
+            return None;
+        }
+
+        let mut updated = false;
+        for source in &mut self.sources {
+            if let Some(frame) = source.engine.get_frame() {
+                if let Some(old) = source.last.replace(frame) {
+                    FramePool::global().recycle(old.data);
+                }
+                updated = true;
+            }
+        }
+        if !updated {
+            return None;
+        }
+
+        // Regions without a frame yet keep their cell, filled with background
+        let sizes: Vec<(u32, u32)> = self
+            .sources
+            .iter()
+            .map(|s| match &s.last {
+                Some(frame) => (frame.width, frame.height),
+                None => (s.region.width, s.region.height),
+            })
+            .collect();
+        let frames: Vec<Option<&CaptureFrame>> =
+            self.sources.iter().map(|s| s.last.as_ref()).collect();
+        match self.compositor.compose(&sizes, &frames) {
+            Ok(mut canvas) => {
+                self.frame_stats.stamp(&mut canvas);
+                Some(canvas)
+            }
+            Err(e) => {
+                log::warn!("Region composition failed: {}", e);
+                None
+            }
+        }
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<()> {
+        for source in &mut self.sources {
+            source.engine.set_cursor_visible(visible)?;
+        }
+        Ok(())
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        for source in &mut self.sources {
+            source.engine.set_cursor_style(style.clone())?;
+        }
+        Ok(())
+    }
+
+    fn set_cpu_frames_required(&mut self, required: bool) {
+        for source in &mut self.sources {
+            source.engine.set_cpu_frames_required(required);
+        }
+    }
+
+    fn get_region(&self) -> Option<CaptureRect> {
+        self.sources[0].engine.get_region()
+    }
+
+    fn update_region(&mut self, region: CaptureRect) -> Result<()> {
+        self.sources[0].engine.update_region(region)?;
+        self.sources[0].region = region;
+        Ok(())
+    }
+
+    fn set_scale_factor(&mut self, scale: f64) -> Result<()> {
+        for source in &mut self.sources {
+            source.engine.set_scale_factor(scale)?;
+        }
+        Ok(())
+    }
+
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{SyntheticCaptureEngine, SyntheticPattern};
+
+    fn compositor(layout: CompositeLayout) -> RegionCompositor {
+        RegionCompositor::new(CompositeSettings {
+            layout,
+            gap: 4,
+            ..CompositeSettings::default()
+        })
+    }
+
+    fn solid(width: u32, height: u32, bgra: [u8; 4]) -> CaptureFrame {
+        CaptureFrame::new(bgra.repeat((width * height) as usize), width, height)
+    }
+
+    fn pixel(frame: &CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+        let idx = (y * frame.stride + x * 4) as usize;
+        [
+            frame.data[idx],
+            frame.data[idx + 1],
+            frame.data[idx + 2],
+            frame.data[idx + 3],
+        ]
+    }
+
+    #[test]
+    fn side_by_side_and_stacked_align_regions_to_the_origin() {
+        let sizes = [(100, 60), (40, 20)];
+
+        let plan = compositor(CompositeLayout::SideBySide).plan(&sizes);
+        assert_eq!((plan.width, plan.height), (144, 60));
+        assert_eq!(
+            plan.cells,
+            vec![
+                CaptureRect::new(0, 0, 100, 60),
+                CaptureRect::new(104, 0, 40, 20)
+            ]
+        );
+
+        let plan = compositor(CompositeLayout::Stacked).plan(&sizes);
+        assert_eq!((plan.width, plan.height), (100, 84));
+        assert_eq!(
+            plan.cells,
+            vec![
+                CaptureRect::new(0, 0, 100, 60),
+                CaptureRect::new(0, 64, 40, 20)
+            ]
+        );
+    }
+
+    #[test]
+    fn picture_in_picture_insets_scale_into_the_corner() {
+        let plan = compositor(CompositeLayout::PictureInPicture).plan(&[(200, 100), (80, 40)]);
+        assert_eq!((plan.width, plan.height), (200, 100));
+        assert_eq!(plan.cells[0], CaptureRect::new(0, 0, 200, 100));
+        // A quarter of the main width, same aspect, `gap` from the bottom-right
+        assert_eq!(plan.cells[1], CaptureRect::new(146, 71, 50, 25));
+    }
+
+    #[test]
+    fn compose_fills_gaps_with_background() {
+        let compositor = RegionCompositor::new(CompositeSettings {
+            gap: 2,
+            background: [0, 0, 255, 255],
+            ..CompositeSettings::default()
+        });
+        let left = solid(4, 4, [0, 255, 0, 255]).with_offset(10, 20);
+        let right = solid(2, 2, [255, 255, 255, 255]);
+        let canvas = compositor
+            .compose(&[(4, 4), (2, 2)], &[Some(&left), Some(&right)])
+            .unwrap();
+
+        assert_eq!((canvas.width, canvas.height), (8, 4));
+        assert_eq!((canvas.offset_x, canvas.offset_y), (10, 20));
+        assert_eq!(pixel(&canvas, 0, 0), [0, 255, 0, 255]);
+        // The gap and the space around the smaller region are background (BGRA)
+        assert_eq!(pixel(&canvas, 4, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 6, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 6, 1), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn redaction_zones_line_up_with_the_primary_region() {
+        use crate::capture::{FrameProcessor, RedactionProcessor, RedactionStyle, RedactionZone};
+
+        // The second region is taller, so the canvas is taller than the primary
+        for layout in [CompositeLayout::SideBySide, CompositeLayout::Stacked] {
+            let primary = solid(4, 4, [0, 255, 0, 255]);
+            let secondary = solid(2, 8, [255, 255, 255, 255]);
+            let mut canvas = compositor(layout)
+                .compose(&[(4, 4), (2, 8)], &[Some(&primary), Some(&secondary)])
+                .unwrap();
+
+            let zone = RedactionZone::new(
+                CaptureRect::new(0, 0, 2, 2),
+                RedactionStyle::Solid {
+                    color: [0, 0, 0, 255],
+                },
+            );
+            RedactionProcessor::new(vec![zone])
+                .process(&mut canvas)
+                .unwrap();
+            for (x, y) in [(0, 0), (1, 1)] {
+                assert_eq!(pixel(&canvas, x, y), [0, 0, 0, 255], "{:?}", layout);
+            }
+            for (x, y) in [(2, 2), (3, 3), (0, 2)] {
+                assert_eq!(pixel(&canvas, x, y), [0, 255, 0, 255], "{:?}", layout);
+            }
+        }
+    }
+
+    #[test]
+    fn engine_composes_regions_and_moves_the_primary() {
+        let primary = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        let secondary = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        let mut engine = MultiRegionCaptureEngine::new(
+            Box::new(primary.with_frame_counter(false)),
+            CompositeSettings {
+                layout: CompositeLayout::Stacked,
+                gap: 0,
+                ..CompositeSettings::default()
+            },
+        )
+        .with_region(
+            Box::new(secondary.with_frame_counter(false)),
+            CaptureRect::new(500, 500, 30, 10),
+        );
+
+        engine
+            .start(CaptureRect::new(0, 0, 40, 20), false, None)
+            .unwrap();
+        let frame = engine.get_frame().expect("frame");
+        assert_eq!((frame.width, frame.height), (40, 30));
+        assert_eq!(frame.sequence, 0);
+
+        engine
+            .update_region(CaptureRect::new(5, 5, 60, 20))
+            .unwrap();
+        assert_eq!(engine.get_region(), Some(CaptureRect::new(5, 5, 60, 20)));
+        assert_eq!(engine.regions()[1], CaptureRect::new(500, 500, 30, 10));
+        let frame = engine.get_frame().expect("frame");
+        assert_eq!((frame.width, frame.height), (60, 30));
+        assert_eq!((frame.offset_x, frame.offset_y), (5, 5));
+
+        engine.stop();
+        assert!(engine.get_frame().is_none());
+    }
+}
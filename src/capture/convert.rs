@@ -0,0 +1,586 @@
+// capture/convert.rs - Pixel Format Conversion
+//
+// Single code path for turning a CaptureFrame into the byte layout a consumer
+// needs. Sources may carry arbitrary row padding (`stride`); outputs are always
+// tightly packed.
+//
+// Planar layouts, with `stride` being the luma row pitch:
+// - NV12: Y plane (stride * height), then interleaved UV plane
+//   (stride * ceil(height / 2)).
+// - I420: Y plane (stride * height), then U and V planes, each
+//   ceil(stride / 2) * ceil(height / 2).
+//
+// YUV uses BT.601 limited range, which is what capture APIs hand out by default.
+
+use super::{CaptureFrame, CaptureRect, FramePool, PixelFormat};
+use anyhow::{bail, Result};
+
+/// Check that `len` bytes can hold a `width` x `height` image of `format` with rows `stride` apart
+pub fn validate_layout(
+    format: PixelFormat,
+    width: u32,
+    height: u32,
+    stride: u32,
+    len: usize,
+) -> Result<()> {
+    let min_stride = format.packed_stride(width);
+    if stride < min_stride {
+        bail!(
+            "{:?} stride {} is smaller than the {} bytes needed for width {}",
+            format,
+            stride,
+            min_stride,
+            width
+        );
+    }
+
+    let needed = format.buffer_len(height, stride);
+    if len < needed {
+        bail!(
+            "{:?} {}x{} with stride {} needs {} bytes, got {}",
+            format,
+            width,
+            height,
+            stride,
+            needed,
+            len
+        );
+    }
+    Ok(())
+}
+
+/// Convert `frame` to tightly packed `target` pixels
+pub fn to_packed(frame: &CaptureFrame, target: PixelFormat) -> Result<Vec<u8>> {
+    convert(
+        &frame.data,
+        frame.pixel_format,
+        frame.width,
+        frame.height,
+        frame.stride,
+        target,
+    )
+}
+
+/// Convert `frame` in place to tightly packed `target` pixels
+///
+/// Frames that already match are left untouched, so this is cheap to call on
+/// every frame.
+pub fn convert_frame(frame: &mut CaptureFrame, target: PixelFormat) -> Result<()> {
+    if frame.pixel_format == target && frame.stride == target.packed_stride(frame.width) {
+        validate_layout(
+            target,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+        return Ok(());
+    }
+
+    let packed = to_packed(frame, target)?;
+    FramePool::global().recycle(std::mem::replace(&mut frame.data, packed));
+    frame.pixel_format = target;
+    frame.stride = target.packed_stride(frame.width);
+    Ok(())
+}
+
+/// Convert raw pixels between formats; the result is tightly packed
+pub fn convert(
+    src: &[u8],
+    src_format: PixelFormat,
+    width: u32,
+    height: u32,
+    src_stride: u32,
+    target: PixelFormat,
+) -> Result<Vec<u8>> {
+    validate_layout(src_format, width, height, src_stride, src.len())?;
+
+    let (w, h, stride) = (width as usize, height as usize, src_stride as usize);
+
+    if !src_format.is_planar() && !target.is_planar() {
+        let src_bpp = src_format.bytes_per_pixel() as usize;
+        let dst_bpp = target.bytes_per_pixel() as usize;
+        let mut out = Vec::with_capacity(w * h * dst_bpp);
+        for row in 0..h {
+            let line = &src[row * stride..row * stride + w * src_bpp];
+            if src_format == target {
+                out.extend_from_slice(line);
+                continue;
+            }
+            for px in line.chunks_exact(src_bpp) {
+                out.extend_from_slice(
+                    &encode_pixel(decode_pixel(px, src_format), target)[..dst_bpp],
+                );
+            }
+        }
+        return Ok(out);
+    }
+
+    if src_format == target {
+        return Ok(repack_planar(src, src_format, w, h, stride));
+    }
+
+    let rgba = to_rgba(src, src_format, w, h, stride);
+    Ok(from_rgba(&rgba, target, w, h))
+}
+
+/// Cut the part of `frame` covered by `region`, keeping screen offsets consistent
+///
+/// Planar frames are cropped after conversion to BGRA. `None` when the region
+/// misses the frame or the frame holds too little data for its size.
+pub(crate) fn crop_to_region(frame: &CaptureFrame, region: &CaptureRect) -> Option<CaptureFrame> {
+    if frame.pixel_format.is_planar() {
+        let data = to_packed(frame, PixelFormat::Bgra8).ok()?;
+        let mut packed = CaptureFrame::new(data, frame.width, frame.height)
+            .with_offset(frame.offset_x, frame.offset_y);
+        packed.captured_at = frame.captured_at;
+        packed.sequence = frame.sequence;
+        packed.dropped_frames = frame.dropped_frames;
+        let cropped = crop_to_region(&packed, region);
+        packed.recycle();
+        return cropped;
+    }
+
+    let bounds = CaptureRect::new(frame.offset_x, frame.offset_y, frame.width, frame.height);
+    let area = region.intersect(&bounds)?;
+    let source = area.relative_to(&bounds);
+    let src_x = source.x as usize;
+    let src_y = source.y as usize;
+    let stride = frame.stride as usize;
+    let bpp = frame.pixel_format.bytes_per_pixel() as usize;
+    let row_len = area.width as usize * bpp;
+    // Checked before taking a pooled buffer, which an early return would lose
+    let end = (src_y + area.height as usize - 1) * stride + src_x * bpp + row_len;
+    if frame.data.len() < end {
+        return None;
+    }
+
+    let mut data = FramePool::global().take(row_len * area.height as usize);
+    for (row, dst) in data.chunks_exact_mut(row_len.max(1)).enumerate() {
+        let start = (src_y + row) * stride + src_x * bpp;
+        dst.copy_from_slice(&frame.data[start..start + row_len]);
+    }
+
+    let mut cropped = CaptureFrame::new(data, area.width, area.height)
+        .with_offset(area.x, area.y)
+        .with_pixel_format(frame.pixel_format);
+    cropped.captured_at = frame.captured_at;
+    cropped.sequence = frame.sequence;
+    cropped.dropped_frames = frame.dropped_frames;
+    Some(cropped)
+}
+
+impl PixelFormat {
+    /// Bytes per pixel for packed formats, bytes per luma sample for planar ones
+    pub fn bytes_per_pixel(self) -> u32 {
+        match self {
+            PixelFormat::Bgra8 | PixelFormat::Rgba8 => 4,
+            PixelFormat::Rgb8 => 3,
+            PixelFormat::Nv12 | PixelFormat::I420 => 1,
+        }
+    }
+
+    /// Whether pixels are split across separate luma and chroma planes
+    pub fn is_planar(self) -> bool {
+        matches!(self, PixelFormat::Nv12 | PixelFormat::I420)
+    }
+
+    /// Row pitch of a tightly packed image `width` pixels wide
+    ///
+    /// NV12 rounds up to an even width so the interleaved UV row fits.
+    pub fn packed_stride(self, width: u32) -> u32 {
+        match self {
+            PixelFormat::Nv12 => (width + 1) & !1,
+            _ => width * self.bytes_per_pixel(),
+        }
+    }
+
+    /// Total bytes of a `height`-row image with rows `stride` apart
+    pub fn buffer_len(self, height: u32, stride: u32) -> usize {
+        let luma = stride as usize * height as usize;
+        let chroma_rows = (height as usize + 1) / 2;
+        match self {
+            PixelFormat::Bgra8 | PixelFormat::Rgba8 | PixelFormat::Rgb8 => luma,
+            PixelFormat::Nv12 => luma + stride as usize * chroma_rows,
+            PixelFormat::I420 => luma + 2 * chroma_stride(stride as usize) * chroma_rows,
+        }
+    }
+}
+
+fn chroma_stride(stride: usize) -> usize {
+    (stride + 1) / 2
+}
+
+fn decode_pixel(px: &[u8], format: PixelFormat) -> [u8; 4] {
+    match format {
+        PixelFormat::Bgra8 => [px[2], px[1], px[0], px[3]],
+        PixelFormat::Rgba8 => [px[0], px[1], px[2], px[3]],
+        PixelFormat::Rgb8 => [px[0], px[1], px[2], 255],
+        PixelFormat::Nv12 | PixelFormat::I420 => unreachable!("planar formats are not per-pixel"),
+    }
+}
+
+fn encode_pixel(rgba: [u8; 4], format: PixelFormat) -> [u8; 4] {
+    match format {
+        PixelFormat::Bgra8 => [rgba[2], rgba[1], rgba[0], rgba[3]],
+        PixelFormat::Rgba8 | PixelFormat::Rgb8 => rgba,
+        PixelFormat::Nv12 | PixelFormat::I420 => unreachable!("planar formats are not per-pixel"),
+    }
+}
+
+fn clamp_u8(v: i32) -> u8 {
+    v.clamp(0, 255) as u8
+}
+
+fn yuv_to_rgb(y: u8, u: u8, v: u8) -> [u8; 3] {
+    let c = y as i32 - 16;
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+    [
+        clamp_u8((298 * c + 409 * e + 128) >> 8),
+        clamp_u8((298 * c - 100 * d - 208 * e + 128) >> 8),
+        clamp_u8((298 * c + 516 * d + 128) >> 8),
+    ]
+}
+
+fn rgb_to_y(r: i32, g: i32, b: i32) -> u8 {
+    clamp_u8(((66 * r + 129 * g + 25 * b + 128) >> 8) + 16)
+}
+
+fn rgb_to_uv(r: i32, g: i32, b: i32) -> (u8, u8) {
+    (
+        clamp_u8(((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128),
+        clamp_u8(((112 * r - 94 * g - 18 * b + 128) >> 8) + 128),
+    )
+}
+
+/// Any source to tightly packed RGBA
+fn to_rgba(src: &[u8], format: PixelFormat, w: usize, h: usize, stride: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(w * h * 4);
+    if !format.is_planar() {
+        let bpp = format.bytes_per_pixel() as usize;
+        for row in 0..h {
+            for px in src[row * stride..row * stride + w * bpp].chunks_exact(bpp) {
+                out.extend_from_slice(&decode_pixel(px, format));
+            }
+        }
+        return out;
+    }
+
+    let luma_len = stride * h;
+    let c_stride = chroma_stride(stride);
+    let c_rows = (h + 1) / 2;
+    for row in 0..h {
+        for col in 0..w {
+            let y = src[row * stride + col];
+            let (u, v) = match format {
+                PixelFormat::Nv12 => {
+                    let idx = luma_len + (row / 2) * stride + (col / 2) * 2;
+                    (src[idx], src[idx + 1])
+                }
+                _ => {
+                    let idx = (row / 2) * c_stride + col / 2;
+                    let u_plane = luma_len;
+                    let v_plane = luma_len + c_stride * c_rows;
+                    (src[u_plane + idx], src[v_plane + idx])
+                }
+            };
+            let [r, g, b] = yuv_to_rgb(y, u, v);
+            out.extend_from_slice(&[r, g, b, 255]);
+        }
+    }
+    out
+}
+
+/// Tightly packed RGBA to `target`, tightly packed
+fn from_rgba(rgba: &[u8], target: PixelFormat, w: usize, h: usize) -> Vec<u8> {
+    if !target.is_planar() {
+        let bpp = target.bytes_per_pixel() as usize;
+        let mut out = Vec::with_capacity(w * h * bpp);
+        for px in rgba.chunks_exact(4) {
+            out.extend_from_slice(&encode_pixel([px[0], px[1], px[2], px[3]], target)[..bpp]);
+        }
+        return out;
+    }
+
+    let stride = target.packed_stride(w as u32) as usize;
+    let mut out = vec![0u8; target.buffer_len(h as u32, stride as u32)];
+    let pixel = |x: usize, y: usize| {
+        let i = (y * w + x) * 4;
+        (rgba[i] as i32, rgba[i + 1] as i32, rgba[i + 2] as i32)
+    };
+
+    for y in 0..h {
+        for x in 0..w {
+            let (r, g, b) = pixel(x, y);
+            out[y * stride + x] = rgb_to_y(r, g, b);
+        }
+    }
+
+    let luma_len = stride * h;
+    let c_w = (w + 1) / 2;
+    let c_h = (h + 1) / 2;
+    for cy in 0..c_h {
+        for cx in 0..c_w {
+            // Average the 2x2 block, clipped at odd edges
+            let (mut r, mut g, mut b, mut n) = (0, 0, 0, 0);
+            for y in cy * 2..(cy * 2 + 2).min(h) {
+                for x in cx * 2..(cx * 2 + 2).min(w) {
+                    let p = pixel(x, y);
+                    r += p.0;
+                    g += p.1;
+                    b += p.2;
+                    n += 1;
+                }
+            }
+            let (u, v) = rgb_to_uv(r / n, g / n, b / n);
+            match target {
+                PixelFormat::Nv12 => {
+                    let idx = luma_len + cy * stride + cx * 2;
+                    out[idx] = u;
+                    out[idx + 1] = v;
+                }
+                _ => {
+                    out[luma_len + cy * c_w + cx] = u;
+                    out[luma_len + c_w * c_h + cy * c_w + cx] = v;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Drop row padding from a planar image without changing its format
+fn repack_planar(src: &[u8], format: PixelFormat, w: usize, h: usize, stride: usize) -> Vec<u8> {
+    let c_rows = (h + 1) / 2;
+    let dst_stride = format.packed_stride(w as u32) as usize;
+    let mut out = Vec::with_capacity(format.buffer_len(h as u32, dst_stride as u32));
+    for row in 0..h {
+        out.extend_from_slice(&src[row * stride..row * stride + dst_stride]);
+    }
+
+    let luma_len = stride * h;
+    match format {
+        PixelFormat::Nv12 => {
+            for row in 0..c_rows {
+                let start = luma_len + row * stride;
+                out.extend_from_slice(&src[start..start + dst_stride]);
+            }
+        }
+        _ => {
+            let (c_stride, c_w) = (chroma_stride(stride), chroma_stride(dst_stride));
+            for plane in 0..2 {
+                let base = luma_len + plane * c_stride * c_rows;
+                for row in 0..c_rows {
+                    let start = base + row * c_stride;
+                    out.extend_from_slice(&src[start..start + c_w]);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [PixelFormat; 5] = [
+        PixelFormat::Bgra8,
+        PixelFormat::Rgba8,
+        PixelFormat::Rgb8,
+        PixelFormat::Nv12,
+        PixelFormat::I420,
+    ];
+
+    /// BGRA test image with `pad` junk bytes at the end of every row
+    fn padded_bgra(width: u32, height: u32, pad: u32) -> CaptureFrame {
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&[x as u8 * 10, y as u8 * 20, 200, 255]);
+            }
+            data.extend(std::iter::repeat(0xAB).take(pad as usize));
+        }
+        let mut frame = CaptureFrame::new(data, width, height);
+        frame.stride = width * 4 + pad;
+        frame
+    }
+
+    #[test]
+    fn packed_swizzles_drop_padding() {
+        let frame = padded_bgra(3, 2, 5);
+
+        let rgba = to_packed(&frame, PixelFormat::Rgba8).unwrap();
+        assert_eq!(rgba.len(), 3 * 2 * 4);
+        assert_eq!(&rgba[4..8], &[200, 0, 10, 255]);
+
+        let rgb = to_packed(&frame, PixelFormat::Rgb8).unwrap();
+        assert_eq!(rgb.len(), 3 * 2 * 3);
+        assert_eq!(&rgb[9..12], &[200, 20, 0]);
+
+        let bgra = to_packed(&frame, PixelFormat::Bgra8).unwrap();
+        assert!(!bgra.contains(&0xAB));
+    }
+
+    #[test]
+    fn rgb8_gains_opaque_alpha() {
+        let rgb = convert(
+            &[1, 2, 3, 4, 5, 6],
+            PixelFormat::Rgb8,
+            2,
+            1,
+            6,
+            PixelFormat::Bgra8,
+        )
+        .unwrap();
+        assert_eq!(rgb, vec![3, 2, 1, 255, 6, 5, 4, 255]);
+    }
+
+    #[test]
+    fn yuv_round_trip_is_close() {
+        // Odd dimensions exercise the partial chroma blocks
+        let frame = padded_bgra(5, 3, 3);
+        let reference = to_packed(&frame, PixelFormat::Rgba8).unwrap();
+
+        for format in [PixelFormat::Nv12, PixelFormat::I420] {
+            let stride = format.packed_stride(5);
+            let yuv = to_packed(&frame, format).unwrap();
+            assert_eq!(yuv.len(), format.buffer_len(3, stride));
+
+            let back = convert(&yuv, format, 5, 3, stride, PixelFormat::Rgba8).unwrap();
+            for (a, b) in back.iter().zip(&reference) {
+                // Chroma is shared by 2x2 blocks, so neighbours bleed a little
+                assert!(
+                    (*a as i32 - *b as i32).abs() <= 40,
+                    "{:?}: {} vs {}",
+                    format,
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn yuv_grey_is_exact_enough() {
+        let grey = CaptureFrame::new([128u8, 128, 128, 255].repeat(4), 2, 2);
+        let nv12 = to_packed(&grey, PixelFormat::Nv12).unwrap();
+        assert_eq!(&nv12[4..], &[128, 128]);
+
+        let back = convert(&nv12, PixelFormat::Nv12, 2, 2, 2, PixelFormat::Rgba8).unwrap();
+        for px in back.chunks_exact(4) {
+            assert!(px[..3].iter().all(|c| (*c as i32 - 128).abs() <= 2));
+            assert_eq!(px[3], 255);
+        }
+    }
+
+    #[test]
+    fn planar_padding_is_removed() {
+        // 2x2 NV12 with stride 4: Y rows then one UV row
+        let src = [
+            16, 16, 0xAB, 0xAB, 235, 235, 0xAB, 0xAB, 128, 128, 0xAB, 0xAB,
+        ];
+        let packed = convert(&src, PixelFormat::Nv12, 2, 2, 4, PixelFormat::Nv12).unwrap();
+        assert_eq!(packed, vec![16, 16, 235, 235, 128, 128]);
+
+        // 2x2 I420 with stride 4: chroma stride is 2
+        let src = [1, 2, 0, 0, 3, 4, 0, 0, 5, 0, 6, 0];
+        let packed = convert(&src, PixelFormat::I420, 2, 2, 4, PixelFormat::I420).unwrap();
+        assert_eq!(packed, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn every_pair_converts() {
+        let frame = padded_bgra(4, 4, 8);
+        for from in ALL {
+            let src = to_packed(&frame, from).unwrap();
+            let stride = from.packed_stride(4);
+            for to in ALL {
+                let out = convert(&src, from, 4, 4, stride, to).unwrap();
+                assert_eq!(
+                    out.len(),
+                    to.buffer_len(4, to.packed_stride(4)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn convert_frame_updates_metadata_and_skips_noop() {
+        let mut frame = padded_bgra(3, 3, 4);
+        convert_frame(&mut frame, PixelFormat::Rgba8).unwrap();
+        assert_eq!(frame.pixel_format, PixelFormat::Rgba8);
+        assert_eq!(frame.stride, 12);
+        assert_eq!(frame.data.len(), 36);
+
+        let ptr = frame.data.as_ptr();
+        convert_frame(&mut frame, PixelFormat::Rgba8).unwrap();
+        assert_eq!(frame.data.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn rejects_bad_layouts() {
+        assert!(convert(&[0; 16], PixelFormat::Bgra8, 2, 2, 4, PixelFormat::Rgba8).is_err());
+        assert!(convert(&[0; 15], PixelFormat::Bgra8, 2, 2, 8, PixelFormat::Rgba8).is_err());
+        assert!(convert(&[0; 5], PixelFormat::Nv12, 2, 2, 2, PixelFormat::Rgba8).is_err());
+        assert!(convert(&[0; 6], PixelFormat::I420, 2, 2, 2, PixelFormat::Rgba8).is_ok());
+    }
+
+    fn numbered_frame(width: u32, height: u32) -> CaptureFrame {
+        let data = (0..width * height)
+            .flat_map(|i| [i as u8, (i >> 8) as u8, 0, 255])
+            .collect();
+        CaptureFrame::new(data, width, height)
+    }
+
+    #[test]
+    fn crop_clips_to_frame_and_keeps_offsets() {
+        let frame = numbered_frame(10, 10).with_offset(100, 50);
+        let cropped = crop_to_region(&frame, &CaptureRect::new(95, 55, 8, 100)).unwrap();
+
+        assert_eq!((cropped.width, cropped.height), (3, 5));
+        assert_eq!((cropped.offset_x, cropped.offset_y), (100, 55));
+        assert_eq!(cropped.stride, 12);
+        // First pixel comes from (0, 5) in the source
+        assert_eq!(cropped.data[0], 50);
+    }
+
+    #[test]
+    fn crop_keeps_packed_format_and_converts_planar() {
+        let rgb = CaptureFrame::new((0..4 * 2 * 3).map(|v| v as u8).collect(), 4, 2)
+            .with_pixel_format(PixelFormat::Rgb8);
+        let cropped = crop_to_region(&rgb, &CaptureRect::new(1, 1, 2, 1)).unwrap();
+        assert_eq!(cropped.pixel_format, PixelFormat::Rgb8);
+        assert_eq!(cropped.data, vec![15, 16, 17, 18, 19, 20]);
+
+        let nv12 =
+            CaptureFrame::new(vec![128u8; 4 * 2 + 4], 4, 2).with_pixel_format(PixelFormat::Nv12);
+        let cropped = crop_to_region(&nv12, &CaptureRect::new(0, 0, 2, 2)).unwrap();
+        assert_eq!(cropped.pixel_format, PixelFormat::Bgra8);
+        assert_eq!(cropped.data.len(), 2 * 2 * 4);
+    }
+
+    #[test]
+    fn crop_outside_frame_is_none() {
+        let frame = numbered_frame(10, 10);
+        assert!(crop_to_region(&frame, &CaptureRect::new(10, 0, 5, 5)).is_none());
+        assert!(crop_to_region(&frame, &CaptureRect::new(-5, -5, 5, 20)).is_none());
+    }
+
+    #[test]
+    fn crop_keeps_counters_and_rejects_short_data() {
+        let mut frame = numbered_frame(4, 4);
+        frame.sequence = 7;
+        frame.dropped_frames = 2;
+        let cropped = crop_to_region(&frame, &CaptureRect::new(1, 1, 2, 2)).unwrap();
+        assert_eq!((cropped.sequence, cropped.dropped_frames), (7, 2));
+
+        frame.data.truncate(4 * 4 * 3);
+        assert!(crop_to_region(&frame, &CaptureRect::new(0, 2, 4, 2)).is_none());
+        assert!(crop_to_region(&frame, &CaptureRect::new(0, 0, 4, 3)).is_some());
+    }
+}
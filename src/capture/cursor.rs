@@ -0,0 +1,540 @@
+// capture/cursor.rs - Software Cursor Compositor
+//
+// Backends that capture without the cursor (X11 root images, CoreGraphics,
+// GDI copies) fetch the OS cursor bitmap and its hotspot, then hand both to a
+// CursorCompositor together with the pointer position. Scaling for
+// presentations, the highlight halo and hiding an idle pointer are therefore
+// the same on every platform; a backend only has to know how to read its cursor.
+
+use super::clicks::{draw_mark, ClickHighlightStyle, ClickMark};
+use super::overlay::blend_rgba;
+use super::{convert, CaptureFrame, CaptureRect, PixelFormat};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How long an idle cursor takes to fade out once `hide_after_ms` has passed
+const HIDE_FADE: Duration = Duration::from_millis(250);
+
+/// Cursor appearance, shared by every backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CursorStyle {
+    /// Size multiplier applied to the OS cursor bitmap
+    pub scale: f32,
+    /// Draw a soft circle behind the cursor
+    pub halo: bool,
+    /// Halo color as [R, G, B, A]
+    pub halo_color: [u8; 4],
+    /// Halo radius in frame pixels
+    pub halo_radius: u32,
+    /// Hide the cursor after it has not moved for this long; 0 keeps it visible
+    pub hide_after_ms: u64,
+}
+
+impl Default for CursorStyle {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            halo: false,
+            halo_color: [255, 255, 0, 80],
+            halo_radius: 28,
+            hide_after_ms: 0,
+        }
+    }
+}
+
+/// Cursor bitmap as straight-alpha RGBA with its hotspot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorImage {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// Pixel of the bitmap that sits on the pointer position
+    pub hotspot_x: i32,
+    pub hotspot_y: i32,
+}
+
+/// Arrow sprite: 'X' = outline, '#' = fill, '.' = transparent
+const ARROW: [&str; 17] = [
+    "X..........",
+    "XX.........",
+    "X#X........",
+    "X##X.......",
+    "X###X......",
+    "X####X.....",
+    "X#####X....",
+    "X######X...",
+    "X#######X..",
+    "X########X.",
+    "X#####XXXXX",
+    "X##X##X....",
+    "X#X.X##X...",
+    "XX..X##X...",
+    "X....X##X..",
+    ".....X##X..",
+    "......XX...",
+];
+
+impl CursorImage {
+    pub fn new(data: Vec<u8>, width: u32, height: u32, hotspot: (i32, i32)) -> Result<Self> {
+        if data.len() != width as usize * height as usize * 4 {
+            return Err(anyhow!(
+                "Cursor bitmap is {} bytes, expected {}x{} RGBA",
+                data.len(),
+                width,
+                height
+            ));
+        }
+        Ok(Self {
+            data,
+            width,
+            height,
+            hotspot_x: hotspot.0,
+            hotspot_y: hotspot.1,
+        })
+    }
+
+    /// From premultiplied RGBA, as CoreGraphics and XFixes hand it out
+    pub fn from_premultiplied(
+        mut data: Vec<u8>,
+        width: u32,
+        height: u32,
+        hotspot: (i32, i32),
+    ) -> Result<Self> {
+        for px in data.chunks_exact_mut(4) {
+            let alpha = px[3] as u32;
+            if alpha > 0 && alpha < 255 {
+                for channel in &mut px[..3] {
+                    *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+                }
+            }
+        }
+        Self::new(data, width, height, hotspot)
+    }
+
+    /// From BGRA renders of the cursor over black and over white, as GDI draws it
+    ///
+    /// Over black a pixel is color * alpha and its lift over white is 1 - alpha.
+    /// Pixels that come out darker over white invert the screen (the I-beam);
+    /// they become black with a white outline so they show on any background.
+    pub fn from_renders(
+        on_black: &[u8],
+        on_white: &[u8],
+        width: u32,
+        height: u32,
+        hotspot: (i32, i32),
+    ) -> Result<Self> {
+        if on_black.len() != on_white.len() {
+            return Err(anyhow!(
+                "Cursor renders differ in size: {} and {} bytes",
+                on_black.len(),
+                on_white.len()
+            ));
+        }
+        let mut data = Vec::with_capacity(on_black.len());
+        let mut inverted = Vec::with_capacity(on_black.len() / 4);
+        for (black, white) in on_black.chunks_exact(4).zip(on_white.chunks_exact(4)) {
+            let invert = white[1] < black[1];
+            inverted.push(invert);
+            if invert {
+                data.extend_from_slice(&[0, 0, 0, 255]);
+            } else {
+                let lift = white[1] - black[1];
+                data.extend_from_slice(&[black[2], black[1], black[0], 255 - lift]);
+            }
+        }
+
+        let (w, h) = (width as usize, height as usize);
+        if inverted.len() == w * h {
+            for y in 0..h {
+                for x in 0..w {
+                    let idx = y * w + x;
+                    if inverted[idx] || data[idx * 4 + 3] != 0 {
+                        continue;
+                    }
+                    let near_inverted = (y.saturating_sub(1)..(y + 2).min(h)).any(|ny| {
+                        (x.saturating_sub(1)..(x + 2).min(w)).any(|nx| inverted[ny * w + nx])
+                    });
+                    if near_inverted {
+                        data[idx * 4..idx * 4 + 4].copy_from_slice(&[255, 255, 255, 255]);
+                    }
+                }
+            }
+        }
+        Self::from_premultiplied(data, width, height, hotspot)
+    }
+
+    /// Built-in black and white arrow for backends that cannot read the OS cursor
+    pub fn arrow() -> Self {
+        let width = ARROW[0].len() as u32;
+        let height = ARROW.len() as u32;
+        let mut data = vec![0; (width * height * 4) as usize];
+        for (y, row) in ARROW.iter().enumerate() {
+            for (x, cell) in row.bytes().enumerate() {
+                let value = match cell {
+                    b'X' => 0,
+                    b'#' => 255,
+                    _ => continue,
+                };
+                let offset = (y * width as usize + x) * 4;
+                data[offset..offset + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        Self {
+            data,
+            width,
+            height,
+            hotspot_x: 0,
+            hotspot_y: 0,
+        }
+    }
+
+    /// Bilinear resize by `scale`, keeping the hotspot on the same spot
+    pub fn scaled(&self, scale: f32) -> Self {
+        let width = ((self.width as f32 * scale).round() as u32).max(1);
+        let height = ((self.height as f32 * scale).round() as u32).max(1);
+        let mut data = vec![0; (width * height * 4) as usize];
+
+        // Interpolate premultiplied values so transparent pixels don't darken the edges
+        let sample = |x: i64, y: i64| -> [f32; 4] {
+            let x = x.clamp(0, self.width as i64 - 1);
+            let y = y.clamp(0, self.height as i64 - 1);
+            let px = &self.data[((y * self.width as i64 + x) * 4) as usize..][..4];
+            let alpha = px[3] as f32 / 255.0;
+            [
+                px[0] as f32 * alpha,
+                px[1] as f32 * alpha,
+                px[2] as f32 * alpha,
+                px[3] as f32,
+            ]
+        };
+        let (sx, sy) = (
+            self.width as f32 / width as f32,
+            self.height as f32 / height as f32,
+        );
+        for y in 0..height {
+            let fy = (y as f32 + 0.5) * sy - 0.5;
+            let (y0, ty) = (fy.floor() as i64, fy - fy.floor());
+            for x in 0..width {
+                let fx = (x as f32 + 0.5) * sx - 0.5;
+                let (x0, tx) = (fx.floor() as i64, fx - fx.floor());
+                let corners = [
+                    (sample(x0, y0), (1.0 - tx) * (1.0 - ty)),
+                    (sample(x0 + 1, y0), tx * (1.0 - ty)),
+                    (sample(x0, y0 + 1), (1.0 - tx) * ty),
+                    (sample(x0 + 1, y0 + 1), tx * ty),
+                ];
+                let mut acc = [0f32; 4];
+                for (value, weight) in corners {
+                    acc.iter_mut()
+                        .zip(value)
+                        .for_each(|(a, v)| *a += v * weight);
+                }
+                let offset = ((y * width + x) * 4) as usize;
+                let alpha = acc[3] / 255.0;
+                for c in 0..3 {
+                    let straight = if alpha > 0.0 { acc[c] / alpha } else { 0.0 };
+                    data[offset + c] = straight.round().clamp(0.0, 255.0) as u8;
+                }
+                data[offset + 3] = acc[3].round().clamp(0.0, 255.0) as u8;
+            }
+        }
+
+        Self {
+            data,
+            width,
+            height,
+            hotspot_x: (self.hotspot_x as f32 * scale).round() as i32,
+            hotspot_y: (self.hotspot_y as f32 * scale).round() as i32,
+        }
+    }
+}
+
+/// Blends a cursor bitmap onto frames
+#[derive(Debug, Clone)]
+pub struct CursorCompositor {
+    style: CursorStyle,
+    last_position: Option<(i32, i32)>,
+    last_moved: Instant,
+    /// Last source bitmap and its scaled copy
+    scaled: Option<(CursorImage, CursorImage)>,
+}
+
+impl Default for CursorCompositor {
+    fn default() -> Self {
+        Self::new(CursorStyle::default())
+    }
+}
+
+impl CursorCompositor {
+    pub fn new(style: CursorStyle) -> Self {
+        Self {
+            style,
+            last_position: None,
+            last_moved: Instant::now(),
+            scaled: None,
+        }
+    }
+
+    pub fn style(&self) -> &CursorStyle {
+        &self.style
+    }
+
+    pub fn set_style(&mut self, style: CursorStyle) {
+        self.style = style;
+        self.scaled = None;
+    }
+
+    /// Record the pointer position; any change counts as activity
+    fn track(&mut self, position: (i32, i32), now: Instant) {
+        if self.last_position != Some(position) {
+            self.last_position = Some(position);
+            self.last_moved = now;
+        }
+    }
+
+    /// Cursor opacity at `now`, 0.0 once auto-hide has faded it out
+    pub fn visibility(&self, now: Instant) -> f32 {
+        if self.style.hide_after_ms == 0 {
+            return 1.0;
+        }
+        let idle = now.saturating_duration_since(self.last_moved);
+        let hide_after = Duration::from_millis(self.style.hide_after_ms);
+        if idle <= hide_after {
+            return 1.0;
+        }
+        let fading = (idle - hide_after).as_secs_f32() / HIDE_FADE.as_secs_f32();
+        (1.0 - fading).max(0.0)
+    }
+
+    fn image_for(&mut self, image: &CursorImage) -> CursorImage {
+        let scale = self.style.scale;
+        if scale <= 0.0 || (scale - 1.0).abs() < f32::EPSILON {
+            return image.clone();
+        }
+        match &self.scaled {
+            Some((source, scaled)) if source == image => scaled.clone(),
+            _ => {
+                let scaled = image.scaled(scale);
+                self.scaled = Some((image.clone(), scaled.clone()));
+                scaled
+            }
+        }
+    }
+
+    /// Draw `image` with its hotspot at (x, y) in frame pixels
+    ///
+    /// Packed frames are drawn in place; planar frames are converted to BGRA first.
+    /// Returns the frame-relative area that was painted, so backends can add it
+    /// to the frame's damage.
+    pub fn draw(
+        &mut self,
+        frame: &mut CaptureFrame,
+        image: &CursorImage,
+        x: i32,
+        y: i32,
+        now: Instant,
+    ) -> Result<Option<CaptureRect>> {
+        self.track((x, y), now);
+        let visibility = self.visibility(now);
+        if visibility <= 0.0 || frame.data.is_empty() {
+            return Ok(None);
+        }
+        if frame.pixel_format.is_planar() {
+            convert::convert_frame(frame, PixelFormat::Bgra8)?;
+        }
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+
+        if self.style.halo && self.style.halo_radius > 0 {
+            draw_mark(
+                frame,
+                &ClickMark {
+                    x: x as f32,
+                    y: y as f32,
+                    radius: self.style.halo_radius as f32,
+                    color: self.style.halo_color,
+                    fade: visibility,
+                    style: ClickHighlightStyle::Filled,
+                },
+            );
+        }
+
+        let image = self.image_for(image);
+        let (left, top) = (
+            x as i64 - image.hotspot_x as i64,
+            y as i64 - image.hotspot_y as i64,
+        );
+        blend_rgba(
+            frame,
+            &image.data,
+            (image.width, image.height),
+            (left, top),
+            visibility,
+        );
+
+        let mut bounds = CaptureRect::new(x, y, image.width, image.height)
+            .offset(image.hotspot_x.saturating_neg(), image.hotspot_y.saturating_neg());
+        if self.style.halo {
+            let halo = CaptureRect::new(x, y, 1, 1).inset(-(self.style.halo_radius as i32));
+            bounds = bounds.union(&halo);
+        }
+        Ok(bounds.intersect(&CaptureRect::new(0, 0, frame.width, frame.height)))
+    }
+
+    /// Draw at a screen position in the same space as the frame's offsets
+    ///
+    /// Suits backends whose offsets are in frame pixels (GDI, X11); macOS frames
+    /// use points for their offsets and should translate and call `draw`.
+    pub fn draw_screen(
+        &mut self,
+        frame: &mut CaptureFrame,
+        image: &CursorImage,
+        screen_x: i32,
+        screen_y: i32,
+        now: Instant,
+    ) -> Result<Option<CaptureRect>> {
+        let (x, y) = (screen_x - frame.offset_x, screen_y - frame.offset_y);
+        self.draw(frame, image, x, y, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(size: u32) -> CaptureFrame {
+        CaptureFrame::new(vec![128; (size * size * 4) as usize], size, size)
+    }
+
+    fn bgra(frame: &CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+        let offset = (y * frame.stride + x * 4) as usize;
+        frame.data[offset..offset + 4].try_into().unwrap()
+    }
+
+    /// 2x2 opaque red cursor with the hotspot on its bottom-right pixel
+    fn red_block() -> CursorImage {
+        CursorImage::new([255, 0, 0, 255].repeat(4), 2, 2, (1, 1)).unwrap()
+    }
+
+    #[test]
+    fn hotspot_lands_on_pointer_position() {
+        let mut frame = gray(8).with_offset(100, 200);
+        let mut compositor = CursorCompositor::default();
+        let painted = compositor
+            .draw_screen(&mut frame, &red_block(), 104, 203, Instant::now())
+            .unwrap();
+        assert_eq!(painted, Some(CaptureRect::new(3, 2, 2, 2)));
+        for (x, y) in [(3, 2), (4, 2), (3, 3), (4, 3)] {
+            assert_eq!(bgra(&frame, x, y), [0, 0, 255, 128], "({}, {})", x, y);
+        }
+        assert_eq!(bgra(&frame, 5, 3), [128; 4]);
+        assert_eq!(bgra(&frame, 2, 3), [128; 4]);
+    }
+
+    #[test]
+    fn scaling_grows_bitmap_and_hotspot() {
+        let mut compositor = CursorCompositor::new(CursorStyle {
+            scale: 2.0,
+            ..CursorStyle::default()
+        });
+        let mut frame = gray(10);
+        compositor
+            .draw(&mut frame, &red_block(), 5, 5, Instant::now())
+            .unwrap();
+        // 4x4 block whose hotspot (2, 2) sits at (5, 5)
+        assert_eq!(bgra(&frame, 3, 3), [0, 0, 255, 128]);
+        assert_eq!(bgra(&frame, 6, 6), [0, 0, 255, 128]);
+        assert_eq!(bgra(&frame, 7, 7), [128; 4]);
+        assert_eq!(bgra(&frame, 2, 2), [128; 4]);
+    }
+
+    #[test]
+    fn halo_surrounds_cursor() {
+        let mut compositor = CursorCompositor::new(CursorStyle {
+            halo: true,
+            halo_color: [0, 0, 255, 255],
+            halo_radius: 10,
+            ..CursorStyle::default()
+        });
+        let mut frame = gray(32);
+        compositor
+            .draw(&mut frame, &red_block(), 16, 16, Instant::now())
+            .unwrap();
+        // Blue halo around, red cursor on top, untouched far away
+        assert_eq!(bgra(&frame, 20, 16), [255, 0, 0, 128]);
+        assert_eq!(bgra(&frame, 16, 16), [0, 0, 255, 128]);
+        assert_eq!(bgra(&frame, 0, 0), [128; 4]);
+    }
+
+    #[test]
+    fn idle_cursor_fades_out() {
+        let mut compositor = CursorCompositor::new(CursorStyle {
+            hide_after_ms: 1000,
+            ..CursorStyle::default()
+        });
+        let start = Instant::now();
+        let mut frame = gray(8);
+        compositor
+            .draw(&mut frame, &red_block(), 4, 4, start)
+            .unwrap();
+
+        assert_eq!(
+            compositor.visibility(start + Duration::from_millis(900)),
+            1.0
+        );
+        let fading = compositor.visibility(start + Duration::from_millis(1125));
+        assert!(fading > 0.4 && fading < 0.6, "fading {}", fading);
+
+        let hidden = start + Duration::from_secs(2);
+        let mut frame = gray(8);
+        compositor
+            .draw(&mut frame, &red_block(), 4, 4, hidden)
+            .unwrap();
+        assert_eq!(frame.data, gray(8).data);
+
+        // Moving brings it back
+        compositor
+            .draw(&mut frame, &red_block(), 5, 4, hidden)
+            .unwrap();
+        assert_eq!(bgra(&frame, 5, 4), [0, 0, 255, 128]);
+    }
+
+    #[test]
+    fn premultiplied_input_is_unpremultiplied() {
+        let image = CursorImage::from_premultiplied(vec![64, 0, 0, 128], 1, 1, (0, 0)).unwrap();
+        assert_eq!(image.data, vec![128, 0, 0, 128]);
+        assert!(CursorImage::new(vec![0; 3], 1, 1, (0, 0)).is_err());
+    }
+
+    #[test]
+    fn inverting_cursor_pixels_get_an_outline() {
+        // 3x1: transparent, inverting (white over black, black over white), red
+        let on_black = [0, 0, 0, 255, 255, 255, 255, 255, 0, 0, 255, 255];
+        let on_white = [255, 255, 255, 255, 0, 0, 0, 255, 0, 0, 255, 255];
+        let image = CursorImage::from_renders(&on_black, &on_white, 3, 1, (1, 0)).unwrap();
+        assert_eq!(&image.data[0..4], &[255, 255, 255, 255]);
+        assert_eq!(&image.data[4..8], &[0, 0, 0, 255]);
+        assert_eq!(&image.data[8..12], &[255, 0, 0, 255]);
+
+        let far = CursorImage::from_renders(&[0; 8], &[255; 8], 2, 1, (0, 0)).unwrap();
+        assert!(far.data.chunks_exact(4).all(|px| px[3] == 0));
+        assert!(CursorImage::from_renders(&[0; 8], &[0; 4], 2, 1, (0, 0)).is_err());
+    }
+
+    #[test]
+    fn arrow_has_outline_and_fill() {
+        let arrow = CursorImage::arrow();
+        assert_eq!((arrow.width, arrow.height), (11, 17));
+        let px = |x: u32, y: u32| &arrow.data[((y * arrow.width + x) * 4) as usize..][..4];
+        assert_eq!(px(0, 0), &[0, 0, 0, 255]);
+        assert_eq!(px(1, 5), &[255, 255, 255, 255]);
+        assert_eq!(px(10, 0)[3], 0);
+    }
+}
@@ -0,0 +1,326 @@
+// capture/damage.rs - Dirty Rectangle Tracking
+//
+// Backends that know what changed (X11 Damage) fill `CaptureFrame::damage`
+// themselves. For everything else TileDiffer compares each frame against the
+// previous one tile by tile and reports the changed tiles, merged into as few
+// rectangles as practical. Only a 64-bit hash per tile is kept between frames.
+
+use super::{CaptureFrame, CaptureRect, PixelFormat};
+
+/// Default tile edge in pixels
+pub const DEFAULT_TILE_SIZE: u32 = 64;
+
+/// CPU fallback that derives `CaptureFrame::damage` by diffing tiles
+#[derive(Debug, Clone)]
+pub struct TileDiffer {
+    tile_size: u32,
+    previous: Option<TileHashes>,
+}
+
+#[derive(Debug, Clone)]
+struct TileHashes {
+    width: u32,
+    height: u32,
+    pixel_format: PixelFormat,
+    hashes: Vec<u64>,
+}
+
+impl Default for TileDiffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TILE_SIZE)
+    }
+}
+
+impl TileDiffer {
+    pub fn new(tile_size: u32) -> Self {
+        Self {
+            tile_size: tile_size.max(1),
+            previous: None,
+        }
+    }
+
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    /// Forget the previous frame, so the next one is reported as fully changed
+    pub fn reset(&mut self) {
+        self.previous = None;
+    }
+
+    /// Fill in `frame.damage` unless the backend already did
+    pub fn annotate(&mut self, frame: &mut CaptureFrame) {
+        if frame.damage.is_some() {
+            // Native damage and tile hashes would drift apart
+            self.reset();
+            return;
+        }
+        frame.damage = Some(self.diff(frame));
+    }
+
+    /// Rectangles that changed since the last frame passed to `diff`
+    ///
+    /// The first frame, a size or format change, and planar frames report the
+    /// whole frame as changed.
+    pub fn diff(&mut self, frame: &CaptureFrame) -> Vec<CaptureRect> {
+        let full = vec![CaptureRect::new(0, 0, frame.width, frame.height)];
+        if frame.width == 0 || frame.height == 0 {
+            self.reset();
+            return Vec::new();
+        }
+        if frame.pixel_format.is_planar()
+            || super::convert::validate_layout(
+                frame.pixel_format,
+                frame.width,
+                frame.height,
+                frame.stride,
+                frame.data.len(),
+            )
+            .is_err()
+        {
+            self.reset();
+            return full;
+        }
+
+        let cols = (frame.width + self.tile_size - 1) / self.tile_size;
+        let rows = (frame.height + self.tile_size - 1) / self.tile_size;
+        let hashes = self.hash_tiles(frame, cols, rows);
+
+        let dirty: Vec<bool> = match &self.previous {
+            Some(prev)
+                if prev.width == frame.width
+                    && prev.height == frame.height
+                    && prev.pixel_format == frame.pixel_format =>
+            {
+                prev.hashes
+                    .iter()
+                    .zip(&hashes)
+                    .map(|(old, new)| old != new)
+                    .collect()
+            }
+            _ => {
+                self.previous = Some(TileHashes {
+                    width: frame.width,
+                    height: frame.height,
+                    pixel_format: frame.pixel_format,
+                    hashes,
+                });
+                return full;
+            }
+        };
+
+        if let Some(prev) = &mut self.previous {
+            prev.hashes = hashes;
+        }
+        self.merge_tiles(&dirty, cols, rows, frame.width, frame.height)
+    }
+
+    fn hash_tiles(&self, frame: &CaptureFrame, cols: u32, rows: u32) -> Vec<u64> {
+        let bpp = frame.pixel_format.bytes_per_pixel() as usize;
+        let tile_bytes = self.tile_size as usize * bpp;
+        let row_bytes = frame.width as usize * bpp;
+        let mut hashes = vec![0xcbf2_9ce4_8422_2325u64; (cols * rows) as usize];
+
+        for y in 0..frame.height as usize {
+            let start = y * frame.stride as usize;
+            let row = &frame.data[start..start + row_bytes];
+            let tile_row = (y / self.tile_size as usize) * cols as usize;
+            for (col, chunk) in row.chunks(tile_bytes).enumerate() {
+                let hash = &mut hashes[tile_row + col];
+                *hash = hash_bytes(*hash, chunk);
+            }
+        }
+        hashes
+    }
+
+    /// Join dirty tiles into horizontal runs, then stack runs with the same span
+    fn merge_tiles(
+        &self,
+        dirty: &[bool],
+        cols: u32,
+        rows: u32,
+        width: u32,
+        height: u32,
+    ) -> Vec<CaptureRect> {
+        let tile = self.tile_size;
+        let mut rects: Vec<CaptureRect> = Vec::new();
+        // Rectangles that end on the previous tile row and may grow downwards
+        let mut open: Vec<usize> = Vec::new();
+
+        for row in 0..rows {
+            let y = row * tile;
+            let h = tile.min(height - y);
+            let mut next_open = Vec::new();
+
+            let mut col = 0;
+            while col < cols {
+                if !dirty[(row * cols + col) as usize] {
+                    col += 1;
+                    continue;
+                }
+                let first = col;
+                while col < cols && dirty[(row * cols + col) as usize] {
+                    col += 1;
+                }
+                let x = first * tile;
+                let w = (col * tile).min(width) - x;
+
+                let extend = open.iter().copied().find(|&index| {
+                    let rect = &rects[index];
+                    rect.x == x as i32 && rect.width == w
+                });
+                match extend {
+                    Some(index) => {
+                        rects[index].height += h;
+                        next_open.push(index);
+                    }
+                    None => {
+                        rects.push(CaptureRect::new(x as i32, y as i32, w, h));
+                        next_open.push(rects.len() - 1);
+                    }
+                }
+            }
+            open = next_open;
+        }
+        rects
+    }
+}
+
+/// FNV-1a over 8-byte words, with a byte-wise tail
+fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut words = bytes.chunks_exact(8);
+    for word in &mut words {
+        hash ^= u64::from_le_bytes(word.try_into().unwrap());
+        hash = hash.wrapping_mul(PRIME);
+    }
+    for &byte in words.remainder() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(PRIME);
+    }
+    hash
+}
+
+/// Clip screen-space damage to `region` and translate it to frame coordinates
+pub fn to_frame_damage(
+    screen_rects: impl IntoIterator<Item = CaptureRect>,
+    region: &CaptureRect,
+) -> Vec<CaptureRect> {
+    screen_rects
+        .into_iter()
+        .filter_map(|rect| rect.intersect(region))
+        .map(|rect| rect.relative_to(region))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32) -> CaptureFrame {
+        CaptureFrame::new(vec![0; (width * height * 4) as usize], width, height)
+    }
+
+    fn paint(frame: &mut CaptureFrame, x: u32, y: u32) {
+        let offset = (y * frame.stride + x * 4) as usize;
+        frame.data[offset] = 0xFF;
+    }
+
+    #[test]
+    fn first_frame_and_resize_are_fully_damaged() {
+        let mut differ = TileDiffer::new(16);
+        assert_eq!(
+            differ.diff(&solid(40, 20)),
+            vec![CaptureRect::new(0, 0, 40, 20)]
+        );
+        assert!(differ.diff(&solid(40, 20)).is_empty());
+        assert_eq!(
+            differ.diff(&solid(30, 20)),
+            vec![CaptureRect::new(0, 0, 30, 20)]
+        );
+    }
+
+    #[test]
+    fn reports_changed_tiles_clipped_to_frame() {
+        let mut differ = TileDiffer::new(16);
+        differ.diff(&solid(40, 40));
+
+        let mut frame = solid(40, 40);
+        paint(&mut frame, 1, 1);
+        paint(&mut frame, 39, 39);
+        assert_eq!(
+            differ.diff(&frame),
+            vec![
+                CaptureRect::new(0, 0, 16, 16),
+                // Edge tile is only 8 pixels wide and tall
+                CaptureRect::new(32, 32, 8, 8),
+            ]
+        );
+        // Unchanged since the last call
+        assert!(differ.diff(&frame).is_empty());
+    }
+
+    #[test]
+    fn merges_adjacent_tiles() {
+        let mut differ = TileDiffer::new(8);
+        differ.diff(&solid(32, 32));
+
+        // A 2x2 block of tiles plus a separate run below it
+        let mut frame = solid(32, 32);
+        for (x, y) in [(0, 0), (8, 0), (0, 8), (8, 8), (16, 24), (24, 24)] {
+            paint(&mut frame, x, y);
+        }
+        assert_eq!(
+            differ.diff(&frame),
+            vec![
+                CaptureRect::new(0, 0, 16, 16),
+                CaptureRect::new(16, 24, 16, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn padding_bytes_are_ignored() {
+        let mut differ = TileDiffer::new(8);
+        let mut frame = solid(4, 4);
+        frame.stride = 32;
+        frame.data = vec![0; 32 * 4];
+        differ.diff(&frame);
+
+        frame.data[20] = 0xAB; // inside the row padding
+        assert!(differ.diff(&frame).is_empty());
+    }
+
+    #[test]
+    fn annotate_keeps_native_damage() {
+        let mut differ = TileDiffer::new(8);
+        let mut frame = solid(8, 8);
+        frame.damage = Some(vec![CaptureRect::new(1, 1, 2, 2)]);
+        differ.annotate(&mut frame);
+        assert_eq!(frame.damage, Some(vec![CaptureRect::new(1, 1, 2, 2)]));
+
+        let mut frame = solid(8, 8);
+        differ.annotate(&mut frame);
+        assert_eq!(frame.damage, Some(vec![CaptureRect::new(0, 0, 8, 8)]));
+    }
+
+    #[test]
+    fn screen_damage_is_translated_to_frame() {
+        let region = CaptureRect::new(100, 50, 40, 30);
+        let damage = to_frame_damage(
+            [
+                CaptureRect::new(90, 60, 20, 5),   // straddles the left edge
+                CaptureRect::new(0, 0, 10, 10),    // outside
+                CaptureRect::new(130, 70, 50, 50), // straddles bottom-right
+            ],
+            &region,
+        );
+        assert_eq!(
+            damage,
+            vec![
+                CaptureRect::new(0, 10, 10, 5),
+                CaptureRect::new(30, 20, 10, 10)
+            ]
+        );
+    }
+}
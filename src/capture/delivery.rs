@@ -0,0 +1,761 @@
+// capture/delivery.rs - Push-Based Frame Delivery
+//
+// Polling `get_frame` from the render loop means the render thread and the border
+// callbacks (which call `update_region`) fight over the same engine lock. A
+// FramePump moves the polling onto its own thread, hands every frame to a
+// FrameSink as soon as it is captured and applies region changes between frames,
+// so callers never have to take the engine lock themselves. Ticks are scheduled
+// by a FramePacer, so capture time doesn't stretch the interval.
+
+use super::broadcast::FrameBroadcaster;
+use super::damage::TileDiffer;
+use super::metrics::{PipelineStage, SharedCaptureMetrics};
+use super::pacing::{FramePacer, PacingSettings, PacingStats};
+use super::pipeline::FramePipeline;
+use super::{CaptureEngine, CaptureFrame, CaptureRect};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Engine slot shared between the app, the pump and border callbacks
+pub type SharedCaptureEngine = Arc<Mutex<Option<Box<dyn CaptureEngine>>>>;
+
+/// What a full channel does with the next frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    /// Evict the oldest queued frame (a slow consumer sees the latest capture)
+    #[default]
+    DropOldest,
+    /// Discard the incoming frame (a slow consumer sees frames in capture order)
+    DropNewest,
+    /// Wait until the consumer makes room, pausing capture
+    Block,
+}
+
+/// Destination for pushed frames
+pub enum FrameSink {
+    /// Invoked on the pump thread for every frame
+    Callback(Box<dyn FnMut(CaptureFrame) + Send>),
+    /// Bounded queue drained by a `FrameReceiver`
+    Channel(FrameSender),
+    /// Shared with every subscriber of a `FrameBroadcaster`
+    Broadcast(FrameBroadcaster),
+}
+
+impl FrameSink {
+    pub fn callback(callback: impl FnMut(CaptureFrame) + Send + 'static) -> Self {
+        FrameSink::Callback(Box::new(callback))
+    }
+
+    /// Bounded channel holding at most `depth` frames
+    ///
+    /// When the receiver falls behind the oldest queued frame is discarded, so a
+    /// slow consumer always sees the most recent capture.
+    pub fn channel(depth: usize) -> (Self, FrameReceiver) {
+        Self::channel_with_policy(depth, DropPolicy::DropOldest)
+    }
+
+    /// Bounded channel with an explicit policy for when it is full
+    pub fn channel_with_policy(depth: usize, policy: DropPolicy) -> (Self, FrameReceiver) {
+        let (sender, receiver) = bounded(depth, policy);
+        (FrameSink::Channel(sender), receiver)
+    }
+
+    /// Deliver one frame. Returns false once nobody is listening any more.
+    pub fn send(&mut self, frame: CaptureFrame) -> bool {
+        match self {
+            FrameSink::Callback(callback) => {
+                callback(frame);
+                true
+            }
+            FrameSink::Channel(sender) => sender.send(frame),
+            // Subscribers may come and go, so a broadcast never disconnects
+            FrameSink::Broadcast(broadcaster) => {
+                broadcaster.publish(frame);
+                true
+            }
+        }
+    }
+}
+
+/// Bounded queue of at most `depth` items, full behaviour set by `policy`
+pub(crate) fn bounded<T>(depth: usize, policy: DropPolicy) -> (FrameSender<T>, FrameReceiver<T>) {
+    let shared = Arc::new(ChannelShared {
+        state: Mutex::new(ChannelState {
+            frames: VecDeque::with_capacity(depth.max(1)),
+            depth: depth.max(1),
+            policy,
+            dropped: 0,
+            sender_alive: true,
+            receiver_alive: true,
+            waker: None,
+        }),
+        ready: Condvar::new(),
+        space: Condvar::new(),
+    });
+
+    (
+        FrameSender {
+            shared: Arc::clone(&shared),
+        },
+        FrameReceiver { shared },
+    )
+}
+
+struct ChannelShared<T> {
+    state: Mutex<ChannelState<T>>,
+    /// Signalled when a frame is queued or the sender goes away
+    ready: Condvar,
+    /// Signalled when a blocked sender may continue
+    space: Condvar,
+}
+
+struct ChannelState<T> {
+    frames: VecDeque<T>,
+    depth: usize,
+    policy: DropPolicy,
+    dropped: u64,
+    sender_alive: bool,
+    receiver_alive: bool,
+    /// Async consumer waiting in `poll_recv`
+    waker: Option<Waker>,
+}
+
+impl<T> ChannelState<T> {
+    fn pop(&mut self, shared: &ChannelShared<T>) -> Option<T> {
+        let frame = self.frames.pop_front();
+        if frame.is_some() && self.policy == DropPolicy::Block {
+            shared.space.notify_one();
+        }
+        frame
+    }
+}
+
+/// Producer half of `FrameSink::channel`
+pub struct FrameSender<T = CaptureFrame> {
+    shared: Arc<ChannelShared<T>>,
+}
+
+impl<T> FrameSender<T> {
+    /// Queue a frame, applying the channel's `DropPolicy` if it is full
+    ///
+    /// Returns false once the receiver has been dropped.
+    pub fn send(&self, frame: T) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+
+        while state.receiver_alive
+            && state.policy == DropPolicy::Block
+            && state.frames.len() >= state.depth
+        {
+            state = self.shared.space.wait(state).unwrap();
+        }
+        if !state.receiver_alive {
+            return false;
+        }
+
+        if state.frames.len() >= state.depth {
+            state.dropped += 1;
+            if state.policy == DropPolicy::DropNewest {
+                return true;
+            }
+            state.frames.pop_front();
+        }
+        state.frames.push_back(frame);
+        let waker = state.waker.take();
+        drop(state);
+
+        self.shared.ready.notify_one();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        true
+    }
+}
+
+impl<T> Drop for FrameSender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.sender_alive = false;
+            state.waker.take()
+        };
+        self.shared.ready.notify_all();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Consumer half of `FrameSink::channel`
+pub struct FrameReceiver<T = CaptureFrame> {
+    shared: Arc<ChannelShared<T>>,
+}
+
+impl<T> FrameReceiver<T> {
+    /// Take the next queued frame without waiting
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared.state.lock().unwrap().pop(&self.shared)
+    }
+
+    /// Block until a frame arrives. Returns None once the sender is gone.
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(frame) = state.pop(&self.shared) {
+                return Some(frame);
+            }
+            if !state.sender_alive {
+                return None;
+            }
+            state = self.shared.ready.wait(state).unwrap();
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.state.lock().unwrap();
+        loop {
+            if let Some(frame) = state.pop(&self.shared) {
+                return Ok(frame);
+            }
+            if !state.sender_alive {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            state = self
+                .shared
+                .ready
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    /// Async receive: registers `cx`'s waker when no frame is queued yet
+    ///
+    /// Resolves to None once the sender is gone and the queue is drained.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(frame) = state.pop(&self.shared) {
+            return Poll::Ready(Some(frame));
+        }
+        if !state.sender_alive {
+            return Poll::Ready(None);
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    /// Frames discarded because the receiver fell behind
+    pub fn dropped(&self) -> u64 {
+        self.shared.state.lock().unwrap().dropped
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Drop for FrameReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receiver_alive = false;
+        state.frames.clear();
+        state.waker = None;
+        drop(state);
+
+        // Release a sender waiting under `DropPolicy::Block`
+        self.shared.space.notify_all();
+    }
+}
+
+struct PumpShared {
+    running: AtomicBool,
+    pacer: Mutex<FramePacer>,
+    pending_region: Mutex<Option<CaptureRect>>,
+    track_damage: AtomicBool,
+    paused: AtomicBool,
+    pipeline: Mutex<FramePipeline>,
+    metrics: Mutex<Option<SharedCaptureMetrics>>,
+}
+
+/// Background thread that polls an engine and pushes frames into a sink
+///
+/// The pump stops when `stop` is called, when it is dropped, or when the sink
+/// reports that its receiver has gone away. With `DropPolicy::Block` a stop only
+/// takes effect once the consumer makes room or drops its receiver.
+pub struct FramePump {
+    shared: Arc<PumpShared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FramePump {
+    /// Start polling `engine` every `interval`
+    pub fn start(engine: SharedCaptureEngine, mut sink: FrameSink, interval: Duration) -> Self {
+        let shared = Arc::new(PumpShared {
+            running: AtomicBool::new(true),
+            pacer: Mutex::new(FramePacer::new(interval, PacingSettings::default())),
+            pending_region: Mutex::new(None),
+            track_damage: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            pipeline: Mutex::new(FramePipeline::new()),
+            metrics: Mutex::new(None),
+        });
+
+        let thread_shared = Arc::clone(&shared);
+        let handle = std::thread::spawn(move || {
+            log::debug!("Frame pump started");
+            let mut differ = TileDiffer::default();
+            // Copy of the last frame sent, kept only while duplication is enabled
+            let mut last_frame: Option<CaptureFrame> = None;
+
+            while thread_shared.running.load(Ordering::Acquire) {
+                let tick_start = Instant::now();
+                let paused = thread_shared.paused.load(Ordering::Relaxed);
+                // Region requests made while paused are kept until capture resumes
+                let frame = if paused {
+                    None
+                } else {
+                    let pending_region = thread_shared.pending_region.lock().unwrap().take();
+                    let mut engine = engine.lock().unwrap();
+                    match engine.as_mut() {
+                        Some(engine) => {
+                            if let Some(region) = pending_region {
+                                if let Err(e) = engine.update_region(region) {
+                                    log::warn!("Frame pump failed to update region: {}", e);
+                                }
+                            }
+                            engine.get_frame()
+                        }
+                        None => None,
+                    }
+                };
+                let acquired_at = Instant::now();
+
+                let duplicate_frames = thread_shared
+                    .pacer
+                    .lock()
+                    .unwrap()
+                    .settings()
+                    .duplicate_frames;
+                let frame = match frame {
+                    Some(mut frame) => {
+                        if thread_shared.track_damage.load(Ordering::Relaxed) {
+                            differ.annotate(&mut frame);
+                        } else {
+                            differ.reset();
+                        }
+                        thread_shared.pipeline.lock().unwrap().process(&mut frame);
+                        if let Some(metrics) = thread_shared.metrics.lock().unwrap().as_ref() {
+                            let mut metrics = metrics.lock().unwrap();
+                            metrics.record(PipelineStage::Acquire, acquired_at - tick_start);
+                            metrics.record(PipelineStage::Process, acquired_at.elapsed());
+                            metrics.set_engine_dropped(frame.dropped_frames);
+                        }
+                        last_frame = if duplicate_frames {
+                            duplicate(&frame)
+                        } else {
+                            None
+                        };
+                        Some(frame)
+                    }
+                    // Hold the output rate on scheduled ticks the engine had nothing for
+                    None if duplicate_frames
+                        && !paused
+                        && thread_shared.pacer.lock().unwrap().is_due(tick_start) =>
+                    {
+                        last_frame.as_ref().and_then(duplicate).map(|frame| {
+                            thread_shared.pacer.lock().unwrap().record_duplicate();
+                            frame
+                        })
+                    }
+                    None => None,
+                };
+                if let Some(frame) = frame {
+                    if !sink.send(frame) {
+                        log::debug!("Frame pump sink disconnected");
+                        break;
+                    }
+                }
+
+                let wait = thread_shared
+                    .pacer
+                    .lock()
+                    .unwrap()
+                    .frame_done(tick_start, Instant::now());
+                if !wait.is_zero() {
+                    // Woken early by `stop` or `request_region`
+                    std::thread::park_timeout(wait);
+                }
+            }
+
+            thread_shared.running.store(false, Ordering::Release);
+            log::debug!("Frame pump stopped");
+        });
+
+        Self {
+            shared,
+            handle: Some(handle),
+        }
+    }
+
+    /// Change the polling interval, e.g. to poll faster while the border is dragged
+    pub fn set_interval(&self, interval: Duration) {
+        self.shared.pacer.lock().unwrap().set_interval(interval);
+    }
+
+    /// Adaptive rate and frame duplication for the pump's schedule
+    pub fn set_pacing(&self, settings: PacingSettings) {
+        self.shared.pacer.lock().unwrap().set_settings(settings);
+    }
+
+    /// Record acquire and process timings of every captured frame into `metrics`
+    pub fn set_metrics(&self, metrics: SharedCaptureMetrics) {
+        *self.shared.metrics.lock().unwrap() = Some(metrics);
+    }
+
+    /// Rate, overruns and jitter of the pump's schedule
+    pub fn pacing_stats(&self) -> PacingStats {
+        self.shared.pacer.lock().unwrap().stats()
+    }
+
+    /// Fill in `CaptureFrame::damage` by tile diffing when the engine doesn't report it
+    pub fn set_damage_tracking(&self, enabled: bool) {
+        self.shared.track_damage.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Stop polling the engine until unpaused, e.g. while a followed window is minimized
+    ///
+    /// The engine keeps running; the sink just receives no frames.
+    pub fn set_paused(&self, paused: bool) {
+        self.shared.paused.store(paused, Ordering::Relaxed);
+        if let Some(handle) = &self.handle {
+            handle.thread().unpark();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.paused.load(Ordering::Relaxed)
+    }
+
+    /// Run `pipeline` on every frame before it reaches the sink
+    ///
+    /// Stages run on the pump thread, so every consumer of the sink (including
+    /// broadcast subscribers) sees processed frames only.
+    pub fn set_pipeline(&self, pipeline: FramePipeline) {
+        *self.shared.pipeline.lock().unwrap() = pipeline;
+    }
+
+    /// Apply `region` on the pump thread before the next capture
+    ///
+    /// Only the most recent request is kept, so a burst of drag updates never
+    /// queues up stale regions.
+    pub fn request_region(&self, region: CaptureRect) {
+        *self.shared.pending_region.lock().unwrap() = Some(region);
+        if let Some(handle) = &self.handle {
+            handle.thread().unpark();
+        }
+    }
+
+    /// False once the pump has stopped or its sink disconnected
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::Acquire)
+    }
+
+    /// Stop the pump thread and wait for it to exit
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for FramePump {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// CPU copy of `frame` to resend when the engine has nothing new
+///
+/// The copy keeps the original sequence number, so consumers can tell it is a
+/// repeat, and reports no damage. GPU-backed frames are not duplicated.
+fn duplicate(frame: &CaptureFrame) -> Option<CaptureFrame> {
+    if frame.gpu_texture.is_some() {
+        return None;
+    }
+    Some(CaptureFrame {
+        data: frame.data.clone(),
+        pixel_format: frame.pixel_format,
+        width: frame.width,
+        height: frame.height,
+        stride: frame.stride,
+        offset_x: frame.offset_x,
+        offset_y: frame.offset_y,
+        gpu_texture: None,
+        captured_at: frame.captured_at,
+        sequence: frame.sequence,
+        dropped_frames: frame.dropped_frames,
+        damage: Some(Vec::new()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{SyntheticCaptureEngine, SyntheticPattern};
+
+    fn frame(value: u8) -> CaptureFrame {
+        CaptureFrame::new(vec![value; 4], 1, 1)
+    }
+
+    fn synthetic_engine(region: CaptureRect) -> SharedCaptureEngine {
+        let mut engine = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        engine.start(region, false, None).unwrap();
+        Arc::new(Mutex::new(Some(Box::new(engine) as Box<dyn CaptureEngine>)))
+    }
+
+    #[test]
+    fn channel_drops_oldest_when_full() {
+        let (mut sink, receiver) = FrameSink::channel(2);
+        for value in 1..=4 {
+            assert!(sink.send(frame(value)));
+        }
+
+        assert_eq!(receiver.len(), 2);
+        assert_eq!(receiver.dropped(), 2);
+        assert_eq!(receiver.try_recv().unwrap().data[0], 3);
+        assert_eq!(receiver.try_recv().unwrap().data[0], 4);
+        assert!(receiver.try_recv().is_none());
+    }
+
+    #[test]
+    fn channel_drop_newest_keeps_queued_frames() {
+        let (mut sink, receiver) = FrameSink::channel_with_policy(2, DropPolicy::DropNewest);
+        for value in 1..=4 {
+            assert!(sink.send(frame(value)));
+        }
+
+        assert_eq!(receiver.dropped(), 2);
+        assert_eq!(receiver.try_recv().unwrap().data[0], 1);
+        assert_eq!(receiver.try_recv().unwrap().data[0], 2);
+    }
+
+    #[test]
+    fn channel_block_waits_for_room() {
+        let (mut sink, receiver) = FrameSink::channel_with_policy(1, DropPolicy::Block);
+        assert!(sink.send(frame(1)));
+
+        let sender = std::thread::spawn(move || {
+            assert!(sink.send(frame(2)));
+            // Released by the receiver being dropped
+            !sink.send(frame(3))
+        });
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(receiver.len(), 1);
+        assert_eq!(receiver.recv().unwrap().data[0], 1);
+        assert_eq!(receiver.recv().unwrap().data[0], 2);
+        assert_eq!(receiver.dropped(), 0);
+
+        drop(receiver);
+        assert!(sender.join().unwrap());
+    }
+
+    #[test]
+    fn channel_reports_disconnects_both_ways() {
+        let (sink, receiver) = FrameSink::channel(1);
+        drop(sink);
+        assert!(receiver.recv().is_none());
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_millis(1)).unwrap_err(),
+            RecvTimeoutError::Disconnected
+        );
+
+        let (mut sink, receiver) = FrameSink::channel(1);
+        drop(receiver);
+        assert!(!sink.send(frame(0)));
+    }
+
+    #[test]
+    fn pump_pushes_frames_and_applies_region_requests() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 32, 16));
+        let (sink, receiver) = FrameSink::channel(4);
+        let pump = FramePump::start(engine.clone(), sink, Duration::from_millis(2));
+
+        let first = receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!((first.width, first.height), (32, 16));
+
+        pump.request_region(CaptureRect::new(10, 10, 8, 8));
+        let resized = (0..100)
+            .filter_map(|_| receiver.recv_timeout(Duration::from_secs(2)).ok())
+            .find(|frame| frame.width == 8)
+            .expect("region request was not applied");
+        assert_eq!((resized.offset_x, resized.offset_y), (10, 10));
+
+        // Callers can still lock the engine while the pump is running
+        let region = engine.lock().unwrap().as_ref().unwrap().get_region();
+        assert_eq!(region, Some(CaptureRect::new(10, 10, 8, 8)));
+
+        pump.stop();
+        // Sender is dropped with the pump thread
+        while receiver.try_recv().is_some() {}
+        assert!(receiver.recv().is_none());
+    }
+
+    #[test]
+    fn pump_exits_when_receiver_is_dropped() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 4, 4));
+        let (sink, receiver) = FrameSink::channel(1);
+        let pump = FramePump::start(engine, sink, Duration::from_millis(1));
+
+        receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+        drop(receiver);
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while pump.is_running() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(!pump.is_running());
+    }
+
+    #[test]
+    fn paused_pump_sends_nothing_and_keeps_region_requests() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 4, 4));
+        let (sink, receiver) = FrameSink::channel(8);
+        let pump = FramePump::start(engine, sink, Duration::from_millis(1));
+        receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+
+        pump.set_paused(true);
+        assert!(pump.is_paused());
+        pump.request_region(CaptureRect::new(2, 2, 6, 6));
+        // Drain whatever was captured before the pause took effect
+        std::thread::sleep(Duration::from_millis(20));
+        while receiver.try_recv().is_some() {}
+        assert!(receiver.recv_timeout(Duration::from_millis(30)).is_err());
+
+        pump.set_paused(false);
+        let resumed = receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!((resumed.width, resumed.height), (6, 6));
+        pump.stop();
+    }
+
+    #[test]
+    fn pump_fills_in_damage_when_enabled() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 16, 16));
+        let (sink, receiver) = FrameSink::channel(8);
+        let pump = FramePump::start(engine, sink, Duration::from_millis(1));
+        pump.set_damage_tracking(true);
+
+        let damaged = (0..100)
+            .filter_map(|_| receiver.recv_timeout(Duration::from_secs(2)).ok())
+            .find(|frame| frame.damage.is_some())
+            .expect("damage tracking was not applied");
+        // Nothing to diff against yet, so the whole frame counts as changed
+        assert_eq!(damaged.damage, Some(vec![CaptureRect::new(0, 0, 16, 16)]));
+        pump.stop();
+    }
+
+    #[test]
+    fn pump_runs_pipeline_before_sink() {
+        use crate::capture::{RedactionProcessor, RedactionStyle, RedactionZone};
+
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 8, 8));
+        let (sink, receiver) = FrameSink::channel(1);
+        let pump = FramePump::start(engine, sink, Duration::from_millis(1));
+        let zone = RedactionZone::new(
+            CaptureRect::new(0, 0, 8, 8),
+            RedactionStyle::Solid {
+                color: [1, 2, 3, 255],
+            },
+        );
+        pump.set_pipeline(FramePipeline::new().with_stage(RedactionProcessor::new(vec![zone])));
+
+        let redacted = (0..100)
+            .filter_map(|_| receiver.recv_timeout(Duration::from_secs(2)).ok())
+            .find(|frame| frame.data[..4] == [3, 2, 1, 255])
+            .expect("pipeline was not applied");
+        assert!(redacted.data.chunks_exact(4).all(|px| px == [3, 2, 1, 255]));
+        pump.stop();
+    }
+
+    #[test]
+    fn pump_duplicates_last_frame_when_engine_has_nothing() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 4, 4));
+        let (sink, receiver) = FrameSink::channel(8);
+        let pump = FramePump::start(engine.clone(), sink, Duration::from_millis(1));
+        pump.set_pacing(PacingSettings {
+            duplicate_frames: true,
+            ..PacingSettings::default()
+        });
+        receiver.recv_timeout(Duration::from_secs(2)).unwrap();
+
+        // Without an engine every scheduled tick resends the last frame
+        *engine.lock().unwrap() = None;
+        while receiver.try_recv().is_some() {}
+        let repeats: Vec<CaptureFrame> = (0..4)
+            .map(|_| receiver.recv_timeout(Duration::from_secs(2)).unwrap())
+            .collect();
+        for repeat in &repeats[2..] {
+            assert_eq!(repeat.sequence, repeats[1].sequence);
+            assert_eq!(repeat.damage, Some(Vec::new()));
+        }
+        assert!(pump.pacing_stats().duplicated >= 3);
+        pump.stop();
+    }
+
+    #[test]
+    fn pump_records_stage_timings() {
+        use crate::capture::CaptureMetrics;
+
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 4, 4));
+        let (sink, receiver) = FrameSink::channel(1);
+        let pump = FramePump::start(engine, sink, Duration::from_millis(1));
+        let metrics = Arc::new(Mutex::new(CaptureMetrics::new()));
+        pump.set_metrics(Arc::clone(&metrics));
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while metrics.lock().unwrap().stage(PipelineStage::Process).len() < 2 {
+            assert!(Instant::now() < deadline, "no timings recorded");
+            let _ = receiver.recv_timeout(Duration::from_millis(50));
+        }
+        let metrics = metrics.lock().unwrap();
+        assert!(!metrics.stage(PipelineStage::Acquire).is_empty());
+        assert!(metrics.stage(PipelineStage::Render).is_empty());
+        pump.stop();
+    }
+
+    #[test]
+    fn callback_sink_runs_on_pump_thread() {
+        let engine = synthetic_engine(CaptureRect::new(0, 0, 4, 4));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let pump = FramePump::start(
+            engine,
+            FrameSink::callback(move |frame| {
+                let _ = tx.send(frame.sequence);
+            }),
+            Duration::from_millis(1),
+        );
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(2)).unwrap(), 0);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(2)).unwrap(), 1);
+        drop(pump);
+    }
+}
@@ -0,0 +1,313 @@
+// capture/geometry.rs - CaptureRect Geometry
+//
+// Rectangle math shared by the capture backends, the border and the platform
+// services: intersection, union, insets, clamping to a monitor, point/pixel
+// scaling and splitting across monitors. Edges are computed in i64, so
+// rectangles near the ends of the i32 range don't overflow; results that
+// can't be represented are clamped.
+
+use super::CaptureRect;
+
+impl CaptureRect {
+    /// Rectangle from a signed size, as window APIs report it; negative sizes become empty
+    pub fn from_signed(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self::new(x, y, width.max(0) as u32, height.max(0) as u32)
+    }
+
+    /// Rectangle spanning `[x0, x1) x [y0, y1)`; inverted edges give an empty rect at (x0, y0)
+    fn from_edges(x0: i64, y0: i64, x1: i64, y1: i64) -> Self {
+        let x = x0.clamp(i32::MIN as i64, i32::MAX as i64);
+        let y = y0.clamp(i32::MIN as i64, i32::MAX as i64);
+        Self::new(
+            x as i32,
+            y as i32,
+            (x1 - x).clamp(0, u32::MAX as i64) as u32,
+            (y1 - y).clamp(0, u32::MAX as i64) as u32,
+        )
+    }
+
+    /// First column past the right edge
+    pub fn right(&self) -> i64 {
+        self.x as i64 + self.width as i64
+    }
+
+    /// First row past the bottom edge
+    pub fn bottom(&self) -> i64 {
+        self.y as i64 + self.height as i64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+
+    pub fn contains_point(&self, x: i32, y: i32) -> bool {
+        x >= self.x && (x as i64) < self.right() && y >= self.y && (y as i64) < self.bottom()
+    }
+
+    /// Whether `other` lies entirely inside this rectangle (empty rects are inside anything)
+    pub fn contains_rect(&self, other: &CaptureRect) -> bool {
+        other.is_empty()
+            || (other.x >= self.x
+                && other.y >= self.y
+                && other.right() <= self.right()
+                && other.bottom() <= self.bottom())
+    }
+
+    /// Overlapping area, or None when the rectangles don't overlap
+    pub fn intersect(&self, other: &CaptureRect) -> Option<CaptureRect> {
+        let x0 = self.x.max(other.x) as i64;
+        let y0 = self.y.max(other.y) as i64;
+        let x1 = self.right().min(other.right());
+        let y1 = self.bottom().min(other.bottom());
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        Some(Self::from_edges(x0, y0, x1, y1))
+    }
+
+    /// Smallest rectangle covering both; empty rectangles are ignored
+    pub fn union(&self, other: &CaptureRect) -> CaptureRect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        Self::from_edges(
+            self.x.min(other.x) as i64,
+            self.y.min(other.y) as i64,
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+
+    /// Move every edge `amount` pixels inwards (outwards when negative)
+    ///
+    /// Insetting past the middle leaves an empty rectangle.
+    pub fn inset(&self, amount: i32) -> CaptureRect {
+        let amount = amount as i64;
+        Self::from_edges(
+            self.x as i64 + amount,
+            self.y as i64 + amount,
+            self.right() - amount,
+            self.bottom() - amount,
+        )
+    }
+
+    /// Grow the size to at least `min_width` x `min_height`, keeping the origin
+    pub fn at_least(&self, min_width: u32, min_height: u32) -> CaptureRect {
+        Self::new(
+            self.x,
+            self.y,
+            self.width.max(min_width),
+            self.height.max(min_height),
+        )
+    }
+
+    /// Translate by (dx, dy)
+    pub fn offset(&self, dx: i32, dy: i32) -> CaptureRect {
+        Self::from_edges(
+            self.x as i64 + dx as i64,
+            self.y as i64 + dy as i64,
+            self.right() + dx as i64,
+            self.bottom() + dy as i64,
+        )
+    }
+
+    /// The same area in coordinates whose origin is `origin`'s top-left corner
+    pub fn relative_to(&self, origin: &CaptureRect) -> CaptureRect {
+        Self::from_edges(
+            self.x as i64 - origin.x as i64,
+            self.y as i64 - origin.y as i64,
+            self.right() - origin.x as i64,
+            self.bottom() - origin.y as i64,
+        )
+    }
+
+    /// Move the rectangle inside `bounds`, shrinking it only if it is larger
+    ///
+    /// Empty rectangles cover nothing and are returned unchanged.
+    pub fn clamp_to(&self, bounds: &CaptureRect) -> CaptureRect {
+        if self.is_empty() {
+            return *self;
+        }
+        let width = self.width.min(bounds.width);
+        let height = self.height.min(bounds.height);
+        let x = (self.x as i64).clamp(bounds.x as i64, bounds.right() - width as i64);
+        let y = (self.y as i64).clamp(bounds.y as i64, bounds.bottom() - height as i64);
+        Self::from_edges(x, y, x + width as i64, y + height as i64)
+    }
+
+    /// Convert from points to pixels for a display with the given scale factor
+    ///
+    /// Edges are rounded independently, so rectangles that touch in points
+    /// still touch in pixels. Non-positive or non-finite factors leave the
+    /// rectangle unchanged.
+    pub fn to_pixels(&self, scale_factor: f64) -> CaptureRect {
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return *self;
+        }
+        let scale = |v: i64| (v as f64 * scale_factor).round() as i64;
+        Self::from_edges(
+            scale(self.x as i64),
+            scale(self.y as i64),
+            scale(self.right()),
+            scale(self.bottom()),
+        )
+    }
+
+    /// Convert from pixels to points; the inverse of `to_pixels`
+    pub fn to_points(&self, scale_factor: f64) -> CaptureRect {
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return *self;
+        }
+        self.to_pixels(1.0 / scale_factor)
+    }
+
+    /// The parts of this rectangle on each monitor, as (monitor index, part)
+    ///
+    /// Areas outside every monitor are dropped; monitors that overlap (mirrored
+    /// displays) each get their share.
+    pub fn split_across(&self, monitors: &[CaptureRect]) -> Vec<(usize, CaptureRect)> {
+        monitors
+            .iter()
+            .enumerate()
+            .filter_map(|(index, monitor)| self.intersect(monitor).map(|part| (index, part)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn rect() -> impl Strategy<Value = CaptureRect> {
+        (-5000i32..5000, -5000i32..5000, 0u32..4000, 0u32..4000)
+            .prop_map(|(x, y, w, h)| CaptureRect::new(x, y, w, h))
+    }
+
+    #[test]
+    fn edge_cases() {
+        let r = CaptureRect::new(10, 20, 30, 40);
+        assert_eq!(r.inset(4), CaptureRect::new(14, 24, 22, 32));
+        assert_eq!(r.inset(-2), CaptureRect::new(8, 18, 34, 44));
+        assert_eq!(r.inset(100), CaptureRect::new(110, 120, 0, 0));
+        assert_eq!(
+            r.inset(100).at_least(1, 1),
+            CaptureRect::new(110, 120, 1, 1)
+        );
+        assert_eq!(
+            CaptureRect::from_signed(0, 0, -5, 7),
+            CaptureRect::new(0, 0, 0, 7)
+        );
+        // Touching edges don't overlap
+        assert_eq!(r.intersect(&CaptureRect::new(40, 20, 10, 10)), None);
+        // No overflow at the ends of the coordinate range
+        let far = CaptureRect::new(i32::MAX - 1, i32::MIN, u32::MAX, u32::MAX);
+        assert_eq!(far.inset(-10).x, i32::MAX - 11);
+        assert!(far.union(&r).width > 0);
+        assert_eq!(r.to_pixels(0.0), r);
+        // Empty rectangles aren't moved onto an empty monitor
+        let empty = CaptureRect::new(0, 0, 0, 0);
+        assert_eq!(empty.clamp_to(&CaptureRect::new(0, -1960, 0, 0)), empty);
+    }
+
+    #[test]
+    fn splits_across_side_by_side_monitors() {
+        let monitors = [
+            CaptureRect::new(0, 0, 1920, 1080),
+            CaptureRect::new(1920, 0, 1280, 1024),
+        ];
+        let parts = CaptureRect::new(1800, 1000, 300, 200).split_across(&monitors);
+        assert_eq!(
+            parts,
+            vec![
+                (0, CaptureRect::new(1800, 1000, 120, 80)),
+                (1, CaptureRect::new(1920, 1000, 180, 24)),
+            ]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn intersection_is_inside_both(a in rect(), b in rect()) {
+            let i = a.intersect(&b);
+            prop_assert_eq!(i, b.intersect(&a));
+            if let Some(i) = i {
+                prop_assert!(!i.is_empty());
+                prop_assert!(a.contains_rect(&i) && b.contains_rect(&i));
+            } else {
+                prop_assert!(a.is_empty() || b.is_empty()
+                    || a.right() <= b.x as i64 || b.right() <= a.x as i64
+                    || a.bottom() <= b.y as i64 || b.bottom() <= a.y as i64);
+            }
+        }
+
+        #[test]
+        fn union_covers_both(a in rect(), b in rect()) {
+            let u = a.union(&b);
+            prop_assert_eq!(u, b.union(&a));
+            prop_assert!(u.contains_rect(&a) && u.contains_rect(&b));
+        }
+
+        #[test]
+        fn inset_and_outset_round_trip(r in rect(), amount in 0i32..100) {
+            let inner = r.inset(amount);
+            prop_assert!(r.contains_rect(&inner));
+            if r.width > 2 * amount as u32 && r.height > 2 * amount as u32 {
+                prop_assert_eq!(inner.inset(-amount), r);
+            }
+        }
+
+        #[test]
+        fn clamp_keeps_rect_on_monitor(r in rect(), monitor in rect()) {
+            let clamped = r.clamp_to(&monitor);
+            prop_assert!(monitor.contains_rect(&clamped));
+            if r.is_empty() {
+                prop_assert_eq!(clamped, r);
+            } else {
+                prop_assert_eq!(clamped.width, r.width.min(monitor.width));
+                prop_assert_eq!(clamped.height, r.height.min(monitor.height));
+            }
+            if monitor.contains_rect(&r) {
+                prop_assert_eq!(clamped, r);
+            }
+        }
+
+        #[test]
+        fn integer_scales_round_trip(r in rect(), scale in 1u32..4) {
+            let pixels = r.to_pixels(scale as f64);
+            prop_assert_eq!(pixels.width, r.width * scale);
+            prop_assert_eq!(pixels.to_points(scale as f64), r);
+        }
+
+        #[test]
+        fn fractional_scales_keep_neighbours_touching(r in rect(), scale in 1.0f64..3.0) {
+            let right = CaptureRect::new(r.right() as i32, r.y, 10, r.height);
+            prop_assert_eq!(r.to_pixels(scale).right(), right.to_pixels(scale).x as i64);
+        }
+
+        #[test]
+        fn split_covers_the_monitor_area(r in rect(), columns in 1usize..4, rows in 1usize..4) {
+            // A grid of non-overlapping 1000x800 monitors starting at the origin
+            let monitors: Vec<CaptureRect> = (0..rows)
+                .flat_map(|row| (0..columns).map(move |col| {
+                    CaptureRect::new(col as i32 * 1000, row as i32 * 800, 1000, 800)
+                }))
+                .collect();
+            let desktop = CaptureRect::new(0, 0, columns as u32 * 1000, rows as u32 * 800);
+
+            let parts = r.split_across(&monitors);
+            for (index, part) in &parts {
+                prop_assert!(monitors[*index].contains_rect(part));
+            }
+            let covered: u64 = parts.iter().map(|(_, part)| part.area()).sum();
+            prop_assert_eq!(covered, r.intersect(&desktop).map_or(0, |i| i.area()));
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// capture/glyphs.rs - Tiny bitmap font for text burned into frames
+//
+// 5x7 glyphs, one byte per row (low 5 bits, MSB on the left). Used for frame
+// counters, labels and watermarks where pulling in a font rasterizer would be
+// overkill. Letters are uppercase only; lowercase input is drawn in capitals.
+
+use super::CaptureFrame;
+
+/// Glyph cell width in font pixels (including no spacing)
+pub const GLYPH_WIDTH: u32 = 5;
+/// Glyph cell height in font pixels
+pub const GLYPH_HEIGHT: u32 = 7;
+/// Horizontal advance between glyphs in font pixels
+pub const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+fn glyph_rows(c: char) -> Option<[u8; 7]> {
+    let rows = match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00; 7],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Size in pixels of `text` rendered at `scale`
+pub fn text_size(text: &str, scale: u32) -> (u32, u32) {
+    let count = text.chars().count() as u32;
+    if count == 0 {
+        return (0, 0);
+    }
+    ((count * GLYPH_ADVANCE - 1) * scale, GLYPH_HEIGHT * scale)
+}
+
+/// Draw `text` into a BGRA8 or RGBA8 frame with its top-left corner at (x, y)
+///
+/// `color` is written as-is, so it must already be in the frame's byte order.
+/// Unknown characters are drawn as blanks; pixels outside the frame are skipped.
+pub fn draw_text(frame: &mut CaptureFrame, x: i32, y: i32, text: &str, scale: u32, color: [u8; 4]) {
+    let scale = scale.max(1) as i32;
+    let mut pen_x = x;
+
+    for c in text.chars() {
+        if let Some(rows) = glyph_rows(c) {
+            for (row, bits) in rows.iter().enumerate() {
+                for col in 0..GLYPH_WIDTH as i32 {
+                    if bits & (0x10 >> col) == 0 {
+                        continue;
+                    }
+                    let px = pen_x + col * scale;
+                    let py = y + row as i32 * scale;
+                    fill_block(frame, px, py, scale, color);
+                }
+            }
+        }
+        pen_x += GLYPH_ADVANCE as i32 * scale;
+    }
+}
+
+fn fill_block(frame: &mut CaptureFrame, x: i32, y: i32, size: i32, color: [u8; 4]) {
+    for py in y.max(0)..(y + size).min(frame.height as i32) {
+        for px in x.max(0)..(x + size).min(frame.width as i32) {
+            let idx = py as usize * frame.stride as usize + px as usize * 4;
+            if let Some(pixel) = frame.data.get_mut(idx..idx + 4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_size_scales_with_glyph_count() {
+        assert_eq!(text_size("", 1), (0, 0));
+        assert_eq!(text_size("0", 1), (5, 7));
+        assert_eq!(text_size("12", 2), (22, 14));
+    }
+
+    #[test]
+    fn draws_one_and_clips_at_edges() {
+        let mut frame = CaptureFrame::new(vec![0u8; 8 * 8 * 4], 8, 8);
+        draw_text(&mut frame, 0, 0, "1", 1, [1, 2, 3, 4]);
+
+        // Top row of '1' is a single pixel in column 2
+        let lit: Vec<u32> = (0..8)
+            .filter(|x| frame.data[(*x * 4) as usize] == 1)
+            .collect();
+        assert_eq!(lit, vec![2]);
+
+        // Drawing partly off-frame must not panic
+        draw_text(&mut frame, -3, 5, "88", 2, [9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn lowercase_uses_capital_glyphs() {
+        assert_eq!(glyph_rows('a'), glyph_rows('A'));
+        assert!(glyph_rows('Z').is_some());
+        assert!(glyph_rows('~').is_none());
+    }
+}
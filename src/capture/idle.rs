@@ -0,0 +1,245 @@
+// capture/idle.rs - Static Content Detection
+//
+// A static slide or an idle terminal produces the same frame over and over,
+// and each one used to be converted and presented at the full frame rate.
+// IdleDetector hashes every frame tile by tile (see `damage::TileDiffer`) and
+// tells the render loop to skip frames that match the previous one. Once
+// nothing has changed for `idle_after_ms` the capture drops to `idle_fps`,
+// and the first changed frame brings it back to full rate.
+//
+// Pointer motion and clicks reported through `note_input` count as changes
+// too, for `idle_after_ms`: click highlights, the cursor and zoom panning must
+// not wait for the next idle capture, and GPU renderers draw click marks over
+// frames whose pixels haven't changed.
+//
+// Frames are checked after the pipeline, so click highlights, zoom and
+// watermarks count as changes. Frames are hashed whenever they carry CPU
+// pixels; only GPU frames without any are always treated as changed.
+
+use super::damage::TileDiffer;
+use super::pacing::FramePacer;
+use super::CaptureFrame;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Idle behavior, usually from the `idle_detection` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdleSettings {
+    /// Skip unchanged frames and slow down while content is static
+    pub enabled: bool,
+    /// How long content must stay unchanged before the rate drops
+    pub idle_after_ms: u32,
+    /// Capture and refresh rate while idle
+    pub idle_fps: u32,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_after_ms: 1000,
+            idle_fps: 2,
+        }
+    }
+}
+
+/// Decides which frames are worth rendering and when capture can slow down
+#[derive(Debug, Clone)]
+pub struct IdleDetector {
+    settings: IdleSettings,
+    differ: TileDiffer,
+    last_change: Option<Instant>,
+    last_render: Option<Instant>,
+    last_input: Option<Instant>,
+}
+
+impl IdleDetector {
+    pub fn new(settings: IdleSettings) -> Self {
+        Self {
+            settings,
+            differ: TileDiffer::default(),
+            last_change: None,
+            last_render: None,
+            last_input: None,
+        }
+    }
+
+    pub fn settings(&self) -> &IdleSettings {
+        &self.settings
+    }
+
+    /// Forget the previous frame, so the next one counts as changed
+    pub fn reset(&mut self) {
+        self.differ.reset();
+        self.last_change = None;
+        self.last_render = None;
+        self.last_input = None;
+    }
+
+    /// Record pointer or click activity at `at`; capture stays at full rate
+    /// and every frame is rendered until `idle_after_ms` have passed
+    pub fn note_input(&mut self, at: Instant) {
+        if self.last_input.map_or(true, |last| at > last) {
+            self.last_input = Some(at);
+            self.last_change = self.last_change.max(Some(at));
+        }
+    }
+
+    /// Whether `frame` should be rendered
+    ///
+    /// Changed frames always are; unchanged ones only as a refresh once per
+    /// idle interval, so the destination still repaints now and then.
+    pub fn observe(&mut self, frame: &CaptureFrame, now: Instant) -> bool {
+        if !self.settings.enabled {
+            return true;
+        }
+        let changed = if frame.data.is_empty() {
+            // Texture-only GPU frame: nothing to compare
+            true
+        } else {
+            !self.differ.diff(frame).is_empty()
+        };
+        let changed = changed || self.recent_input(now);
+        if changed {
+            self.last_change = Some(now);
+        } else if let Some(last_render) = self.last_render {
+            if now.saturating_duration_since(last_render) < self.idle_interval() {
+                return false;
+            }
+        }
+        self.last_render = Some(now);
+        true
+    }
+
+    /// True once nothing has changed for `idle_after_ms`
+    pub fn is_idle(&self, now: Instant) -> bool {
+        let idle_after = Duration::from_millis(self.settings.idle_after_ms as u64);
+        self.settings.enabled
+            && self.last_change.map_or(false, |last| {
+                now.saturating_duration_since(last) >= idle_after
+            })
+    }
+
+    /// Interval to capture at: `active` normally, the idle interval while idle
+    pub fn interval(&self, active: Duration, now: Instant) -> Duration {
+        if self.is_idle(now) {
+            self.idle_interval().max(active)
+        } else {
+            active
+        }
+    }
+
+    fn recent_input(&self, now: Instant) -> bool {
+        let idle_after = Duration::from_millis(self.settings.idle_after_ms as u64);
+        self.last_input.map_or(false, |last| {
+            now.saturating_duration_since(last) < idle_after
+        })
+    }
+
+    fn idle_interval(&self) -> Duration {
+        FramePacer::interval_for_fps(self.settings.idle_fps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACTIVE: Duration = Duration::from_millis(16);
+
+    fn frame(value: u8) -> CaptureFrame {
+        CaptureFrame::new(vec![value; 32 * 32 * 4], 32, 32)
+    }
+
+    #[test]
+    fn skips_unchanged_frames_and_goes_idle() {
+        let mut idle = IdleDetector::new(IdleSettings::default());
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert!(idle.observe(&frame(1), at(0)));
+        assert!(!idle.observe(&frame(1), at(16)));
+        assert!(!idle.is_idle(at(500)));
+        assert_eq!(idle.interval(ACTIVE, at(500)), ACTIVE);
+
+        // Unchanged for a second: drop to 2 fps, refreshing once per interval
+        assert!(idle.is_idle(at(1000)));
+        assert_eq!(idle.interval(ACTIVE, at(1000)), Duration::from_millis(500));
+        assert!(idle.observe(&frame(1), at(1000)));
+        assert!(!idle.observe(&frame(1), at(1200)));
+
+        // New content is rendered at once and restores the full rate
+        assert!(idle.observe(&frame(2), at(1300)));
+        assert!(!idle.is_idle(at(1300)));
+        assert_eq!(idle.interval(ACTIVE, at(1300)), ACTIVE);
+    }
+
+    #[test]
+    fn clicks_wake_idle_capture() {
+        let mut idle = IdleDetector::new(IdleSettings::default());
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert!(idle.observe(&frame(1), at(0)));
+        assert!(idle.observe(&frame(1), at(1000)));
+        assert_eq!(idle.interval(ACTIVE, at(1100)), Duration::from_millis(500));
+
+        // A click while idle restores the full rate straight away, and unchanged
+        // frames are rendered so its highlight shows up
+        idle.note_input(at(1100));
+        assert!(!idle.is_idle(at(1100)));
+        assert_eq!(idle.interval(ACTIVE, at(1100)), ACTIVE);
+        assert!(idle.observe(&frame(1), at(1116)));
+        assert!(idle.observe(&frame(1), at(1132)));
+
+        // Older input reported late doesn't rewind anything
+        idle.note_input(at(500));
+        assert!(!idle.is_idle(at(1132)));
+
+        // Once the input settles the detector goes idle again
+        assert!(idle.observe(&frame(1), at(2150)));
+        assert!(!idle.observe(&frame(1), at(2200)));
+        assert!(idle.is_idle(at(2200)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn gpu_frames_with_pixels_are_compared() {
+        use crate::capture::GpuTextureHandle;
+
+        let with_texture = |value: u8| {
+            let mut frame = frame(value);
+            frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+                fd: -1,
+                width: 32,
+                height: 32,
+                stride: 32 * 4,
+                format: 0,
+            });
+            frame
+        };
+        let mut idle = IdleDetector::new(IdleSettings::default());
+        let now = Instant::now();
+        assert!(idle.observe(&with_texture(1), now));
+        assert!(!idle.observe(&with_texture(1), now + ACTIVE));
+
+        // Without pixels there is nothing to compare
+        let mut empty = with_texture(1);
+        empty.data.clear();
+        assert!(idle.observe(&empty, now + ACTIVE * 2));
+    }
+
+    #[test]
+    fn disabled_detector_renders_everything() {
+        let settings = IdleSettings {
+            enabled: false,
+            ..IdleSettings::default()
+        };
+        let mut idle = IdleDetector::new(settings);
+        let now = Instant::now();
+        assert!(idle.observe(&frame(1), now));
+        assert!(idle.observe(&frame(1), now));
+        assert!(!idle.is_idle(now + Duration::from_secs(60)));
+    }
+}
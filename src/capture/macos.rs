@@ -7,7 +7,10 @@
 // ALL CoreGraphics operations must happen on main thread to avoid ObjC exceptions
 #![allow(deprecated)]
 
-use super::{CaptureEngine, CaptureFrame, CaptureRect};
+use super::{
+    CaptureEngine, CaptureFrame, CaptureRect, CaptureStats, CursorCompositor, CursorImage,
+    CursorStyle, FrameStatsTracker,
+};
 use crate::display_info;
 use crate::window_filter::WindowIdentifier;
 use anyhow::{anyhow, Result};
@@ -134,8 +137,7 @@ extern "C" {
 }
 
 #[cfg(target_os = "macos")]
-fn cursor_rgba_premultiplied() -> Option<(Vec<u8>, u32, u32, i32, i32)> {
-    // Returns (rgba_premultiplied, width_px, height_px, hotspot_x_px, hotspot_y_px)
+fn current_cursor_image() -> Option<CursorImage> {
     // Hotspot is assumed to be in image coordinates with origin at top-left (AppKit behavior).
     use objc::{class, msg_send, sel, sel_impl};
 
@@ -218,112 +220,7 @@ fn cursor_rgba_premultiplied() -> Option<(Vec<u8>, u32, u32, i32, i32)> {
             &cursor_image,
         );
 
-        Some((rgba, cw, ch, hotspot_x, hotspot_y))
-    }
-}
-
-#[cfg(target_os = "macos")]
-#[allow(clippy::too_many_arguments)]
-fn overlay_cursor_rgba_premultiplied(
-    dst: &mut [u8],
-    dst_width: u32,
-    dst_height: u32,
-    src: &[u8],
-    src_width: u32,
-    src_height: u32,
-    dst_x: i32,
-    dst_y: i32,
-) {
-    if dst_width == 0 || dst_height == 0 || src_width == 0 || src_height == 0 {
-        return;
-    }
-
-    let dst_stride = (dst_width as usize) * 4;
-    let src_stride = (src_width as usize) * 4;
-
-    for sy in 0..(src_height as i32) {
-        let dy = dst_y + sy;
-        if dy < 0 || dy >= dst_height as i32 {
-            continue;
-        }
-        let src_row = (sy as usize) * src_stride;
-        let dst_row = (dy as usize) * dst_stride;
-
-        for sx in 0..(src_width as i32) {
-            let dx = dst_x + sx;
-            if dx < 0 || dx >= dst_width as i32 {
-                continue;
-            }
-            let si = src_row + (sx as usize) * 4;
-            let di = dst_row + (dx as usize) * 4;
-            if si + 3 >= src.len() || di + 3 >= dst.len() {
-                continue;
-            }
-
-            let sa = src[si + 3] as u16;
-            if sa == 0 {
-                continue;
-            }
-            let inv = 255u16 - sa;
-
-            // Premultiplied alpha-over: out = src + dst*(1-sa)
-            dst[di] = (src[si] as u16 + (dst[di] as u16 * inv + 127) / 255) as u8;
-            dst[di + 1] = (src[si + 1] as u16 + (dst[di + 1] as u16 * inv + 127) / 255) as u8;
-            dst[di + 2] = (src[si + 2] as u16 + (dst[di + 2] as u16 * inv + 127) / 255) as u8;
-            dst[di + 3] = (sa + (dst[di + 3] as u16 * inv + 127) / 255) as u8;
-        }
-    }
-}
-
-#[cfg(target_os = "macos")]
-fn overlay_software_cursor_dot_rgba(
-    pixel_data: &mut [u8],
-    width: u32,
-    height: u32,
-    center_x: i32,
-    center_y_from_top: i32,
-) {
-    if width == 0 || height == 0 {
-        return;
-    }
-
-    let radius: i32 = 6;
-    let border: i32 = 1;
-    let r2 = radius * radius;
-    let inner_r2 = (radius - border).max(0);
-    let inner_r2 = inner_r2 * inner_r2;
-
-    let stride = (width as usize) * 4;
-
-    for dy in -radius..=radius {
-        for dx in -radius..=radius {
-            let dist2 = dx * dx + dy * dy;
-            if dist2 > r2 {
-                continue;
-            }
-
-            let x = center_x + dx;
-            let y = center_y_from_top + dy;
-
-            if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
-                continue;
-            }
-
-            let is_border = dist2 >= inner_r2;
-            let (r, g, b, a) = if is_border {
-                (0u8, 0u8, 0u8, 255u8)
-            } else {
-                (255u8, 255u8, 255u8, 255u8)
-            };
-
-            let idx = (y as usize) * stride + (x as usize) * 4;
-            if idx + 3 < pixel_data.len() {
-                pixel_data[idx] = r;
-                pixel_data[idx + 1] = g;
-                pixel_data[idx + 2] = b;
-                pixel_data[idx + 3] = a;
-            }
-        }
+        CursorImage::from_premultiplied(rgba, cw, ch, (hotspot_x, hotspot_y)).ok()
     }
 }
 
@@ -352,6 +249,7 @@ pub struct MacOSCaptureEngine {
     is_active: bool,
     region: Option<CaptureRect>,
     show_cursor: bool,
+    cursor: CursorCompositor,
     last_frame: Option<Arc<Vec<u8>>>,
     frame_width: u32,
     frame_height: u32,
@@ -364,6 +262,8 @@ pub struct MacOSCaptureEngine {
     sck: Option<ScreenCaptureKitCapture>,
     sck_last_seq: u64,
     using_sck: bool,
+
+    frame_stats: FrameStatsTracker,
 }
 
 /// Context for dispatch_sync callback - returns raw pixel data, not CGImage
@@ -373,6 +273,8 @@ struct CaptureContext {
     show_cursor: bool,
     // Results - all processing done on main thread
     pixel_data: Option<Vec<u8>>,
+    /// Cursor image and hotspot in frame pixels, if it is inside the region
+    cursor: Option<(CursorImage, i32, i32)>,
     result_width: u32,
     result_height: u32,
     error: Option<String>,
@@ -453,7 +355,8 @@ extern "C" fn capture_on_main_thread(context: *mut std::ffi::c_void) {
         let bytes_per_row = width as usize * 4;
 
         let color_space = core_graphics::color_space::CGColorSpace::create_device_rgb();
-        let mut pixel_data = vec![0u8; bytes_per_row * height as usize];
+        // Zeroed: the image is composited onto the buffer, not copied
+        let mut pixel_data = super::FramePool::global().take_zeroed(bytes_per_row * height as usize);
         let cg_context = core_graphics::context::CGContext::create_bitmap_context(
             Some(pixel_data.as_mut_ptr() as *mut _),
             width as usize,
@@ -478,7 +381,7 @@ extern "C" fn capture_on_main_thread(context: *mut std::ffi::c_void) {
         // Optional: overlay cursor into the captured frame.
         // CoreGraphics screen capture does not include the cursor by default, so we overlay it.
         // We prefer the real system cursor image; if we can't fetch it reliably, we fall back to
-        // a software dot.
+        // the built-in arrow.
         if ctx.show_cursor {
             // Use NSEvent mouseLocation (global, bottom-left origin in Cocoa screen coords)
             // and convert to our capture-space which uses top-left origin (see hollow_border cache).
@@ -518,30 +421,9 @@ extern "C" fn capture_on_main_thread(context: *mut std::ffi::c_void) {
                         let cursor_x_px = rel_x_px.round() as i32;
                         let cursor_y_px = rel_y_px_from_top.round() as i32;
 
-                        if let Some((cursor_rgba, cw, ch, hot_x, hot_y)) =
-                            cursor_rgba_premultiplied()
-                        {
-                            let draw_x = cursor_x_px - hot_x;
-                            let draw_y = cursor_y_px - hot_y;
-                            overlay_cursor_rgba_premultiplied(
-                                &mut pixel_data,
-                                width,
-                                height,
-                                &cursor_rgba,
-                                cw,
-                                ch,
-                                draw_x,
-                                draw_y,
-                            );
-                        } else {
-                            overlay_software_cursor_dot_rgba(
-                                &mut pixel_data,
-                                width,
-                                height,
-                                cursor_x_px,
-                                cursor_y_px,
-                            );
-                        }
+                        // Drawn by the shared compositor once back on the capture thread
+                        let image = current_cursor_image().unwrap_or_else(CursorImage::arrow);
+                        ctx.cursor = Some((image, cursor_x_px, cursor_y_px));
                     }
                 }
             }
@@ -598,6 +480,7 @@ impl MacOSCaptureEngine {
             is_active: false,
             region: None,
             show_cursor: true,
+            cursor: CursorCompositor::default(),
             last_frame: None,
             frame_width: 0,
             frame_height: 0,
@@ -606,6 +489,8 @@ impl MacOSCaptureEngine {
             sck,
             sck_last_seq: 0,
             using_sck: false,
+
+            frame_stats: FrameStatsTracker::new(),
         })
     }
 
@@ -619,6 +504,7 @@ impl MacOSCaptureEngine {
             region,
             show_cursor: self.show_cursor,
             pixel_data: None,
+            cursor: None,
             result_width: 0,
             result_height: 0,
             error: None,
@@ -647,10 +533,22 @@ impl MacOSCaptureEngine {
         }
 
         // Get the pixel data (already converted on main thread)
-        let pixel_data = ctx
+        let mut pixel_data = ctx
             .pixel_data
             .ok_or_else(|| anyhow!("No pixel data captured"))?;
 
+        if let Some((image, x, y)) = ctx.cursor {
+            let mut frame = CaptureFrame::new(pixel_data, ctx.result_width, ctx.result_height)
+                .with_pixel_format(super::PixelFormat::Rgba8);
+            if let Err(e) = self
+                .cursor
+                .draw(&mut frame, &image, x, y, std::time::Instant::now())
+            {
+                log::warn!("Failed to draw cursor: {}", e);
+            }
+            pixel_data = frame.data;
+        }
+
         self.frame_width = ctx.result_width;
         self.frame_height = ctx.result_height;
         self.last_frame = Some(Arc::new(pixel_data));
@@ -701,6 +599,7 @@ impl CaptureEngine for MacOSCaptureEngine {
 
         self.region = Some(region);
         self.show_cursor = show_cursor;
+        self.frame_stats.reset();
         self.is_active = true;
 
         // Detect which monitor contains this region
@@ -817,6 +716,10 @@ impl CaptureEngine for MacOSCaptureEngine {
                 if let Some(ref sck) = self.sck {
                     if let Some((data, w, h, seq)) = sck.latest_frame_rgba() {
                         if seq != self.sck_last_seq {
+                            // SCK numbers every frame it hands us; gaps were overwritten unseen
+                            if self.sck_last_seq != 0 && seq > self.sck_last_seq + 1 {
+                                self.frame_stats.record_dropped(seq - self.sck_last_seq - 1);
+                            }
                             self.sck_last_seq = seq;
                         }
 
@@ -843,15 +746,20 @@ impl CaptureEngine for MacOSCaptureEngine {
                             },
                         );
 
-                        return Some(CaptureFrame {
+                        return self.frame_stats.deliver(Some(CaptureFrame {
                             data,
+                            pixel_format: super::PixelFormat::Rgba8,
                             width: w,
                             height: h,
                             stride: w * 4,
                             offset_x: region.x,
                             offset_y: region.y,
                             gpu_texture,
-                        });
+                            captured_at: std::time::Instant::now(),
+                            sequence: 0,
+                            dropped_frames: 0,
+                            damage: None,
+                        }));
                     }
                 }
             }
@@ -861,15 +769,21 @@ impl CaptureEngine for MacOSCaptureEngine {
         if let Some(region) = self.region {
             if self.capture_region(region).is_ok() {
                 if let Some(pixel_data) = &self.last_frame {
-                    return Some(CaptureFrame {
+                    let frame = CaptureFrame {
                         data: (**pixel_data).clone(),
+                        pixel_format: super::PixelFormat::Rgba8,
                         width: self.frame_width,
                         height: self.frame_height,
                         stride: self.frame_width * 4,
                         offset_x: region.x,
                         offset_y: region.y,
                         gpu_texture: None, // CoreGraphics fallback doesn't support GPU
-                    });
+                        captured_at: std::time::Instant::now(),
+                        sequence: 0,
+                        dropped_frames: 0,
+                        damage: None,
+                    };
+                    return self.frame_stats.deliver(Some(frame));
                 }
             }
         }
@@ -884,6 +798,13 @@ impl CaptureEngine for MacOSCaptureEngine {
         Ok(())
     }
 
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        // Only the CoreGraphics path draws its own cursor; ScreenCaptureKit
+        // frames carry the system cursor
+        self.cursor.set_style(style);
+        Ok(())
+    }
+
     fn update_region(&mut self, region: CaptureRect) -> Result<()> {
         self.region = Some(region);
 
@@ -908,6 +829,10 @@ impl CaptureEngine for MacOSCaptureEngine {
         self.region
     }
 
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
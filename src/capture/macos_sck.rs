@@ -12,6 +12,7 @@
 #![allow(unused_unsafe)]
 #![allow(clippy::arc_with_non_send_sync)]
 
+use crate::capture::CaptureRect;
 use crate::window_filter::WindowIdentifier;
 use anyhow::{anyhow, Result};
 use block::ConcreteBlock;
@@ -339,40 +340,39 @@ fn ensure_output_class() -> *const Class {
                     return;
                 }
 
-                let scale_m = state.scale_milli.load(Ordering::Relaxed).max(1) as i64;
-                let rx_pt = state.region_x.load(Ordering::Relaxed) as i64;
-                let ry_pt = state.region_y.load(Ordering::Relaxed) as i64;
-                let rw_pt = state.region_w.load(Ordering::Relaxed) as i64;
-                let rh_pt = state.region_h.load(Ordering::Relaxed) as i64;
-
-                // Convert region from points (top-left origin) to pixels.
-                let mut rx_px = ((rx_pt * scale_m) + 500) / 1000;
-                let mut ry_px = ((ry_pt * scale_m) + 500) / 1000;
-                let mut rw_px = ((rw_pt * scale_m) + 500) / 1000;
-                let mut rh_px = ((rh_pt * scale_m) + 500) / 1000;
-
-                if rw_px <= 0 || rh_px <= 0 {
-                    // Fallback: full frame
-                    rx_px = 0;
-                    ry_px = 0;
-                    rw_px = full_w as i64;
-                    rh_px = full_h as i64;
-                }
-
-                // Clamp to bounds.
-                if rx_px < 0 {
-                    rw_px += rx_px;
-                    rx_px = 0;
-                }
-                if ry_px < 0 {
-                    rh_px += ry_px;
-                    ry_px = 0;
-                }
+                let scale = state.scale_milli.load(Ordering::Relaxed).max(1) as f64 / 1000.0;
+                let region_pt = CaptureRect::new(
+                    state.region_x.load(Ordering::Relaxed),
+                    state.region_y.load(Ordering::Relaxed),
+                    state.region_w.load(Ordering::Relaxed),
+                    state.region_h.load(Ordering::Relaxed),
+                );
 
-                let max_w = ((full_w as i64) - rx_px).max(0);
-                let max_h = ((full_h as i64) - ry_px).max(0);
-                rw_px = rw_px.clamp(0, max_w);
-                rh_px = rh_px.clamp(0, max_h);
+                // Convert region from points (top-left origin) to pixels and
+                // clip it to the frame; an empty region means the full frame.
+                let frame_px = CaptureRect::new(0, 0, full_w, full_h);
+                let region_px = region_pt.to_pixels(scale);
+                let crop = if region_px.is_empty() {
+                    Some(frame_px)
+                } else {
+                    region_px.intersect(&frame_px)
+                };
+                let crop = match crop {
+                    Some(crop) => crop,
+                    None => {
+                        let _ = CVPixelBufferUnlockBaseAddress(
+                            pixel_buffer,
+                            KCVPIXELBUFFERLOCK_READONLY,
+                        );
+                        return;
+                    }
+                };
+                let (rx_px, ry_px, rw_px, rh_px) = (
+                    crop.x as i64,
+                    crop.y as i64,
+                    crop.width as i64,
+                    crop.height as i64,
+                );
 
                 let out_w = rw_px as u32;
                 let out_h = rh_px as u32;
@@ -389,7 +389,8 @@ fn ensure_output_class() -> *const Class {
                     return;
                 }
 
-                let mut rgba = vec![0u8; (out_w as usize) * (out_h as usize) * 4];
+                let mut rgba = crate::capture::FramePool::global()
+                    .take((out_w as usize) * (out_h as usize) * 4);
                 let out_stride = (out_w as usize) * 4;
 
                 // OPTIMIZED: Bulk copy rows then swap channels in-place
@@ -879,19 +880,25 @@ impl ScreenCaptureKitCapture {
                 let display_h_px = display_info.height_pixels as f64;
 
                 // Convert AppKit coordinates (bottom-left origin, points) to CGDisplay (top-left origin, pixels)
-                // This is the CORRECT way - using centralized coordinate conversion
-                let (rx_px, ry_px, rw_px, rh_px) =
-                    display_info.appkit_to_cgdisplay(rx_pt, ry_pt, rw_pt, rh_pt);
-                let mut rx_px = rx_px;
-                let mut ry_px = ry_px;
-                let mut rw_px = rw_px;
-                let mut rh_px = rh_px;
-
-                // Clamp to display bounds to prevent crash
-                rx_px = rx_px.max(0.0).min(display_w_px - 1.0);
-                ry_px = ry_px.max(0.0).min(display_h_px - 1.0);
-                rw_px = rw_px.min(display_w_px - rx_px).max(1.0);
-                rh_px = rh_px.min(display_h_px - ry_px).max(1.0);
+                let region_pt = CaptureRect::new(
+                    rx_pt as i32,
+                    (display_info.height_points - ry_pt - rh_pt).round() as i32,
+                    rw_pt as u32,
+                    rh_pt as u32,
+                );
+                // Clip to display bounds to prevent crash
+                let display_px =
+                    CaptureRect::new(0, 0, display_info.width_pixels, display_info.height_pixels);
+                let region_px = region_pt
+                    .to_pixels(display_info.scale_factor)
+                    .intersect(&display_px)
+                    .unwrap_or(display_px);
+                let (rx_px, ry_px, rw_px, rh_px) = (
+                    region_px.x as f64,
+                    region_px.y as f64,
+                    region_px.width as f64,
+                    region_px.height as f64,
+                );
 
                 log::info!(
                     "[SCK] Display: {}x{} pixels @ {:.1}x scale",
@@ -0,0 +1,350 @@
+// capture/metrics.rs - Pipeline Latency Metrics
+//
+// A frame goes through several stages between the engine and the screen:
+// acquiring it from the engine, running the frame pipeline (conversion, click
+// and cursor compositing, scaling), waiting for the destination window and
+// rendering it. CaptureMetrics keeps a rolling window of timings per stage so
+// a slow stage can be told apart from a slow source, plus running drop counts.
+//
+// The pump records the acquire and process stages, the render loop the rest.
+// Percentiles are computed from the last `HISTORY` samples of each stage when
+// a snapshot is taken, not on the capture path.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Samples kept per stage (10 seconds at 60 fps)
+const HISTORY: usize = 600;
+
+/// Metrics shared between the pump, the render loop and whoever reports them
+pub type SharedCaptureMetrics = Arc<Mutex<CaptureMetrics>>;
+
+/// Timed steps of a frame's path to the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStage {
+    /// Getting the frame from the engine, including the engine lock
+    Acquire,
+    /// Damage tracking and the frame pipeline
+    Process,
+    /// Drawing the frame in the destination window
+    Render,
+    /// Waiting for the destination window lock before rendering
+    DestinationLock,
+}
+
+impl PipelineStage {
+    pub const ALL: [PipelineStage; 4] = [
+        PipelineStage::Acquire,
+        PipelineStage::Process,
+        PipelineStage::Render,
+        PipelineStage::DestinationLock,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStage::Acquire => "acquire",
+            PipelineStage::Process => "process",
+            PipelineStage::Render => "render",
+            PipelineStage::DestinationLock => "destination_lock",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Rolling window of the most recent durations for one stage
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new(HISTORY)
+    }
+}
+
+impl LatencyHistogram {
+    /// Histogram keeping the last `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn record(&mut self, duration: Duration) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(duration);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Nearest-rank percentile (`p` from 0 to 100) of the samples in the window
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        Self::rank(&sorted, p)
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        let ms = |p: f64| Self::rank(&sorted, p).map_or(0.0, |d| d.as_secs_f64() * 1000.0);
+        let total: Duration = sorted.iter().sum();
+        LatencySummary {
+            samples: sorted.len(),
+            mean_ms: if sorted.is_empty() {
+                0.0
+            } else {
+                total.as_secs_f64() * 1000.0 / sorted.len() as f64
+            },
+            p50_ms: ms(50.0),
+            p90_ms: ms(90.0),
+            p99_ms: ms(99.0),
+            max_ms: ms(100.0),
+        }
+    }
+
+    fn rank(sorted: &[Duration], p: f64) -> Option<Duration> {
+        if sorted.is_empty() {
+            return None;
+        }
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+}
+
+/// Percentiles of one stage, in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LatencySummary {
+    /// Samples in the window the percentiles were taken from
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// How one frame's render went, as reported by the platform's render call
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderTiming {
+    /// Time spent acquiring the destination window lock
+    pub lock_wait: Duration,
+    /// Time spent drawing, or None when the frame was not rendered (lock busy,
+    /// no destination window)
+    pub render: Option<Duration>,
+}
+
+/// Per-stage latencies and drop counts for the current capture session
+#[derive(Debug, Clone, Default)]
+pub struct CaptureMetrics {
+    stages: [LatencyHistogram; 4],
+    frames_rendered: u64,
+    render_skipped: u64,
+    unchanged_skipped: u64,
+    engine_dropped: u64,
+    queue_dropped: u64,
+}
+
+impl CaptureMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new session
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn record(&mut self, stage: PipelineStage, duration: Duration) {
+        self.stages[stage.index()].record(duration);
+    }
+
+    pub fn stage(&self, stage: PipelineStage) -> &LatencyHistogram {
+        &self.stages[stage.index()]
+    }
+
+    /// Record the destination lock wait and render time of one frame
+    pub fn record_render(&mut self, timing: RenderTiming) {
+        self.record(PipelineStage::DestinationLock, timing.lock_wait);
+        match timing.render {
+            Some(render) => {
+                self.record(PipelineStage::Render, render);
+                self.frames_rendered += 1;
+            }
+            None => self.render_skipped += 1,
+        }
+    }
+
+    /// Count a frame not rendered because it matched the previous one
+    pub fn record_unchanged(&mut self) {
+        self.unchanged_skipped += 1;
+    }
+
+    /// Update the engine's running drop count (`CaptureFrame::dropped_frames`)
+    pub fn set_engine_dropped(&mut self, dropped: u64) {
+        self.engine_dropped = self.engine_dropped.max(dropped);
+    }
+
+    /// Update the frames discarded by the delivery queue (`FrameReceiver::dropped`)
+    pub fn set_queue_dropped(&mut self, dropped: u64) {
+        self.queue_dropped = self.queue_dropped.max(dropped);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let summary = |stage: PipelineStage| self.stage(stage).summary();
+        MetricsSnapshot {
+            acquire: summary(PipelineStage::Acquire),
+            process: summary(PipelineStage::Process),
+            render: summary(PipelineStage::Render),
+            destination_lock: summary(PipelineStage::DestinationLock),
+            frames_rendered: self.frames_rendered,
+            render_skipped: self.render_skipped,
+            unchanged_skipped: self.unchanged_skipped,
+            engine_dropped: self.engine_dropped,
+            queue_dropped: self.queue_dropped,
+        }
+    }
+}
+
+/// Point-in-time view of `CaptureMetrics`, serializable for the frontend
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    pub acquire: LatencySummary,
+    pub process: LatencySummary,
+    pub render: LatencySummary,
+    pub destination_lock: LatencySummary,
+    /// Frames drawn in the destination window
+    pub frames_rendered: u64,
+    /// Frames not drawn because the destination window was busy or missing
+    pub render_skipped: u64,
+    /// Frames not drawn because nothing changed since the previous one
+    pub unchanged_skipped: u64,
+    /// Frames the engine produced but never delivered
+    pub engine_dropped: u64,
+    /// Frames discarded because the render loop fell behind the pump
+    pub queue_dropped: u64,
+}
+
+impl MetricsSnapshot {
+    pub fn stage(&self, stage: PipelineStage) -> &LatencySummary {
+        match stage {
+            PipelineStage::Acquire => &self.acquire,
+            PipelineStage::Process => &self.process,
+            PipelineStage::Render => &self.render,
+            PipelineStage::DestinationLock => &self.destination_lock,
+        }
+    }
+}
+
+/// One-line summary for logs: p50/p99 per stage, then the counters
+impl fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for stage in PipelineStage::ALL {
+            let summary = self.stage(stage);
+            write!(
+                f,
+                "{} p50 {:.2}ms p99 {:.2}ms, ",
+                stage.name(),
+                summary.p50_ms,
+                summary.p99_ms
+            )?;
+        }
+        write!(
+            f,
+            "rendered {}, skipped {} (busy) {} (unchanged), dropped {} (engine) {} (queue)",
+            self.frames_rendered,
+            self.render_skipped,
+            self.unchanged_skipped,
+            self.engine_dropped,
+            self.queue_dropped
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(value: u64) -> Duration {
+        Duration::from_millis(value)
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let mut histogram = LatencyHistogram::new(100);
+        assert_eq!(histogram.percentile(50.0), None);
+        for value in 1..=100 {
+            histogram.record(ms(value));
+        }
+        assert_eq!(histogram.percentile(50.0), Some(ms(50)));
+        assert_eq!(histogram.percentile(99.0), Some(ms(99)));
+        assert_eq!(histogram.percentile(0.0), Some(ms(1)));
+
+        let summary = histogram.summary();
+        assert_eq!(summary.samples, 100);
+        assert!((summary.max_ms - 100.0).abs() < 1e-9);
+        assert!((summary.mean_ms - 50.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn window_keeps_the_latest_samples() {
+        let mut histogram = LatencyHistogram::new(3);
+        for value in [50, 1, 2, 3] {
+            histogram.record(ms(value));
+        }
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(histogram.percentile(100.0), Some(ms(3)));
+    }
+
+    #[test]
+    fn render_timings_and_drops_land_in_the_snapshot() {
+        let mut metrics = CaptureMetrics::new();
+        metrics.record(PipelineStage::Acquire, ms(4));
+        metrics.record_render(RenderTiming {
+            lock_wait: ms(1),
+            render: Some(ms(6)),
+        });
+        metrics.record_render(RenderTiming {
+            lock_wait: ms(0),
+            render: None,
+        });
+        metrics.record_unchanged();
+        metrics.set_engine_dropped(3);
+        metrics.set_engine_dropped(2);
+        metrics.set_queue_dropped(5);
+
+        let snapshot = metrics.snapshot();
+        assert!((snapshot.acquire.p50_ms - 4.0).abs() < 1e-9);
+        assert_eq!(snapshot.render.samples, 1);
+        assert_eq!(snapshot.destination_lock.samples, 2);
+        assert_eq!((snapshot.frames_rendered, snapshot.render_skipped), (1, 1));
+        assert_eq!(snapshot.unchanged_skipped, 1);
+        assert_eq!((snapshot.engine_dropped, snapshot.queue_dropped), (3, 5));
+        assert!(snapshot.to_string().contains("render p50 6.00ms"));
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+    }
+}
@@ -16,7 +16,84 @@ pub use macos::MacOSCaptureEngine;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+pub mod synthetic;
+pub use synthetic::{SyntheticCaptureEngine, SyntheticPattern};
+
+pub mod replay;
+pub use replay::{RawFrameWriter, ReplayAnchor, ReplayCaptureEngine};
+
+pub mod convert;
+
+pub mod stats;
+pub use stats::{CaptureStats, FrameStatsTracker};
+
+pub mod delivery;
+pub use delivery::{DropPolicy, FramePump, FrameReceiver, FrameSink, SharedCaptureEngine};
+
+pub mod pacing;
+pub use pacing::{FramePacer, PacingSettings, PacingStats};
+
+pub mod idle;
+pub use idle::{IdleDetector, IdleSettings};
+
+pub mod metrics;
+pub use metrics::{
+    CaptureMetrics, LatencyHistogram, LatencySummary, MetricsSnapshot, PipelineStage,
+    RenderTiming, SharedCaptureMetrics,
+};
+
+pub mod broadcast;
+pub use broadcast::{FrameBroadcaster, SharedFrame};
+
+pub mod pool;
+pub use pool::{FramePool, PoolStats};
+
+pub mod damage;
+pub use damage::TileDiffer;
+
+pub mod scale;
+pub use scale::{FitMode, FrameScaler, ScaleFilter};
+
+pub mod pipeline;
+pub use pipeline::{FramePipeline, FrameProcessor, ProcessorConfig};
+
+pub mod redact;
+pub use redact::{RedactionProcessor, RedactionStyle, RedactionZone, MAX_BLUR_RADIUS};
+
+pub mod overlay;
+pub use overlay::{OverlayPosition, OverlayProcessor, OverlaySettings};
+
+pub mod clicks;
+pub use clicks::{
+    ClickButton, ClickEvent, ClickHighlightProcessor, ClickHighlightStyle, ClickHighlighter,
+    ClickMark,
+};
+
+pub mod cursor;
+pub use cursor::{CursorCompositor, CursorImage, CursorStyle};
+
+pub mod zoom;
+pub use zoom::{FollowZoom, ZoomControl, ZoomProcessor, ZoomSettings};
+
+pub mod compose;
+pub use compose::{
+    CompositeLayout, CompositePlan, CompositeSettings, MultiRegionCaptureEngine, RegionCompositor,
+};
+
+pub mod span;
+pub use span::{EngineFactory, SpanMonitor, SpanSettings, SpanningCaptureEngine};
+
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(feature = "stream")]
+pub use stream::{FrameStream, FrameStreamOptions};
+
+mod geometry;
+mod glyphs;
+
 /// Screen region to capture
+///
+/// Intersection, insets, DPI scaling and monitor splitting live in `geometry`.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CaptureRect {
     pub x: i32,
@@ -36,6 +113,36 @@ impl CaptureRect {
     }
 }
 
+/// What a capture session records
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaptureTarget {
+    /// A fixed screen rectangle
+    Region(CaptureRect),
+    /// A single window by native id (X11 window, HWND, CGWindowID), captured
+    /// wherever it is and, where the backend allows, even when covered
+    Window(u64),
+    /// A whole monitor, by index in the backend's monitor order
+    Monitor(usize),
+}
+
+/// Kinds of `CaptureTarget`, for `CaptureEngine::supported_targets`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaptureTargetKind {
+    Region,
+    Window,
+    Monitor,
+}
+
+impl CaptureTarget {
+    pub fn kind(&self) -> CaptureTargetKind {
+        match self {
+            CaptureTarget::Region(_) => CaptureTargetKind::Region,
+            CaptureTarget::Window(_) => CaptureTargetKind::Window,
+            CaptureTarget::Monitor(_) => CaptureTargetKind::Monitor,
+        }
+    }
+}
+
 /// Capture settings
 #[derive(Debug, Clone)]
 pub struct CaptureSettings {
@@ -54,11 +161,31 @@ impl Default for CaptureSettings {
     }
 }
 
+/// Byte layout of `CaptureFrame::data`
+///
+/// Packed formats store one pixel per 3 or 4 bytes; NV12 and I420 store a full
+/// resolution luma plane followed by 2x2-subsampled chroma (see `convert`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PixelFormat {
+    /// B, G, R, A bytes (Windows, Linux X11)
+    Bgra8,
+    /// R, G, B, A bytes (macOS)
+    Rgba8,
+    /// R, G, B bytes, no alpha
+    Rgb8,
+    /// Y plane followed by interleaved UV plane
+    Nv12,
+    /// Y plane followed by separate U and V planes
+    I420,
+}
+
 /// A captured frame containing pixel data
 #[derive(Debug)]
 pub struct CaptureFrame {
-    /// BGRA pixel data (CPU fallback)
+    /// Pixel data in `pixel_format` (CPU fallback)
     pub data: Vec<u8>,
+    /// Layout of `data`
+    pub pixel_format: PixelFormat,
     /// Frame width in pixels
     pub width: u32,
     /// Frame height in pixels
@@ -75,6 +202,59 @@ pub struct CaptureFrame {
     /// - Windows: D3D11 texture handle
     /// - Linux: DMA-BUF file descriptor
     pub gpu_texture: Option<GpuTextureHandle>,
+    /// Monotonic time the pixels were captured
+    pub captured_at: std::time::Instant,
+    /// Position of this frame among those delivered in the session, starting at 0
+    pub sequence: u64,
+    /// Frames dropped in the session before this one was delivered
+    pub dropped_frames: u64,
+    /// Areas that changed since the previous frame, in frame pixel coordinates
+    ///
+    /// `None` means unknown (treat the whole frame as changed); an empty list
+    /// means nothing changed. See `damage::TileDiffer` for a CPU fallback.
+    pub damage: Option<Vec<CaptureRect>>,
+}
+
+impl CaptureFrame {
+    /// Create a BGRA CPU frame with tightly packed rows and no screen offset
+    pub fn new(data: Vec<u8>, width: u32, height: u32) -> Self {
+        Self {
+            data,
+            pixel_format: PixelFormat::Bgra8,
+            width,
+            height,
+            stride: width * 4,
+            offset_x: 0,
+            offset_y: 0,
+            gpu_texture: None,
+            captured_at: std::time::Instant::now(),
+            sequence: 0,
+            dropped_frames: 0,
+            damage: None,
+        }
+    }
+
+    /// Set the screen position this frame was captured from
+    pub fn with_offset(mut self, offset_x: i32, offset_y: i32) -> Self {
+        self.offset_x = offset_x;
+        self.offset_y = offset_y;
+        self
+    }
+
+    /// Declare the layout of `data`; the stride is reset to the packed row pitch
+    pub fn with_pixel_format(mut self, pixel_format: PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self.stride = pixel_format.packed_stride(self.width);
+        self
+    }
+
+    /// Take the pixel data as tightly packed `target` bytes
+    ///
+    /// Moves the buffer out without copying when it already matches.
+    pub fn into_packed(mut self, target: PixelFormat) -> anyhow::Result<Vec<u8>> {
+        convert::convert_frame(&mut self, target)?;
+        Ok(self.data)
+    }
 }
 
 /// Platform-specific GPU texture handle
@@ -109,6 +289,38 @@ pub enum GpuTextureHandle {
     },
 }
 
+impl GpuTextureHandle {
+    /// Drop the reference the engine took for the renderer
+    ///
+    /// For textures that will never reach a renderer, which would otherwise
+    /// release them after drawing.
+    pub fn release(self) {
+        match self {
+            #[cfg(target_os = "macos")]
+            GpuTextureHandle::Metal { iosurface_ptr, .. } => {
+                #[link(name = "CoreFoundation", kind = "framework")]
+                extern "C" {
+                    fn CFRelease(cf: *mut std::ffi::c_void);
+                }
+                if !iosurface_ptr.is_null() {
+                    unsafe { CFRelease(iosurface_ptr) };
+                }
+            }
+            #[cfg(target_os = "windows")]
+            GpuTextureHandle::D3D11 { texture_ptr, .. } => {
+                if texture_ptr != 0 {
+                    use windows::core::Interface;
+                    use windows::Win32::Graphics::Direct3D11::ID3D11Texture2D;
+                    // from_raw takes over the engine's AddRef; dropping it releases
+                    drop(unsafe { ID3D11Texture2D::from_raw(texture_ptr as *mut _) });
+                }
+            }
+            #[cfg(target_os = "linux")]
+            GpuTextureHandle::DmaBuf { .. } => {}
+        }
+    }
+}
+
 /// Trait for platform-specific capture engines
 pub trait CaptureEngine: Send {
     /// Start capturing the specified region
@@ -124,6 +336,27 @@ pub trait CaptureEngine: Send {
         excluded_windows: Option<Vec<crate::window_filter::WindowIdentifier>>,
     ) -> anyhow::Result<()>;
 
+    /// Target kinds `start_target` accepts; every engine supports `Region`
+    fn supported_targets(&self) -> &'static [CaptureTargetKind] {
+        &[CaptureTargetKind::Region]
+    }
+
+    /// Start capturing a region, window or monitor
+    ///
+    /// The default implementation handles `Region` through `start` and rejects
+    /// the rest; backends that advertise more targets override it.
+    fn start_target(
+        &mut self,
+        target: CaptureTarget,
+        show_cursor: bool,
+        excluded_windows: Option<Vec<crate::window_filter::WindowIdentifier>>,
+    ) -> anyhow::Result<()> {
+        match target {
+            CaptureTarget::Region(region) => self.start(region, show_cursor, excluded_windows),
+            other => anyhow::bail!("{:?} capture is not supported by this engine", other.kind()),
+        }
+    }
+
     /// Stop the capture session
     fn stop(&mut self);
 
@@ -140,6 +373,17 @@ pub trait CaptureEngine: Send {
     /// Update cursor visibility setting
     fn set_cursor_visible(&mut self, visible: bool) -> anyhow::Result<()>;
 
+    /// Update scale, halo and auto-hide for software-composited cursors
+    /// Backends whose cursor is drawn by the OS ignore this
+    fn set_cursor_style(&mut self, _style: CursorStyle) -> anyhow::Result<()> {
+        Ok(()) // Default implementation does nothing
+    }
+
+    /// Always copy frames to CPU memory instead of handing out GPU-only textures
+    /// Set while the frame pipeline has stages that must see every frame
+    /// (redaction, watermarks); backends that always fill `CaptureFrame::data` ignore this
+    fn set_cpu_frames_required(&mut self, _required: bool) {}
+
     /// Get the current capture region
     fn get_region(&self) -> Option<CaptureRect>;
 
@@ -152,6 +396,11 @@ pub trait CaptureEngine: Send {
         Ok(()) // Default implementation does nothing
     }
 
+    /// Delivery statistics for the current session (reset by `start`)
+    fn stats(&self) -> CaptureStats {
+        CaptureStats::default()
+    }
+
     /// Downcast to Any for platform-specific access
     fn as_any(&self) -> &dyn std::any::Any;
 }
@@ -0,0 +1,462 @@
+// capture/overlay.rs - Watermark and Text Overlay
+//
+// Composites a PNG logo and/or a templated text label onto outgoing frames, e.g.
+// a "CONFIDENTIAL" mark on recordings of internal demos. The logo and label form
+// one block anchored to a corner (or the center) with a margin, and are blended
+// with a global opacity. Text uses the built-in bitmap font, so only ASCII
+// letters, digits and basic punctuation render.
+//
+// Template placeholders: {presenter}, {date} (YYYY-MM-DD), {time} (HH:MM:SS),
+// {datetime} and {frame}. Date and time are local wall-clock time.
+
+use super::pipeline::FrameProcessor;
+use super::replay::decode_png;
+use super::{convert, glyphs, CaptureFrame, PixelFormat};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Where the overlay block is anchored in the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// Watermark configuration as stored in Settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    pub enabled: bool,
+    /// PNG drawn to the left of the text
+    pub logo_path: Option<PathBuf>,
+    /// Text template, e.g. "CONFIDENTIAL - {presenter} - {time}"
+    pub text: String,
+    /// Value substituted for {presenter}
+    pub presenter: String,
+    /// Text color as [R, G, B, A]
+    pub text_color: [u8; 4],
+    /// Font pixel size multiplier (1 = 5x7 pixel glyphs)
+    pub text_scale: u32,
+    pub position: OverlayPosition,
+    /// 0.0 (invisible) to 1.0 (opaque)
+    pub opacity: f32,
+    /// Distance from the frame edges in pixels
+    pub margin: u32,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            logo_path: None,
+            text: String::new(),
+            presenter: String::new(),
+            text_color: [255, 255, 255, 255],
+            text_scale: 3,
+            position: OverlayPosition::default(),
+            opacity: 0.6,
+            margin: 16,
+        }
+    }
+}
+
+/// Fill in template placeholders for one frame
+pub fn expand_template(
+    template: &str,
+    presenter: &str,
+    now: &chrono::DateTime<chrono::Local>,
+    frame: u64,
+) -> String {
+    template
+        .replace("{presenter}", presenter)
+        .replace("{datetime}", &now.format("%Y-%m-%d %H:%M:%S").to_string())
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H:%M:%S").to_string())
+        .replace("{frame}", &frame.to_string())
+}
+
+/// Straight-alpha RGBA image blended onto frames
+struct Stamp {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+/// Pipeline stage drawing the configured logo and text
+pub struct OverlayProcessor {
+    settings: OverlaySettings,
+    logo: Option<Stamp>,
+    /// Rendered label, reused while the expanded text stays the same
+    label: Option<(String, Stamp)>,
+}
+
+impl OverlayProcessor {
+    /// Load the logo (if any) up front so a bad path is reported once
+    ///
+    /// A logo that fails to load is left out when there is text to draw
+    /// instead; without text it's an error, as the overlay would be empty.
+    pub fn new(settings: OverlaySettings) -> Result<Self> {
+        let logo = match &settings.logo_path {
+            Some(path) => match load_logo(path) {
+                Ok(logo) => Some(logo),
+                Err(e) if !settings.text.is_empty() => {
+                    log::warn!("{:#}; drawing the watermark text only", e);
+                    None
+                }
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        Ok(Self {
+            settings,
+            logo,
+            label: None,
+        })
+    }
+
+    fn label(&mut self, frame: u64) -> Option<&Stamp> {
+        if self.settings.text.is_empty() {
+            return None;
+        }
+        let text = expand_template(
+            &self.settings.text,
+            &self.settings.presenter,
+            &chrono::Local::now(),
+            frame,
+        );
+        let stale = !matches!(&self.label, Some((cached, _)) if *cached == text);
+        if stale {
+            let stamp = render_text(&text, self.settings.text_scale, self.settings.text_color);
+            self.label = Some((text, stamp));
+        }
+        self.label
+            .as_ref()
+            .map(|(_, stamp)| stamp)
+            .filter(|s| s.width > 0)
+    }
+}
+
+impl FrameProcessor for OverlayProcessor {
+    fn name(&self) -> &str {
+        "overlay"
+    }
+
+    fn is_required(&self) -> bool {
+        self.settings.enabled
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        if !self.settings.enabled {
+            return Ok(());
+        }
+        if frame.pixel_format.is_planar() {
+            convert::convert_frame(frame, PixelFormat::Bgra8)?;
+        }
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+
+        let opacity = self.settings.opacity.clamp(0.0, 1.0);
+        let margin = self.settings.margin as i64;
+        let position = self.settings.position;
+        let gap = self.settings.text_scale.max(1) as i64 * 3;
+
+        // Take the logo out so `label` can borrow self mutably
+        let logo = self.logo.take();
+        let label = self.label(frame.sequence);
+        let parts: Vec<&Stamp> = logo.iter().chain(label).collect();
+        if !parts.is_empty() {
+            // Logo and label side by side, vertically centered on each other
+            let block_w =
+                parts.iter().map(|s| s.width as i64).sum::<i64>() + gap * (parts.len() as i64 - 1);
+            let block_h = parts.iter().map(|s| s.height as i64).max().unwrap_or(0);
+            let (fw, fh) = (frame.width as i64, frame.height as i64);
+            let (x, y) = match position {
+                OverlayPosition::TopLeft => (margin, margin),
+                OverlayPosition::TopRight => (fw - margin - block_w, margin),
+                OverlayPosition::BottomLeft => (margin, fh - margin - block_h),
+                OverlayPosition::BottomRight => (fw - margin - block_w, fh - margin - block_h),
+                OverlayPosition::Center => ((fw - block_w) / 2, (fh - block_h) / 2),
+            };
+
+            let mut pen_x = x;
+            for stamp in parts {
+                let stamp_y = y + (block_h - stamp.height as i64) / 2;
+                blend(frame, stamp, pen_x, stamp_y, opacity);
+                pen_x += stamp.width as i64 + gap;
+            }
+        }
+        self.logo = logo;
+        Ok(())
+    }
+}
+
+fn load_logo(path: &std::path::Path) -> Result<Stamp> {
+    let image =
+        decode_png(path).with_context(|| format!("Failed to load logo {}", path.display()))?;
+    let data = if image.pixel_format == PixelFormat::Rgba8 {
+        image.data
+    } else {
+        convert::to_packed(&image, PixelFormat::Rgba8)?
+    };
+    Ok(Stamp {
+        data,
+        width: image.width,
+        height: image.height,
+    })
+}
+
+fn render_text(text: &str, scale: u32, color: [u8; 4]) -> Stamp {
+    let (width, height) = glyphs::text_size(text, scale.max(1));
+    let mut canvas = CaptureFrame::new(vec![0; (width * height * 4) as usize], width, height)
+        .with_pixel_format(PixelFormat::Rgba8);
+    glyphs::draw_text(&mut canvas, 0, 0, text, scale.max(1), color);
+    Stamp {
+        data: canvas.data,
+        width,
+        height,
+    }
+}
+
+fn blend(frame: &mut CaptureFrame, stamp: &Stamp, x: i64, y: i64, opacity: f32) {
+    blend_rgba(
+        frame,
+        &stamp.data,
+        (stamp.width, stamp.height),
+        (x, y),
+        opacity,
+    );
+}
+
+/// Alpha-blend a straight-alpha RGBA image of `size` onto `frame` at `at`,
+/// leaving the frame's alpha as is
+pub(crate) fn blend_rgba(
+    frame: &mut CaptureFrame,
+    rgba: &[u8],
+    (width, height): (u32, u32),
+    (x, y): (i64, i64),
+    opacity: f32,
+) {
+    let bpp = frame.pixel_format.bytes_per_pixel() as usize;
+    let (r, b) = match frame.pixel_format {
+        PixelFormat::Bgra8 => (2, 0),
+        _ => (0, 2),
+    };
+
+    for sy in 0..height as i64 {
+        let fy = y + sy;
+        if fy < 0 || fy >= frame.height as i64 {
+            continue;
+        }
+        for sx in 0..width as i64 {
+            let fx = x + sx;
+            if fx < 0 || fx >= frame.width as i64 {
+                continue;
+            }
+            let src = &rgba[((sy * width as i64 + sx) * 4) as usize..][..4];
+            let alpha = src[3] as f32 / 255.0 * opacity;
+            if alpha <= 0.0 {
+                continue;
+            }
+            let offset = fy as usize * frame.stride as usize + fx as usize * bpp;
+            let dst = &mut frame.data[offset..offset + bpp];
+            for (channel, value) in [(r, src[0]), (1, src[1]), (b, src[2])] {
+                let mixed = value as f32 * alpha + dst[channel] as f32 * (1.0 - alpha);
+                dst[channel] = mixed.round() as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn black(width: u32, height: u32) -> CaptureFrame {
+        CaptureFrame::new(vec![0; (width * height * 4) as usize], width, height)
+    }
+
+    fn lit_bounds(frame: &CaptureFrame) -> Option<(u32, u32, u32, u32)> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let offset = (y * frame.stride + x * 4) as usize;
+                if frame.data[offset..offset + 3].iter().any(|&v| v > 0) {
+                    let (x0, y0, x1, y1) = bounds.unwrap_or((x, y, x, y));
+                    bounds = Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)));
+                }
+            }
+        }
+        bounds
+    }
+
+    fn text_overlay(text: &str, position: OverlayPosition, opacity: f32) -> OverlayProcessor {
+        OverlayProcessor::new(OverlaySettings {
+            enabled: true,
+            text: text.to_string(),
+            text_scale: 1,
+            position,
+            opacity,
+            margin: 4,
+            ..OverlaySettings::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn expands_placeholders() {
+        let now = chrono::Local
+            .with_ymd_and_hms(2024, 3, 9, 14, 5, 7)
+            .unwrap();
+        assert_eq!(
+            expand_template(
+                "{presenter} {date} {time} #{frame} {unknown}",
+                "Ana",
+                &now,
+                12
+            ),
+            "Ana 2024-03-09 14:05:07 #12 {unknown}"
+        );
+        assert_eq!(
+            expand_template("{datetime}", "", &now, 0),
+            "2024-03-09 14:05:07"
+        );
+    }
+
+    #[test]
+    fn text_is_anchored_with_margin() {
+        let mut frame = black(100, 50);
+        text_overlay("CONFIDENTIAL", OverlayPosition::BottomRight, 1.0)
+            .process(&mut frame)
+            .unwrap();
+        let (_, _, x1, y1) = lit_bounds(&frame).unwrap();
+        // The final L fills its whole cell width
+        assert_eq!(x1, 100 - 4 - 1);
+        assert_eq!(y1, 50 - 4 - 1);
+
+        let mut frame = black(100, 50);
+        text_overlay("X", OverlayPosition::TopLeft, 1.0)
+            .process(&mut frame)
+            .unwrap();
+        let (x0, y0, _, _) = lit_bounds(&frame).unwrap();
+        assert_eq!((x0, y0), (4, 4));
+    }
+
+    #[test]
+    fn opacity_blends_with_frame() {
+        let mut frame = black(40, 20);
+        text_overlay("I", OverlayPosition::Center, 0.5)
+            .process(&mut frame)
+            .unwrap();
+        let brightest = frame.data.iter().copied().max().unwrap();
+        assert!((127..=128).contains(&brightest), "got {}", brightest);
+        // Frame alpha is left alone
+        assert!(frame.data.chunks_exact(4).all(|px| px[3] == 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn watermark_reaches_gpu_frames() {
+        use crate::capture::{FramePipeline, GpuTextureHandle};
+
+        let mut pipeline =
+            FramePipeline::new().with_stage(text_overlay("X", OverlayPosition::TopLeft, 1.0));
+        // Engines are told to keep copying pixels so the watermark can't be skipped
+        assert!(pipeline.requires_cpu_frames());
+        let mut frame = black(32, 16);
+        frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+            fd: -1,
+            width: 32,
+            height: 16,
+            stride: 32 * 4,
+            format: 0,
+        });
+        pipeline.process(&mut frame);
+        // Rendered from the stamped pixels, not the untouched texture
+        assert!(frame.gpu_texture.is_none());
+        assert!(lit_bounds(&frame).is_some());
+    }
+
+    #[test]
+    fn disabled_overlay_is_a_no_op() {
+        let mut overlay = OverlayProcessor::new(OverlaySettings {
+            text: "CONFIDENTIAL".to_string(),
+            ..OverlaySettings::default()
+        })
+        .unwrap();
+        let mut frame = black(64, 16);
+        overlay.process(&mut frame).unwrap();
+        assert!(lit_bounds(&frame).is_none());
+    }
+
+    #[test]
+    fn logo_sits_left_of_text() {
+        let path = std::env::temp_dir().join(format!("rustframe-logo-{}.png", std::process::id()));
+        {
+            let file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+            let mut encoder = png::Encoder::new(file, 3, 3);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[[0, 0, 255, 255]; 9].concat())
+                .unwrap();
+        }
+
+        let mut overlay = OverlayProcessor::new(OverlaySettings {
+            enabled: true,
+            logo_path: Some(path.clone()),
+            text: "I".to_string(),
+            text_scale: 1,
+            position: OverlayPosition::TopLeft,
+            opacity: 1.0,
+            margin: 2,
+            ..OverlaySettings::default()
+        })
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut frame = black(32, 16);
+        overlay.process(&mut frame).unwrap();
+        // Blue logo (BGRA) at the margin, vertically centered on the 7px label
+        let offset = (4 * frame.stride + 2 * 4) as usize;
+        assert_eq!(&frame.data[offset..offset + 4], &[255, 0, 0, 0]);
+        let (x0, y0, x1, _) = lit_bounds(&frame).unwrap();
+        assert_eq!((x0, y0), (2, 2));
+        assert!(x1 > 2 + 3 + 3);
+    }
+
+    #[test]
+    fn missing_logo_falls_back_to_text() {
+        let settings = OverlaySettings {
+            enabled: true,
+            logo_path: Some(PathBuf::from("/nonexistent/logo.png")),
+            text_scale: 1,
+            position: OverlayPosition::TopLeft,
+            margin: 4,
+            ..OverlaySettings::default()
+        };
+        // Nothing left to draw without the logo
+        assert!(OverlayProcessor::new(settings.clone()).is_err());
+
+        let mut overlay = OverlayProcessor::new(OverlaySettings {
+            text: "X".to_string(),
+            ..settings
+        })
+        .unwrap();
+        let mut frame = black(20, 20);
+        overlay.process(&mut frame).unwrap();
+        let (x0, y0, _, _) = lit_bounds(&frame).unwrap();
+        assert_eq!((x0, y0), (4, 4));
+    }
+}
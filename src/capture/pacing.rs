@@ -0,0 +1,340 @@
+// capture/pacing.rs - Frame Pacing
+//
+// Sleeping a fixed interval after every frame adds the capture time to each
+// period, so the loop runs slower than asked and drifts further the busier it
+// gets. A FramePacer keeps an absolute schedule instead: every frame has a
+// deadline one interval after the previous one, the wait is whatever is left
+// of the slot, and slots that were overrun are skipped rather than made up in
+// a burst.
+//
+// The pacer also measures how late frames start (jitter), and with `adaptive`
+// enabled it lowers the rate when frames keep overrunning their budget and
+// raises it again once there is headroom, never below `min_fps`.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Shortest interval a pacer runs at (1000 fps)
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Frames looked at before the adaptive rate changes
+const ADAPT_WINDOW: u32 = 30;
+
+/// Pacing behavior, usually from the `frame_pacing` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PacingSettings {
+    /// Lower the rate while frames overrun their budget, restore it once they fit
+    pub adaptive: bool,
+    /// Floor for the adaptive rate
+    pub min_fps: u32,
+    /// Resend the last frame when the engine has nothing new, so consumers see
+    /// a constant frame rate
+    pub duplicate_frames: bool,
+}
+
+impl Default for PacingSettings {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            min_fps: 15,
+            duplicate_frames: false,
+        }
+    }
+}
+
+/// Schedule and timing statistics of a pacer
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PacingStats {
+    /// Rate the pacer was asked for
+    pub target_fps: f64,
+    /// Rate it is running at (lower than `target_fps` while adaptively slowed)
+    pub current_fps: f64,
+    /// Frames that ran on schedule
+    pub frames: u64,
+    /// Frames whose work took longer than their slot
+    pub overruns: u64,
+    /// Slots skipped because a frame ran past them
+    pub missed_slots: u64,
+    /// Frames resent because the engine had nothing new
+    pub duplicated: u64,
+    /// Mean time frames started after their deadline
+    pub mean_jitter: Duration,
+    /// Latest any frame started after its deadline
+    pub max_jitter: Duration,
+}
+
+/// Drift-free frame scheduler
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    settings: PacingSettings,
+    /// Interval for the requested rate
+    target_interval: Duration,
+    /// Interval currently in use (longer while adaptively slowed)
+    interval: Duration,
+    next_deadline: Option<Instant>,
+    window_frames: u32,
+    window_overruns: u32,
+    window_max_work: Duration,
+    total_jitter: Duration,
+    stats: PacingStats,
+}
+
+impl FramePacer {
+    /// Pacer running at `interval` per frame
+    pub fn new(interval: Duration, settings: PacingSettings) -> Self {
+        let interval = interval.max(MIN_INTERVAL);
+        let mut pacer = Self {
+            settings,
+            target_interval: interval,
+            interval,
+            next_deadline: None,
+            window_frames: 0,
+            window_overruns: 0,
+            window_max_work: Duration::default(),
+            total_jitter: Duration::default(),
+            stats: PacingStats::default(),
+        };
+        pacer.update_rates();
+        pacer
+    }
+
+    /// Pacer running at `fps` frames per second
+    pub fn with_fps(fps: u32, settings: PacingSettings) -> Self {
+        Self::new(Self::interval_for_fps(fps), settings)
+    }
+
+    /// Frame interval for `fps`; zero is treated as 1 fps instead of dividing by zero
+    pub fn interval_for_fps(fps: u32) -> Duration {
+        Duration::from_nanos(1_000_000_000 / fps.max(1) as u64).max(MIN_INTERVAL)
+    }
+
+    pub fn settings(&self) -> &PacingSettings {
+        &self.settings
+    }
+
+    /// Change the pacing behavior; an adaptive slowdown is undone
+    pub fn set_settings(&mut self, settings: PacingSettings) {
+        self.settings = settings;
+        self.interval = self.target_interval;
+        self.reset_window();
+        self.update_rates();
+    }
+
+    /// Interval currently in use
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Change the requested interval, starting the schedule again from the next frame
+    pub fn set_interval(&mut self, interval: Duration) {
+        let interval = interval.max(MIN_INTERVAL);
+        if interval == self.target_interval {
+            return;
+        }
+        self.target_interval = interval;
+        self.interval = interval;
+        self.next_deadline = None;
+        self.reset_window();
+        self.update_rates();
+    }
+
+    /// Whether the current slot has started, i.e. `now` is not an early wake-up
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.next_deadline.map_or(true, |deadline| now >= deadline)
+    }
+
+    /// Record a frame that started at `started` and finished at `now`, and
+    /// return how long to wait before the next one
+    ///
+    /// Frames started before their deadline (woken early, e.g. for a region
+    /// change) don't move the schedule; the wait is just the rest of the slot.
+    pub fn frame_done(&mut self, started: Instant, now: Instant) -> Duration {
+        let deadline = match self.next_deadline {
+            Some(deadline) if started < deadline => {
+                return deadline.saturating_duration_since(now);
+            }
+            Some(deadline) => deadline,
+            None => started,
+        };
+
+        let jitter = started - deadline;
+        self.total_jitter += jitter;
+        self.stats.max_jitter = self.stats.max_jitter.max(jitter);
+        self.stats.frames += 1;
+        self.stats.mean_jitter = self.total_jitter / self.stats.frames.min(u32::MAX as u64) as u32;
+
+        let work = now.saturating_duration_since(started);
+        if work > self.interval {
+            self.stats.overruns += 1;
+            self.window_overruns += 1;
+        }
+        self.window_max_work = self.window_max_work.max(work);
+        self.window_frames += 1;
+        if self.window_frames >= ADAPT_WINDOW {
+            self.adapt();
+        }
+
+        let mut next = deadline + self.interval;
+        if next < now {
+            // Skip the slots this frame ran over instead of bursting to catch up
+            let behind = (now - next).as_nanos() / self.interval.as_nanos();
+            let skipped = behind as u32 + 1;
+            next += self.interval * skipped;
+            self.stats.missed_slots += skipped as u64;
+        }
+        self.next_deadline = Some(next);
+        next - now
+    }
+
+    /// Count a frame resent because the engine had nothing new
+    pub fn record_duplicate(&mut self) {
+        self.stats.duplicated += 1;
+    }
+
+    pub fn stats(&self) -> PacingStats {
+        self.stats
+    }
+
+    /// Slow down after a window of mostly overrun frames, speed up after one with headroom
+    fn adapt(&mut self) {
+        if self.settings.adaptive {
+            let slowest = self
+                .target_interval
+                .max(Self::interval_for_fps(self.settings.min_fps));
+            let faster = self.target_interval.max(self.interval * 3 / 4);
+            if self.window_overruns * 4 >= self.window_frames * 3 {
+                let slower = slowest.min(self.interval * 4 / 3);
+                if slower != self.interval {
+                    log::debug!("Frames overrun their budget, pacing at {:?}", slower);
+                    self.interval = slower;
+                }
+            } else if self.interval > self.target_interval
+                && self.window_overruns == 0
+                && self.window_max_work * 4 <= faster * 3
+            {
+                log::debug!("Frames fit their budget again, pacing at {:?}", faster);
+                self.interval = faster;
+            }
+            self.update_rates();
+        }
+        self.reset_window();
+    }
+
+    fn reset_window(&mut self) {
+        self.window_frames = 0;
+        self.window_overruns = 0;
+        self.window_max_work = Duration::default();
+    }
+
+    fn update_rates(&mut self) {
+        self.stats.target_fps = 1.0 / self.target_interval.as_secs_f64();
+        self.stats.current_fps = 1.0 / self.interval.as_secs_f64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(10);
+
+    /// Run `frames` frames that each take `work`, waiting exactly as told
+    fn run(pacer: &mut FramePacer, start: Instant, frames: u32, work: Duration) -> Instant {
+        let mut now = start;
+        for _ in 0..frames {
+            let started = now;
+            now += work;
+            now += pacer.frame_done(started, now);
+        }
+        now
+    }
+
+    #[test]
+    fn zero_fps_does_not_divide_by_zero() {
+        assert_eq!(FramePacer::interval_for_fps(0), Duration::from_secs(1));
+        assert_eq!(
+            FramePacer::interval_for_fps(60),
+            Duration::from_nanos(16_666_666)
+        );
+        assert_eq!(FramePacer::interval_for_fps(100_000), MIN_INTERVAL);
+    }
+
+    #[test]
+    fn schedule_does_not_drift_with_work_time() {
+        let mut pacer = FramePacer::new(FRAME, PacingSettings::default());
+        let start = Instant::now();
+        // 100 frames with 3ms of work still take exactly 100 slots
+        let end = run(&mut pacer, start, 100, Duration::from_millis(3));
+        assert_eq!(end - start, FRAME * 100);
+
+        let stats = pacer.stats();
+        assert_eq!(stats.frames, 100);
+        assert_eq!((stats.overruns, stats.missed_slots), (0, 0));
+        assert_eq!(stats.max_jitter, Duration::default());
+    }
+
+    #[test]
+    fn overrun_skips_slots_and_counts_jitter() {
+        let mut pacer = FramePacer::new(FRAME, PacingSettings::default());
+        let start = Instant::now();
+        let wait = pacer.frame_done(start, start + Duration::from_millis(25));
+        // Slots at 10ms and 20ms were missed, the next one is at 30ms
+        assert_eq!(wait, Duration::from_millis(5));
+        assert_eq!(pacer.stats().missed_slots, 2);
+
+        // Woken early: the schedule is unchanged
+        let early = start + Duration::from_millis(27);
+        assert!(!pacer.is_due(early));
+        assert_eq!(pacer.frame_done(early, early), Duration::from_millis(3));
+        assert_eq!(pacer.stats().frames, 1);
+
+        // Starting 2ms late counts as jitter
+        let late = start + Duration::from_millis(32);
+        pacer.frame_done(late, late);
+        assert_eq!(pacer.stats().max_jitter, Duration::from_millis(2));
+        assert_eq!(pacer.stats().mean_jitter, Duration::from_millis(1));
+    }
+
+    #[test]
+    fn adaptive_rate_backs_off_and_recovers() {
+        let settings = PacingSettings {
+            min_fps: 50,
+            ..PacingSettings::default()
+        };
+        let mut pacer = FramePacer::new(FRAME, settings);
+        let start = Instant::now();
+
+        // Consistently 25ms of work in a 10ms budget: slow down, but not below 50 fps
+        let now = run(
+            &mut pacer,
+            start,
+            ADAPT_WINDOW * 4,
+            Duration::from_millis(25),
+        );
+        assert_eq!(pacer.interval(), Duration::from_millis(20));
+        assert!((pacer.stats().current_fps - 50.0).abs() < 1e-9);
+        assert!((pacer.stats().target_fps - 100.0).abs() < 1e-9);
+
+        // Light frames bring the rate back to the target
+        run(&mut pacer, now, ADAPT_WINDOW * 4, Duration::from_millis(1));
+        assert_eq!(pacer.interval(), FRAME);
+    }
+
+    #[test]
+    fn fixed_rate_ignores_overruns() {
+        let settings = PacingSettings {
+            adaptive: false,
+            ..PacingSettings::default()
+        };
+        let mut pacer = FramePacer::new(FRAME, settings);
+        run(
+            &mut pacer,
+            Instant::now(),
+            ADAPT_WINDOW * 2,
+            Duration::from_millis(15),
+        );
+        assert_eq!(pacer.interval(), FRAME);
+        assert_eq!(pacer.stats().overruns, ADAPT_WINDOW as u64 * 2);
+    }
+}
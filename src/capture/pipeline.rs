@@ -0,0 +1,478 @@
+// capture/pipeline.rs - Frame Processing Pipeline
+//
+// Everything that happens to a frame between capture and render (cropping,
+// scaling, overlays, filters) is a FrameProcessor stage. A FramePipeline runs its
+// stages in order, and can be built from a serializable list of
+// ProcessorConfig entries so the chain is driven by Settings instead of
+// arguments threaded through every platform service.
+
+use super::compose::filled_canvas;
+use super::convert::crop_to_region;
+use super::{convert, CaptureFrame, CaptureRect, FitMode, FrameScaler, PixelFormat, ScaleFilter};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// One step between capture and render
+///
+/// Stages work on CPU pixels in place, replacing `frame` outright when the size
+/// changes. On error the frame must be left usable so the pipeline can carry on.
+pub trait FrameProcessor: Send {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    /// Whether `process` would change `frame` right now
+    ///
+    /// A frame keeps its GPU texture when no stage is active, so stages that
+    /// are often idle (zoom at 1x) should say so here.
+    fn is_active(&self, _frame: &CaptureFrame) -> bool {
+        true
+    }
+
+    /// Whether no frame may be shown without this stage (redaction, watermarks)
+    ///
+    /// If such a stage can't run on a frame or fails, the pipeline blacks the
+    /// frame out rather than passing on the unprocessed pixels.
+    fn is_required(&self) -> bool {
+        false
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()>;
+}
+
+/// Ordered list of processing stages
+#[derive(Default)]
+pub struct FramePipeline {
+    stages: Vec<Box<dyn FrameProcessor>>,
+    warned_gpu_only: bool,
+}
+
+impl FramePipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the stages described by `configs`, in order
+    pub fn from_config(configs: &[ProcessorConfig]) -> Self {
+        let mut pipeline = Self::new();
+        for config in configs {
+            pipeline.push(config.build());
+        }
+        pipeline
+    }
+
+    /// Append a stage to the end of the pipeline
+    pub fn push(&mut self, stage: Box<dyn FrameProcessor>) {
+        self.stages.push(stage);
+    }
+
+    pub fn with_stage(mut self, stage: impl FrameProcessor + 'static) -> Self {
+        self.push(Box::new(stage));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Whether some stage must see every frame, so engines should always copy
+    /// frames to CPU memory instead of handing out GPU-only textures
+    pub fn requires_cpu_frames(&self) -> bool {
+        self.stages.iter().any(|stage| stage.is_required())
+    }
+
+    /// Stage names in execution order
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Run every stage over `frame`
+    ///
+    /// Stages only see CPU pixels, so a frame that also carries a GPU texture
+    /// loses it and is rendered from the processed pixels instead, unless no
+    /// stage is active for it. Frames with no CPU pixels at all pass through
+    /// untouched, unless a required stage is configured: then they are blacked
+    /// out. A failing stage is logged and skipped and the remaining stages still
+    /// run; if the stage was required the frame is blacked out first.
+    pub fn process(&mut self, frame: &mut CaptureFrame) {
+        if self.stages.is_empty() {
+            return;
+        }
+        if frame.gpu_texture.is_some() && !self.stages.iter().any(|stage| stage.is_active(frame)) {
+            return;
+        }
+        if frame.data.is_empty() {
+            if frame.gpu_texture.is_some() && !self.warned_gpu_only {
+                self.warned_gpu_only = true;
+                log::warn!("GPU-only frames can't be processed, the engine must copy them to CPU");
+            }
+            if !self.requires_cpu_frames() {
+                return;
+            }
+            black_out(frame);
+        }
+        // The texture would show the unprocessed image (and skip redaction)
+        if let Some(texture) = frame.gpu_texture.take() {
+            texture.release();
+        }
+        for stage in &mut self.stages {
+            if let Err(e) = stage.process(frame) {
+                if stage.is_required() {
+                    log::warn!(
+                        "Frame processor '{}' failed, blacking out the frame: {}",
+                        stage.name(),
+                        e
+                    );
+                    black_out(frame);
+                } else {
+                    log::warn!("Frame processor '{}' failed: {}", stage.name(), e);
+                }
+            }
+        }
+    }
+}
+
+/// Serializable description of a pipeline stage, as stored in Settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProcessorConfig {
+    /// Keep only a rectangle of the frame (frame-relative pixels)
+    Crop {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+    /// Resample to a fixed size
+    Scale {
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        filter: ScaleFilter,
+        #[serde(default)]
+        fit: FitMode,
+    },
+    /// Drop color, keeping luminance
+    Grayscale,
+}
+
+impl ProcessorConfig {
+    pub fn build(&self) -> Box<dyn FrameProcessor> {
+        match *self {
+            ProcessorConfig::Crop {
+                x,
+                y,
+                width,
+                height,
+            } => Box::new(CropProcessor::new(CaptureRect::new(x, y, width, height))),
+            ProcessorConfig::Scale {
+                width,
+                height,
+                filter,
+                fit,
+            } => Box::new(
+                FrameScaler::new(width, height)
+                    .with_filter(filter)
+                    .with_fit(fit),
+            ),
+            ProcessorConfig::Grayscale => Box::new(GrayscaleProcessor),
+        }
+    }
+}
+
+pub(crate) fn replace_frame(frame: &mut CaptureFrame, mut processed: CaptureFrame) {
+    processed.sequence = frame.sequence;
+    processed.dropped_frames = frame.dropped_frames;
+    std::mem::replace(frame, processed).recycle();
+}
+
+/// Replace `frame` with opaque black of the same size, dropping its texture
+fn black_out(frame: &mut CaptureFrame) {
+    if let Some(texture) = frame.gpu_texture.take() {
+        texture.release();
+    }
+    let data = filled_canvas((frame.width, frame.height), [0, 0, 0, 255]);
+    let mut black = CaptureFrame::new(data, frame.width, frame.height)
+        .with_offset(frame.offset_x, frame.offset_y);
+    black.captured_at = frame.captured_at;
+    replace_frame(frame, black);
+}
+
+impl FrameProcessor for FrameScaler {
+    fn name(&self) -> &str {
+        "scale"
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        let scaled = self.scale(frame)?;
+        replace_frame(frame, scaled);
+        Ok(())
+    }
+}
+
+/// Cuts a fixed rectangle out of every frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct CropProcessor {
+    /// Relative to the frame's top-left corner
+    rect: CaptureRect,
+}
+
+impl CropProcessor {
+    pub fn new(rect: CaptureRect) -> Self {
+        Self { rect }
+    }
+}
+
+impl FrameProcessor for CropProcessor {
+    fn name(&self) -> &str {
+        "crop"
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        let screen_rect = CaptureRect::new(
+            frame.offset_x + self.rect.x,
+            frame.offset_y + self.rect.y,
+            self.rect.width,
+            self.rect.height,
+        );
+        let cropped = crop_to_region(frame, &screen_rect)
+            .ok_or_else(|| anyhow!("Crop {:?} lies outside the frame", self.rect))?;
+        replace_frame(frame, cropped);
+        Ok(())
+    }
+}
+
+/// Converts packed frames to gray in place (BT.601 luma)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrayscaleProcessor;
+
+impl FrameProcessor for GrayscaleProcessor {
+    fn name(&self) -> &str {
+        "grayscale"
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        if frame.pixel_format.is_planar() {
+            convert::convert_frame(frame, PixelFormat::Bgra8)?;
+        }
+        let bpp = frame.pixel_format.bytes_per_pixel() as usize;
+        let (r, b) = match frame.pixel_format {
+            PixelFormat::Bgra8 => (2, 0),
+            _ => (0, 2),
+        };
+        let row_len = frame.width as usize * bpp;
+        for row in frame.data.chunks_mut(frame.stride as usize) {
+            let len = row_len.min(row.len());
+            for px in row[..len].chunks_exact_mut(bpp) {
+                let luma = (px[r] as u32 * 77 + px[1] as u32 * 150 + px[b] as u32 * 29) >> 8;
+                px[r] = luma as u8;
+                px[1] = luma as u8;
+                px[b] = luma as u8;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fill(u8);
+
+    impl FrameProcessor for Fill {
+        fn name(&self) -> &str {
+            "fill"
+        }
+
+        fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+            frame.data.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    struct Fail;
+
+    impl FrameProcessor for Fail {
+        fn name(&self) -> &str {
+            "fail"
+        }
+
+        fn process(&mut self, _frame: &mut CaptureFrame) -> Result<()> {
+            Err(anyhow!("boom"))
+        }
+    }
+
+    #[test]
+    fn stages_run_in_order_and_errors_are_skipped() {
+        let mut pipeline = FramePipeline::new()
+            .with_stage(Fill(1))
+            .with_stage(Fail)
+            .with_stage(Fill(2));
+        assert_eq!(pipeline.stage_names(), vec!["fill", "fail", "fill"]);
+
+        let mut frame = CaptureFrame::new(vec![0; 16], 2, 2);
+        pipeline.process(&mut frame);
+        assert!(frame.data.iter().all(|&b| b == 2));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn gpu_frames_are_redacted_from_cpu_pixels() {
+        use crate::capture::{GpuTextureHandle, RedactionProcessor, RedactionStyle, RedactionZone};
+
+        let zone = RedactionZone::new(
+            CaptureRect::new(0, 0, 2, 2),
+            RedactionStyle::Solid {
+                color: [1, 2, 3, 255],
+            },
+        );
+        let mut pipeline = FramePipeline::new().with_stage(RedactionProcessor::new(vec![zone]));
+        let mut frame = CaptureFrame::new(vec![0; 16], 2, 2);
+        frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+            fd: -1,
+            width: 2,
+            height: 2,
+            stride: 8,
+            format: 0,
+        });
+        pipeline.process(&mut frame);
+        assert!(frame.gpu_texture.is_none());
+        assert!(frame.data.chunks_exact(4).all(|px| px == [3, 2, 1, 255]));
+
+        // Without stages the texture is kept for zero-copy rendering
+        let mut frame = CaptureFrame::new(vec![0; 16], 2, 2);
+        frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+            fd: -1,
+            width: 2,
+            height: 2,
+            stride: 8,
+            format: 0,
+        });
+        FramePipeline::new().process(&mut frame);
+        assert!(frame.gpu_texture.is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn gpu_only_frames_are_blacked_out_when_redacting() {
+        use crate::capture::{GpuTextureHandle, RedactionProcessor, RedactionStyle, RedactionZone};
+
+        let zone = RedactionZone::new(CaptureRect::new(0, 0, 1, 1), RedactionStyle::default());
+        let mut pipeline = FramePipeline::new().with_stage(RedactionProcessor::new(vec![zone]));
+        assert!(pipeline.requires_cpu_frames());
+
+        let mut frame = CaptureFrame::new(Vec::new(), 2, 2).with_offset(5, 6);
+        frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+            fd: -1,
+            width: 2,
+            height: 2,
+            stride: 8,
+            format: 0,
+        });
+        frame.sequence = 9;
+        pipeline.process(&mut frame);
+        assert!(frame.gpu_texture.is_none());
+        assert_eq!((frame.width, frame.height), (2, 2));
+        assert_eq!((frame.offset_x, frame.offset_y, frame.sequence), (5, 6, 9));
+        assert!(frame.data.chunks_exact(4).all(|px| px == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn failed_redaction_blacks_out_the_frame() {
+        use crate::capture::{RedactionProcessor, RedactionStyle, RedactionZone};
+
+        let zone = RedactionZone::new(CaptureRect::new(0, 0, 1, 1), RedactionStyle::default());
+        let mut pipeline = FramePipeline::new()
+            .with_stage(RedactionProcessor::new(vec![zone]))
+            .with_stage(Fill(7));
+
+        // Too little data for a 2x2 frame, so redaction can't run
+        let mut frame = CaptureFrame::new(vec![200; 8], 2, 2);
+        pipeline.process(&mut frame);
+        assert_eq!(frame.data.len(), 16);
+        // Later stages still run on the blacked out frame
+        assert!(frame.data.iter().all(|&b| b == 7));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn idle_stages_keep_gpu_textures() {
+        use crate::capture::{FollowZoom, GpuTextureHandle, ZoomProcessor, ZoomSettings};
+        use std::sync::{Arc, Mutex};
+        use std::time::Instant;
+
+        let gpu_frame = || {
+            let mut frame = CaptureFrame::new(vec![0; 16], 2, 2);
+            frame.gpu_texture = Some(GpuTextureHandle::DmaBuf {
+                fd: -1,
+                width: 2,
+                height: 2,
+                stride: 8,
+                format: 0,
+            });
+            frame
+        };
+        let zoom = Arc::new(Mutex::new(FollowZoom::new(ZoomSettings {
+            transition_ms: 0,
+            ..ZoomSettings::default()
+        })));
+        let mut pipeline =
+            FramePipeline::new().with_stage(ZoomProcessor::new(zoom.clone(), Box::new(|_| None)));
+
+        let mut frame = gpu_frame();
+        pipeline.process(&mut frame);
+        assert!(frame.gpu_texture.is_some());
+
+        // Once zoomed the stage needs the CPU pixels
+        zoom.lock().unwrap().set_level(2.0, Instant::now());
+        let mut frame = gpu_frame();
+        pipeline.process(&mut frame);
+        assert!(frame.gpu_texture.is_none());
+    }
+
+    #[test]
+    fn builds_from_settings_json() {
+        let configs: Vec<ProcessorConfig> = serde_json::from_str(
+            r#"[
+                {"type": "crop", "x": 2, "y": 0, "width": 4, "height": 4},
+                {"type": "scale", "width": 8, "height": 2, "fit": "Stretch"},
+                {"type": "grayscale"}
+            ]"#,
+        )
+        .unwrap();
+        let mut pipeline = FramePipeline::from_config(&configs);
+        assert_eq!(pipeline.stage_names(), vec!["crop", "scale", "grayscale"]);
+
+        let mut frame = CaptureFrame::new(vec![0; 8 * 4 * 4], 8, 4).with_offset(100, 50);
+        frame.sequence = 9;
+        pipeline.process(&mut frame);
+        assert_eq!((frame.width, frame.height), (8, 2));
+        assert_eq!((frame.offset_x, frame.offset_y), (102, 50));
+        assert_eq!(frame.sequence, 9);
+    }
+
+    #[test]
+    fn crop_outside_frame_leaves_it_alone() {
+        let mut crop = CropProcessor::new(CaptureRect::new(10, 10, 4, 4));
+        let mut frame = CaptureFrame::new(vec![5; 16], 2, 2);
+        assert!(crop.process(&mut frame).is_err());
+        assert_eq!(frame.data, vec![5; 16]);
+    }
+
+    #[test]
+    fn grayscale_respects_channel_order() {
+        let mut frame = CaptureFrame::new(vec![0, 0, 255, 255], 1, 1);
+        GrayscaleProcessor.process(&mut frame).unwrap();
+        // Pure red in BGRA
+        assert_eq!(frame.data, vec![76, 76, 76, 255]);
+
+        let mut frame =
+            CaptureFrame::new(vec![255, 0, 0], 1, 1).with_pixel_format(PixelFormat::Rgb8);
+        GrayscaleProcessor.process(&mut frame).unwrap();
+        assert_eq!(frame.data, vec![76, 76, 76]);
+    }
+}
@@ -0,0 +1,232 @@
+// capture/pool.rs - Recycled Frame Buffers
+//
+// Backends take pixel buffers from a FramePool instead of allocating a fresh Vec
+// per frame, and consumers hand them back once the frame has been drawn. At
+// steady state the same few buffers circulate and the allocator is never hit.
+
+use super::CaptureFrame;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Buffers kept by `FramePool::global`
+///
+/// Enough for the frame being captured, one queued, one on screen and some
+/// slack for broadcast subscribers.
+pub const DEFAULT_POOL_CAPACITY: usize = 8;
+
+/// Counters describing how well the pool is doing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// `take` calls served from a pooled buffer
+    pub hits: u64,
+    /// `take` calls that had to allocate
+    pub misses: u64,
+    /// Buffers handed back and kept
+    pub recycled: u64,
+    /// Buffers handed back but freed because the pool was full
+    pub discarded: u64,
+    /// Buffers currently waiting in the pool
+    pub pooled: usize,
+}
+
+impl PoolStats {
+    /// Fraction of `take` calls served without allocating
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+struct PoolInner {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    recycled: AtomicU64,
+    discarded: AtomicU64,
+}
+
+/// Thread-safe pool of pixel buffers; clones share the same buffers
+#[derive(Clone)]
+pub struct FramePool {
+    inner: Arc<PoolInner>,
+}
+
+lazy_static::lazy_static! {
+    static ref GLOBAL_POOL: FramePool = FramePool::new(DEFAULT_POOL_CAPACITY);
+}
+
+impl FramePool {
+    /// Pool that keeps at most `capacity` idle buffers
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                buffers: Mutex::new(Vec::with_capacity(capacity)),
+                capacity,
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                recycled: AtomicU64::new(0),
+                discarded: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Pool shared by the built-in backends and the render path
+    pub fn global() -> &'static FramePool {
+        &GLOBAL_POOL
+    }
+
+    /// Buffer of exactly `len` bytes with unspecified contents
+    ///
+    /// Use this when every byte is about to be overwritten anyway.
+    pub fn take(&self, len: usize) -> Vec<u8> {
+        let pooled = {
+            let mut buffers = self.inner.buffers.lock().unwrap();
+            // Smallest buffer that fits, so large ones stay available for large frames
+            buffers
+                .iter()
+                .enumerate()
+                .filter(|(_, buf)| buf.capacity() >= len)
+                .min_by_key(|(_, buf)| buf.capacity())
+                .map(|(index, _)| index)
+                .map(|index| buffers.swap_remove(index))
+        };
+
+        match pooled {
+            Some(mut buf) => {
+                self.inner.hits.fetch_add(1, Ordering::Relaxed);
+                if buf.len() > len {
+                    buf.truncate(len);
+                } else {
+                    buf.resize(len, 0);
+                }
+                buf
+            }
+            None => {
+                self.inner.misses.fetch_add(1, Ordering::Relaxed);
+                vec![0u8; len]
+            }
+        }
+    }
+
+    /// Buffer of exactly `len` zero bytes
+    pub fn take_zeroed(&self, len: usize) -> Vec<u8> {
+        let mut buf = self.take(len);
+        buf.fill(0);
+        buf
+    }
+
+    /// Hand a buffer back for reuse
+    ///
+    /// When the pool is full the smallest buffer is freed, which lets the pool
+    /// follow a region that grows instead of clinging to stale small buffers.
+    pub fn recycle(&self, buf: Vec<u8>) {
+        if buf.capacity() == 0 || self.inner.capacity == 0 {
+            return;
+        }
+
+        let mut buffers = self.inner.buffers.lock().unwrap();
+        if buffers.len() < self.inner.capacity {
+            buffers.push(buf);
+            self.inner.recycled.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let smallest = buffers
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, pooled)| pooled.capacity())
+            .map(|(index, pooled)| (index, pooled.capacity()));
+        match smallest {
+            Some((index, capacity)) if capacity < buf.capacity() => {
+                buffers[index] = buf;
+                self.inner.recycled.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        self.inner.discarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Return a consumed frame's pixel buffer
+    pub fn recycle_frame(&self, frame: CaptureFrame) {
+        self.recycle(frame.data);
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            recycled: self.inner.recycled.load(Ordering::Relaxed),
+            discarded: self.inner.discarded.load(Ordering::Relaxed),
+            pooled: self.inner.buffers.lock().unwrap().len(),
+        }
+    }
+}
+
+impl CaptureFrame {
+    /// Return this frame's pixel buffer to the global pool once it is no longer needed
+    pub fn recycle(self) {
+        FramePool::global().recycle_frame(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_returned_buffers() {
+        let pool = FramePool::new(4);
+        let buf = pool.take(1024);
+        assert_eq!(buf.len(), 1024);
+        let ptr = buf.as_ptr();
+        pool.recycle(buf);
+
+        // A smaller request is served from the same allocation
+        let again = pool.take(512);
+        assert_eq!(again.len(), 512);
+        assert_eq!(again.as_ptr(), ptr);
+
+        let stats = pool.stats();
+        assert_eq!((stats.hits, stats.misses, stats.recycled), (1, 1, 1));
+        assert_eq!(stats.hit_rate(), Some(0.5));
+    }
+
+    #[test]
+    fn picks_smallest_fitting_buffer() {
+        let pool = FramePool::new(4);
+        pool.recycle(Vec::with_capacity(4096));
+        pool.recycle(Vec::with_capacity(256));
+        pool.recycle(Vec::with_capacity(1024));
+
+        assert!(pool.take(300).capacity() < 4096);
+        // Nothing left that holds 8K bytes
+        assert_eq!(pool.take(8192).len(), 8192);
+        assert_eq!(pool.stats().misses, 1);
+    }
+
+    #[test]
+    fn full_pool_keeps_largest_buffers() {
+        let pool = FramePool::new(2);
+        pool.recycle(Vec::with_capacity(100));
+        pool.recycle(Vec::with_capacity(200));
+        pool.recycle(Vec::with_capacity(300));
+        pool.recycle(Vec::with_capacity(50));
+
+        let stats = pool.stats();
+        assert_eq!(stats.pooled, 2);
+        assert_eq!(stats.discarded, 2);
+        assert_eq!(pool.take(250).capacity(), 300);
+        assert_eq!(pool.take(150).capacity(), 200);
+    }
+
+    #[test]
+    fn take_zeroed_clears_stale_pixels() {
+        let pool = FramePool::new(1);
+        pool.recycle(vec![0xAB; 64]);
+
+        let buf = pool.take_zeroed(64);
+        assert!(buf.iter().all(|&b| b == 0));
+        assert_eq!(pool.stats().hits, 1);
+    }
+}
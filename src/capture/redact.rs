@@ -0,0 +1,385 @@
+// capture/redact.rs - Privacy Redaction Zones
+//
+// Hides parts of the shared region (a token in a terminal, a customer name in a
+// sidebar) before a frame reaches any consumer. Zones are in region-relative
+// pixels, so they follow the border when it is dragged, and are applied as the
+// first pipeline stage while coordinates still match the captured region.
+//
+// Blur with a small radius can leave text guessable; prefer solid fill or a
+// coarse pixelate for real secrets.
+
+use super::pipeline::FrameProcessor;
+use super::{convert, CaptureFrame, CaptureRect, PixelFormat};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Largest blur radius applied; bigger ones cost a lot per frame and hide no more
+pub const MAX_BLUR_RADIUS: u32 = 64;
+
+/// How a zone is obscured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RedactionStyle {
+    /// Paint over with a color given as [R, G, B, A]
+    Solid { color: [u8; 4] },
+    /// Gaussian blur; `radius` is the kernel half-width in pixels, capped at
+    /// `MAX_BLUR_RADIUS`
+    Blur { radius: u32 },
+    /// Average `block` x `block` squares
+    Pixelate { block: u32 },
+}
+
+impl Default for RedactionStyle {
+    fn default() -> Self {
+        RedactionStyle::Solid {
+            color: [0, 0, 0, 255],
+        }
+    }
+}
+
+/// A rectangle to obscure, relative to the capture region's top-left corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionZone {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub style: RedactionStyle,
+}
+
+impl RedactionZone {
+    pub fn new(rect: CaptureRect, style: RedactionStyle) -> Self {
+        Self {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+            style,
+        }
+    }
+
+    /// Part of the zone inside a `width` x `height` frame, as (x, y, w, h)
+    fn clip(&self, width: u32, height: u32) -> Option<(usize, usize, usize, usize)> {
+        let x0 = (self.x as i64).max(0);
+        let y0 = (self.y as i64).max(0);
+        let x1 = (self.x as i64 + self.width as i64).min(width as i64);
+        let y1 = (self.y as i64 + self.height as i64).min(height as i64);
+        (x0 < x1 && y0 < y1).then(|| {
+            (
+                x0 as usize,
+                y0 as usize,
+                (x1 - x0) as usize,
+                (y1 - y0) as usize,
+            )
+        })
+    }
+}
+
+/// Pipeline stage applying a list of redaction zones
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RedactionProcessor {
+    zones: Vec<RedactionZone>,
+}
+
+impl RedactionProcessor {
+    /// Blur radii above `MAX_BLUR_RADIUS` are clamped to it
+    pub fn new(mut zones: Vec<RedactionZone>) -> Self {
+        for zone in &mut zones {
+            if let RedactionStyle::Blur { radius } = &mut zone.style {
+                *radius = (*radius).min(MAX_BLUR_RADIUS);
+            }
+        }
+        Self { zones }
+    }
+
+    pub fn zones(&self) -> &[RedactionZone] {
+        &self.zones
+    }
+}
+
+impl FrameProcessor for RedactionProcessor {
+    fn name(&self) -> &str {
+        "redact"
+    }
+
+    fn is_required(&self) -> bool {
+        true
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        if self.zones.is_empty() {
+            return Ok(());
+        }
+        if frame.pixel_format.is_planar() {
+            convert::convert_frame(frame, PixelFormat::Bgra8)?;
+        }
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+
+        let mut image = Pixels {
+            data: &mut frame.data,
+            stride: frame.stride as usize,
+            bpp: frame.pixel_format.bytes_per_pixel() as usize,
+        };
+        for zone in &self.zones {
+            let area = match zone.clip(frame.width, frame.height) {
+                Some(area) => area,
+                None => continue,
+            };
+            match zone.style {
+                RedactionStyle::Solid { color } => {
+                    image.fill(area, &pixel_bytes(color, frame.pixel_format))
+                }
+                RedactionStyle::Blur { radius } => image.blur(area, radius as usize),
+                RedactionStyle::Pixelate { block } => image.pixelate(area, block.max(1) as usize),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `rgba` in the byte order of `format`
+pub(crate) fn pixel_bytes(rgba: [u8; 4], format: PixelFormat) -> Vec<u8> {
+    let [r, g, b, a] = rgba;
+    match format {
+        PixelFormat::Bgra8 => vec![b, g, r, a],
+        PixelFormat::Rgb8 => vec![r, g, b],
+        _ => vec![r, g, b, a],
+    }
+}
+
+struct Pixels<'a> {
+    data: &'a mut [u8],
+    stride: usize,
+    bpp: usize,
+}
+
+impl Pixels<'_> {
+    fn row_mut(&mut self, x: usize, y: usize, w: usize) -> &mut [u8] {
+        let start = y * self.stride + x * self.bpp;
+        &mut self.data[start..start + w * self.bpp]
+    }
+
+    fn fill(&mut self, (x, y, w, h): (usize, usize, usize, usize), pixel: &[u8]) {
+        for row in y..y + h {
+            for px in self.row_mut(x, row, w).chunks_exact_mut(pixel.len()) {
+                px.copy_from_slice(pixel);
+            }
+        }
+    }
+
+    fn pixelate(&mut self, (x, y, w, h): (usize, usize, usize, usize), block: usize) {
+        let bpp = self.bpp;
+        for by in (y..y + h).step_by(block) {
+            let bh = block.min(y + h - by);
+            for bx in (x..x + w).step_by(block) {
+                let bw = block.min(x + w - bx);
+
+                let mut sum = vec![0u32; bpp];
+                for row in by..by + bh {
+                    for px in self.row_mut(bx, row, bw).chunks_exact(bpp) {
+                        sum.iter_mut().zip(px).for_each(|(s, &v)| *s += v as u32);
+                    }
+                }
+                let count = (bw * bh) as u32;
+                let average: Vec<u8> = sum
+                    .iter()
+                    .map(|s| ((s + count / 2) / count) as u8)
+                    .collect();
+
+                for row in by..by + bh {
+                    for px in self.row_mut(bx, row, bw).chunks_exact_mut(bpp) {
+                        px.copy_from_slice(&average);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Separable Gaussian over the zone only, so nothing outside it bleeds in
+    fn blur(&mut self, (x, y, w, h): (usize, usize, usize, usize), radius: usize) {
+        if radius == 0 {
+            return;
+        }
+        let bpp = self.bpp;
+        let kernel = gaussian_kernel(radius);
+
+        let mut buffer: Vec<f32> = Vec::with_capacity(w * h * bpp);
+        for row in y..y + h {
+            buffer.extend(self.row_mut(x, row, w).iter().map(|&v| v as f32));
+        }
+
+        let mut scratch = vec![0f32; buffer.len()];
+        // Horizontal, then vertical
+        convolve(&buffer, &mut scratch, &kernel, w, h, bpp, (bpp, w * bpp));
+        convolve(&scratch, &mut buffer, &kernel, h, w, bpp, (w * bpp, bpp));
+
+        for (row, values) in (y..y + h).zip(buffer.chunks_exact(w * bpp)) {
+            for (dst, &v) in self.row_mut(x, row, w).iter_mut().zip(values) {
+                *dst = v.round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+}
+
+fn gaussian_kernel(radius: usize) -> Vec<f32> {
+    // The kernel spans roughly three standard deviations each side
+    let sigma = (radius as f32 / 3.0).max(0.5);
+    let weights: Vec<f32> = (0..=2 * radius)
+        .map(|i| {
+            let d = i as f32 - radius as f32;
+            (-(d * d) / (2.0 * sigma * sigma)).exp()
+        })
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / sum).collect()
+}
+
+/// Convolve `lines` lines of `len` pixels, addressed by (pixel step, line step)
+fn convolve(
+    src: &[f32],
+    dst: &mut [f32],
+    kernel: &[f32],
+    len: usize,
+    lines: usize,
+    bpp: usize,
+    (step, line_step): (usize, usize),
+) {
+    let radius = kernel.len() / 2;
+    for line in 0..lines {
+        let base = line * line_step;
+        for i in 0..len {
+            for c in 0..bpp {
+                let mut acc = 0.0;
+                for (k, &weight) in kernel.iter().enumerate() {
+                    // Clamp to the zone edge
+                    let j = (i + k).saturating_sub(radius).min(len - 1);
+                    acc += weight * src[base + j * step + c];
+                }
+                dst[base + i * step + c] = acc;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard(width: u32, height: u32) -> CaptureFrame {
+        let data = (0..height)
+            .flat_map(|y| {
+                (0..width).flat_map(move |x| {
+                    let v = if (x + y) % 2 == 0 { 0 } else { 200 };
+                    [v, v, v, 255]
+                })
+            })
+            .collect();
+        CaptureFrame::new(data, width, height)
+    }
+
+    fn px(frame: &CaptureFrame, x: u32, y: u32) -> &[u8] {
+        let start = (y * frame.stride + x * 4) as usize;
+        &frame.data[start..start + 4]
+    }
+
+    fn redact(frame: &mut CaptureFrame, zone: RedactionZone) {
+        RedactionProcessor::new(vec![zone]).process(frame).unwrap();
+    }
+
+    #[test]
+    fn solid_fill_is_clipped_to_frame() {
+        let mut frame = checkerboard(8, 8);
+        redact(
+            &mut frame,
+            RedactionZone::new(
+                CaptureRect::new(-2, 6, 4, 10),
+                RedactionStyle::Solid {
+                    color: [255, 0, 0, 255],
+                },
+            ),
+        );
+        // Red in BGRA order
+        assert_eq!(px(&frame, 0, 7), &[0, 0, 255, 255]);
+        assert_eq!(px(&frame, 1, 6), &[0, 0, 255, 255]);
+        // Outside the zone
+        assert_eq!(px(&frame, 2, 7), &[200, 200, 200, 255]);
+        assert_eq!(px(&frame, 0, 5), &[200, 200, 200, 255]);
+    }
+
+    #[test]
+    fn pixelate_averages_blocks() {
+        let mut frame = checkerboard(6, 4);
+        redact(
+            &mut frame,
+            RedactionZone::new(
+                CaptureRect::new(0, 0, 4, 4),
+                RedactionStyle::Pixelate { block: 2 },
+            ),
+        );
+        for (x, y) in [(0, 0), (1, 1), (3, 2)] {
+            assert_eq!(px(&frame, x, y), &[100, 100, 100, 255]);
+        }
+        assert_eq!(px(&frame, 4, 0), &[0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn blur_smooths_inside_zone_only() {
+        let mut frame = checkerboard(10, 10);
+        redact(
+            &mut frame,
+            RedactionZone::new(
+                CaptureRect::new(2, 2, 6, 6),
+                RedactionStyle::Blur { radius: 3 },
+            ),
+        );
+        let center = px(&frame, 5, 5)[0];
+        assert!((80..=120).contains(&center), "center {}", center);
+        assert_eq!(px(&frame, 1, 2), &[200, 200, 200, 255]);
+    }
+
+    #[test]
+    fn blur_radius_is_clamped() {
+        let zone = RedactionZone::new(
+            CaptureRect::new(0, 0, 4, 4),
+            RedactionStyle::Blur { radius: 100_000 },
+        );
+        let processor = RedactionProcessor::new(vec![zone]);
+        assert_eq!(
+            processor.zones()[0].style,
+            RedactionStyle::Blur {
+                radius: MAX_BLUR_RADIUS
+            }
+        );
+    }
+
+    #[test]
+    fn zones_outside_frame_are_ignored() {
+        let mut frame = checkerboard(4, 4);
+        let before = frame.data.clone();
+        redact(
+            &mut frame,
+            RedactionZone::new(CaptureRect::new(10, 10, 5, 5), RedactionStyle::default()),
+        );
+        assert_eq!(frame.data, before);
+    }
+
+    #[test]
+    fn zone_json_round_trips() {
+        let zone: RedactionZone = serde_json::from_str(
+            r#"{"x": 10, "y": 20, "width": 200, "height": 30, "style": {"type": "blur", "radius": 8}}"#,
+        )
+        .unwrap();
+        assert_eq!(zone.style, RedactionStyle::Blur { radius: 8 });
+
+        let zone: RedactionZone =
+            serde_json::from_str(r#"{"x": 0, "y": 0, "width": 1, "height": 1}"#).unwrap();
+        assert_eq!(zone.style, RedactionStyle::default());
+    }
+}
@@ -0,0 +1,631 @@
+// capture/replay.rs - Recorded Frame Playback
+//
+// Serves frames recorded earlier instead of reading the screen, at the timing they
+// were recorded with. Two on-disk sources are supported:
+//
+// - A directory of PNG files, played in file name order. An optional
+//   `timestamps.txt` next to them lists one millisecond offset per frame;
+//   without it frames are spaced at DEFAULT_REPLAY_FPS.
+// - A raw frame dump written by `RawFrameWriter`, which keeps the exact pixel
+//   bytes, format, stride, screen offsets and timestamps of every frame.
+//
+// Frames are decoded lazily when they become due, so long recordings do not have
+// to fit in memory.
+
+use super::convert::crop_to_region;
+use super::{
+    convert, CaptureEngine, CaptureFrame, CaptureRect, CaptureStats, FrameStatsTracker, PixelFormat,
+};
+use crate::window_filter::WindowIdentifier;
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Frame rate used for PNG sequences that have no timestamps file
+pub const DEFAULT_REPLAY_FPS: u32 = 30;
+
+/// Name of the optional per-frame timing file in a PNG directory
+pub const TIMESTAMPS_FILE: &str = "timestamps.txt";
+
+const RAW_MAGIC: &[u8; 8] = b"RFRAME\x00\x01";
+const RAW_FRAME_HEADER_LEN: u64 = 32;
+
+/// Where recorded frames are placed on screen before cropping to the capture region
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayAnchor {
+    /// Use the screen offsets stored in the recording
+    Recorded,
+    /// Place the top-left of each frame at the origin of the region passed to `start`
+    StartRegion,
+}
+
+#[derive(Debug, Clone)]
+enum FrameLocation {
+    Png(PathBuf),
+    Raw {
+        data_offset: u64,
+        offset_x: i32,
+        offset_y: i32,
+        width: u32,
+        height: u32,
+        stride: u32,
+        pixel_format: PixelFormat,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct RecordedFrame {
+    timestamp: Duration,
+    location: FrameLocation,
+}
+
+/// Capture engine that plays back frames from a PNG directory or raw frame dump
+pub struct ReplayCaptureEngine {
+    source: PathBuf,
+    frames: Vec<RecordedFrame>,
+    raw_file: Option<BufReader<File>>,
+    anchor: ReplayAnchor,
+    looping: bool,
+    is_active: bool,
+    region: Option<CaptureRect>,
+    start_origin: (i32, i32),
+    started_at: Option<Instant>,
+    /// (loop iteration, frame index) of the last frame handed out
+    last_served: Option<(u64, usize)>,
+    /// Serve the current frame again even though it was already handed out
+    refresh: bool,
+    frame_stats: FrameStatsTracker,
+}
+
+impl ReplayCaptureEngine {
+    /// Open a recording; directories are read as PNG sequences, files as raw dumps
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let (frames, raw_file, anchor) = if path.is_dir() {
+            (index_png_dir(path)?, None, ReplayAnchor::StartRegion)
+        } else {
+            let mut reader = BufReader::new(
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+            );
+            let frames = index_raw_dump(&mut reader)
+                .with_context(|| format!("Invalid raw frame dump {}", path.display()))?;
+            (frames, Some(reader), ReplayAnchor::Recorded)
+        };
+
+        if frames.is_empty() {
+            bail!("No frames found in {}", path.display());
+        }
+
+        log::info!(
+            "Replay source {} opened with {} frames ({:?})",
+            path.display(),
+            frames.len(),
+            frames.last().map(|f| f.timestamp).unwrap_or_default()
+        );
+
+        Ok(Self {
+            source: path.to_path_buf(),
+            frames,
+            raw_file,
+            anchor,
+            looping: false,
+            is_active: false,
+            region: None,
+            start_origin: (0, 0),
+            started_at: None,
+            last_served: None,
+            refresh: false,
+            frame_stats: FrameStatsTracker::new(),
+        })
+    }
+
+    /// Restart from the first frame after the last one instead of holding it
+    pub fn with_looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// Override how frames are positioned on screen
+    pub fn with_anchor(mut self, anchor: ReplayAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Ignore recorded timing and space frames evenly at `fps`
+    pub fn with_frame_rate(mut self, fps: u32) -> Self {
+        let interval = frame_interval(fps);
+        for (i, frame) in self.frames.iter_mut().enumerate() {
+            frame.timestamp = interval * i as u32;
+        }
+        self
+    }
+
+    /// Path this engine was opened from
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Number of frames in the recording
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Length of one pass over the recording, including the last frame's display time
+    pub fn duration(&self) -> Duration {
+        let last = self.frames[self.frames.len() - 1].timestamp;
+        let tail = if self.frames.len() > 1 {
+            last - self.frames[self.frames.len() - 2].timestamp
+        } else {
+            frame_interval(DEFAULT_REPLAY_FPS)
+        };
+        last + tail.max(Duration::from_millis(1))
+    }
+
+    /// (loop iteration, frame index) that should be on screen at `elapsed`
+    fn due_frame(&self, elapsed: Duration) -> (u64, usize) {
+        let (cycle, position) = if self.looping {
+            let total = self.duration().as_nanos();
+            let elapsed = elapsed.as_nanos();
+            (
+                (elapsed / total) as u64,
+                Duration::from_nanos((elapsed % total) as u64),
+            )
+        } else {
+            (0, elapsed)
+        };
+
+        let index = self
+            .frames
+            .partition_point(|f| f.timestamp <= position)
+            .saturating_sub(1);
+        (cycle, index)
+    }
+
+    fn current_due(&self) -> Option<(u64, usize)> {
+        let started_at = self.started_at?;
+        let elapsed = started_at.elapsed();
+        // Nothing is due until the first frame's own timestamp
+        if !self.looping && elapsed < self.frames[0].timestamp {
+            return None;
+        }
+        Some(self.due_frame(elapsed))
+    }
+
+    fn load_frame(&mut self, index: usize) -> Result<CaptureFrame> {
+        match self.frames[index].location.clone() {
+            FrameLocation::Png(path) => {
+                decode_png(&path).with_context(|| format!("Failed to decode {}", path.display()))
+            }
+            FrameLocation::Raw {
+                data_offset,
+                offset_x,
+                offset_y,
+                width,
+                height,
+                stride,
+                pixel_format,
+            } => {
+                let reader = self
+                    .raw_file
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("Raw dump is not open"))?;
+                reader.seek(SeekFrom::Start(data_offset))?;
+                let mut data = vec![0u8; pixel_format.buffer_len(height, stride)];
+                reader.read_exact(&mut data)?;
+                let mut frame = CaptureFrame::new(data, width, height)
+                    .with_offset(offset_x, offset_y)
+                    .with_pixel_format(pixel_format);
+                frame.stride = stride;
+                Ok(frame)
+            }
+        }
+    }
+
+    fn place(&self, frame: CaptureFrame) -> CaptureFrame {
+        match self.anchor {
+            ReplayAnchor::Recorded => frame,
+            ReplayAnchor::StartRegion => {
+                frame.with_offset(self.start_origin.0, self.start_origin.1)
+            }
+        }
+    }
+}
+
+impl CaptureEngine for ReplayCaptureEngine {
+    fn start(
+        &mut self,
+        region: CaptureRect,
+        _show_cursor: bool,
+        _excluded_windows: Option<Vec<WindowIdentifier>>,
+    ) -> Result<()> {
+        self.region = Some(region);
+        self.start_origin = (region.x, region.y);
+        self.started_at = Some(Instant::now());
+        self.last_served = None;
+        self.refresh = false;
+        self.frame_stats.reset();
+        self.is_active = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.is_active = false;
+        self.started_at = None;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn has_new_frame(&self) -> bool {
+        if !self.is_active {
+            return false;
+        }
+        match self.current_due() {
+            Some(due) => self.refresh || Some(due) != self.last_served,
+            None => false,
+        }
+    }
+
+    fn get_frame(&mut self) -> Option<CaptureFrame> {
+        if !self.is_active {
+            return None;
+        }
+
+        let due = self.current_due()?;
+        if Some(due) == self.last_served && !self.refresh {
+            return None;
+        }
+        self.refresh = false;
+
+        // Frames that became due and were superseded before anyone asked for them
+        if Some(due) != self.last_served {
+            let count = self.frames.len() as u64;
+            let position = due.0 * count + due.1 as u64;
+            let skipped = match self.last_served {
+                Some((cycle, index)) => position - (cycle * count + index as u64) - 1,
+                None => position,
+            };
+            self.frame_stats.record_dropped(skipped);
+        }
+        self.last_served = Some(due);
+
+        let mut frame = match self.load_frame(due.1) {
+            Ok(frame) => self.place(frame),
+            Err(e) => {
+                log::warn!("Replay frame {} unavailable: {:#}", due.1, e);
+                return None;
+            }
+        };
+        if let Some(started_at) = self.started_at {
+            let pass = if self.looping {
+                self.duration() * due.0 as u32
+            } else {
+                Duration::ZERO
+            };
+            frame.captured_at = started_at + pass + self.frames[due.1].timestamp;
+        }
+
+        let region = self.region?;
+        self.frame_stats.deliver(crop_to_region(&frame, &region))
+    }
+
+    fn set_cursor_visible(&mut self, _visible: bool) -> Result<()> {
+        // The cursor is whatever was recorded
+        Ok(())
+    }
+
+    fn get_region(&self) -> Option<CaptureRect> {
+        self.region
+    }
+
+    fn update_region(&mut self, region: CaptureRect) -> Result<()> {
+        self.region = Some(region);
+        // Re-serve the current frame so the new crop shows up without waiting for the next one
+        self.refresh = true;
+        Ok(())
+    }
+
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// Writes frames in the raw dump format read by [`ReplayCaptureEngine`]
+pub struct RawFrameWriter {
+    writer: BufWriter<File>,
+    last_timestamp: Option<Duration>,
+}
+
+impl RawFrameWriter {
+    /// Create (or truncate) a dump file
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut writer = BufWriter::new(
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        writer.write_all(RAW_MAGIC)?;
+        Ok(Self {
+            writer,
+            last_timestamp: None,
+        })
+    }
+
+    /// Append a CPU frame recorded `timestamp` after the start of the recording
+    ///
+    /// Timestamps must not go backwards.
+    pub fn write_frame(&mut self, frame: &CaptureFrame, timestamp: Duration) -> Result<()> {
+        if let Some(last) = self.last_timestamp {
+            if timestamp < last {
+                bail!(
+                    "Frame timestamp {:?} is before the previous frame's {:?}",
+                    timestamp,
+                    last
+                );
+            }
+        }
+        convert::validate_layout(
+            frame.pixel_format,
+            frame.width,
+            frame.height,
+            frame.stride,
+            frame.data.len(),
+        )?;
+        let len = frame.pixel_format.buffer_len(frame.height, frame.stride);
+
+        let micros = u64::try_from(timestamp.as_micros()).unwrap_or(u64::MAX);
+        self.writer.write_all(&micros.to_le_bytes())?;
+        self.writer.write_all(&frame.offset_x.to_le_bytes())?;
+        self.writer.write_all(&frame.offset_y.to_le_bytes())?;
+        self.writer.write_all(&frame.width.to_le_bytes())?;
+        self.writer.write_all(&frame.height.to_le_bytes())?;
+        self.writer.write_all(&frame.stride.to_le_bytes())?;
+        self.writer
+            .write_all(&format_code(frame.pixel_format).to_le_bytes())?;
+        self.writer.write_all(&frame.data[..len])?;
+        self.last_timestamp = Some(timestamp);
+        Ok(())
+    }
+
+    /// Flush buffered frames to disk
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+fn frame_interval(fps: u32) -> Duration {
+    Duration::from_nanos(1_000_000_000 / fps.max(1) as u64)
+}
+
+fn index_png_dir(dir: &Path) -> Result<Vec<RecordedFrame>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .map_or(false, |e| e.eq_ignore_ascii_case("png"))
+        })
+        .collect();
+    paths.sort();
+
+    let timestamps_path = dir.join(TIMESTAMPS_FILE);
+    let timestamps = if timestamps_path.is_file() {
+        let text = std::fs::read_to_string(&timestamps_path)?;
+        let parsed = parse_timestamps(&text)
+            .with_context(|| format!("Invalid {}", timestamps_path.display()))?;
+        if parsed.len() != paths.len() {
+            bail!(
+                "{} lists {} timestamps for {} frames",
+                timestamps_path.display(),
+                parsed.len(),
+                paths.len()
+            );
+        }
+        parsed
+    } else {
+        let interval = frame_interval(DEFAULT_REPLAY_FPS);
+        (0..paths.len()).map(|i| interval * i as u32).collect()
+    };
+
+    Ok(paths
+        .into_iter()
+        .zip(timestamps)
+        .map(|(path, timestamp)| RecordedFrame {
+            timestamp,
+            location: FrameLocation::Png(path),
+        })
+        .collect())
+}
+
+/// One millisecond offset per non-empty line, non-decreasing
+fn parse_timestamps(text: &str) -> Result<Vec<Duration>> {
+    let mut timestamps: Vec<Duration> = Vec::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let ms: f64 = line
+            .parse()
+            .map_err(|_| anyhow!("line {}: '{}' is not a number", line_no + 1, line))?;
+        if !ms.is_finite() || ms < 0.0 {
+            bail!(
+                "line {}: timestamp must be a non-negative number",
+                line_no + 1
+            );
+        }
+        let timestamp = Duration::from_secs_f64(ms / 1000.0);
+        if timestamps.last().map_or(false, |prev| timestamp < *prev) {
+            bail!("line {}: timestamps must not go backwards", line_no + 1);
+        }
+        timestamps.push(timestamp);
+    }
+    Ok(timestamps)
+}
+
+fn index_raw_dump<R: Read + Seek>(reader: &mut R) -> Result<Vec<RecordedFrame>> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != RAW_MAGIC {
+        bail!("Not a RustFrame raw frame dump");
+    }
+
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut position = RAW_MAGIC.len() as u64;
+    let mut frames = Vec::new();
+
+    while position < file_len {
+        reader.seek(SeekFrom::Start(position))?;
+        let mut header = [0u8; RAW_FRAME_HEADER_LEN as usize];
+        reader
+            .read_exact(&mut header)
+            .context("Truncated frame header")?;
+
+        let u32_at = |at: usize| {
+            u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+        };
+        let mut micros = [0u8; 8];
+        micros.copy_from_slice(&header[..8]);
+        let timestamp = Duration::from_micros(u64::from_le_bytes(micros));
+        if frames
+            .last()
+            .map_or(false, |prev: &RecordedFrame| timestamp < prev.timestamp)
+        {
+            bail!("Frame {} timestamp goes backwards", frames.len());
+        }
+        let width = u32_at(16);
+        let height = u32_at(20);
+        let stride = u32_at(24);
+        let pixel_format = format_from_code(u32_at(28))
+            .ok_or_else(|| anyhow!("Frame {} has unknown pixel format", frames.len()))?;
+
+        if stride < pixel_format.packed_stride(width) {
+            bail!(
+                "Frame {} has stride {} narrower than width {}",
+                frames.len(),
+                stride,
+                width
+            );
+        }
+
+        let data_offset = position + RAW_FRAME_HEADER_LEN;
+        let data_len = pixel_format.buffer_len(height, stride) as u64;
+        if data_offset + data_len > file_len {
+            bail!("Frame {} is truncated", frames.len());
+        }
+
+        frames.push(RecordedFrame {
+            timestamp,
+            location: FrameLocation::Raw {
+                data_offset,
+                offset_x: u32_at(8) as i32,
+                offset_y: u32_at(12) as i32,
+                width,
+                height,
+                stride,
+                pixel_format,
+            },
+        });
+        position = data_offset + data_len;
+    }
+
+    Ok(frames)
+}
+
+fn format_code(format: PixelFormat) -> u32 {
+    match format {
+        PixelFormat::Bgra8 => 0,
+        PixelFormat::Rgba8 => 1,
+        PixelFormat::Rgb8 => 2,
+        PixelFormat::Nv12 => 3,
+        PixelFormat::I420 => 4,
+    }
+}
+
+fn format_from_code(code: u32) -> Option<PixelFormat> {
+    Some(match code {
+        0 => PixelFormat::Bgra8,
+        1 => PixelFormat::Rgba8,
+        2 => PixelFormat::Rgb8,
+        3 => PixelFormat::Nv12,
+        4 => PixelFormat::I420,
+        _ => return None,
+    })
+}
+
+/// Decode a PNG into a tightly packed RGBA or RGB frame
+pub(crate) fn decode_png(path: &Path) -> Result<CaptureFrame> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0u8; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    buf.truncate(info.buffer_size());
+
+    let (data, format) = match info.color_type {
+        png::ColorType::Rgba => (buf, PixelFormat::Rgba8),
+        png::ColorType::Rgb => (buf, PixelFormat::Rgb8),
+        png::ColorType::GrayscaleAlpha => (
+            buf.chunks_exact(2)
+                .flat_map(|px| [px[0], px[0], px[0], px[1]])
+                .collect(),
+            PixelFormat::Rgba8,
+        ),
+        png::ColorType::Grayscale => (
+            buf.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+            PixelFormat::Rgba8,
+        ),
+        png::ColorType::Indexed => bail!("Indexed PNG was not expanded"),
+    };
+
+    Ok(CaptureFrame::new(data, info.width, info.height).with_pixel_format(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_parse_and_validate() {
+        let parsed = parse_timestamps("0\n# comment\n\n33.5\n 100 \n").unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                Duration::ZERO,
+                Duration::from_micros(33_500),
+                Duration::from_millis(100)
+            ]
+        );
+
+        assert!(parse_timestamps("10\n5\n").is_err());
+        assert!(parse_timestamps("-1\n").is_err());
+        assert!(parse_timestamps("abc\n").is_err());
+    }
+
+    #[test]
+    fn raw_dump_index_rejects_bad_input() {
+        let mut bad_magic = std::io::Cursor::new(b"NOTADUMP".to_vec());
+        assert!(index_raw_dump(&mut bad_magic).is_err());
+
+        let mut truncated = RAW_MAGIC.to_vec();
+        truncated.extend_from_slice(&[0u8; 16]);
+        assert!(index_raw_dump(&mut std::io::Cursor::new(truncated)).is_err());
+
+        // Two empty frames, the second recorded before the first
+        let mut backwards = RAW_MAGIC.to_vec();
+        for micros in [10_000u64, 5_000] {
+            backwards.extend_from_slice(&micros.to_le_bytes());
+            backwards.extend_from_slice(&[0u8; RAW_FRAME_HEADER_LEN as usize - 8]);
+        }
+        let error = index_raw_dump(&mut std::io::Cursor::new(backwards)).unwrap_err();
+        assert!(error.to_string().contains("backwards"));
+    }
+}
@@ -0,0 +1,505 @@
+// capture/scale.rs - CPU Frame Scaler
+//
+// Resamples a CaptureFrame of any size to a fixed output resolution, so a call
+// expecting 1280x720 keeps receiving 1280x720 however the border is resized.
+// Scaling is separable: a horizontal pass into an f32 buffer, then a vertical
+// pass. Filter weights are computed once per axis and widened when shrinking,
+// so downscales are properly low-passed instead of skipping pixels.
+
+use super::redact::pixel_bytes;
+use super::{convert, CaptureFrame, CaptureRect, FramePool, PixelFormat};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Resampling kernel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScaleFilter {
+    /// Nearest pixel, sharp and cheap (good for text at integer ratios)
+    Nearest,
+    /// Linear interpolation between neighbours
+    #[default]
+    Bilinear,
+    /// Windowed sinc over 3 lobes, sharpest result for large downscales
+    Lanczos3,
+}
+
+impl ScaleFilter {
+    fn support(self) -> f64 {
+        match self {
+            ScaleFilter::Nearest => 0.5,
+            ScaleFilter::Bilinear => 1.0,
+            ScaleFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            ScaleFilter::Nearest => {
+                if x < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ScaleFilter::Bilinear => (1.0 - x).max(0.0),
+            ScaleFilter::Lanczos3 => {
+                if x < 3.0 {
+                    sinc(x) * sinc(x / 3.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f64::consts::PI;
+        x.sin() / x
+    }
+}
+
+/// How the source aspect ratio is mapped onto the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FitMode {
+    /// Fit entirely inside the output, padding the rest with the background color
+    #[default]
+    Letterbox,
+    /// Fill the output, cropping the source edges that don't fit
+    CropToFill,
+    /// Fill the output, distorting the aspect ratio
+    Stretch,
+}
+
+/// Where a source area lands in the output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleLayout {
+    /// Source area that is sampled (x, y, width, height), in fractional pixels
+    pub source: (f64, f64, f64, f64),
+    /// Output area covered by the image; the rest is background
+    pub target: CaptureRect,
+}
+
+/// Resamples frames to a fixed output size
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameScaler {
+    width: u32,
+    height: u32,
+    filter: ScaleFilter,
+    fit: FitMode,
+    /// Letterbox fill as [R, G, B, A]
+    background: [u8; 4],
+}
+
+impl FrameScaler {
+    /// Bilinear, letterboxed onto black
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            filter: ScaleFilter::default(),
+            fit: FitMode::default(),
+            background: [0, 0, 0, 255],
+        }
+    }
+
+    pub fn with_filter(mut self, filter: ScaleFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_fit(mut self, fit: FitMode) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Letterbox fill color as [R, G, B, A]
+    pub fn with_background(mut self, rgba: [u8; 4]) -> Self {
+        self.background = rgba;
+        self
+    }
+
+    pub fn output_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Compute which part of a `src_width` x `src_height` frame goes where
+    pub fn layout(&self, src_width: u32, src_height: u32) -> ScaleLayout {
+        let (sw, sh) = (src_width as f64, src_height as f64);
+        let (dw, dh) = (self.width as f64, self.height as f64);
+        let full_source = (0.0, 0.0, sw, sh);
+        let full_target = CaptureRect::new(0, 0, self.width, self.height);
+
+        match self.fit {
+            FitMode::Stretch => ScaleLayout {
+                source: full_source,
+                target: full_target,
+            },
+            FitMode::Letterbox => {
+                let scale = (dw / sw).min(dh / sh);
+                let w = ((sw * scale).round() as u32).clamp(1, self.width);
+                let h = ((sh * scale).round() as u32).clamp(1, self.height);
+                ScaleLayout {
+                    source: full_source,
+                    target: CaptureRect::new(
+                        ((self.width - w) / 2) as i32,
+                        ((self.height - h) / 2) as i32,
+                        w,
+                        h,
+                    ),
+                }
+            }
+            FitMode::CropToFill => {
+                let scale = (dw / sw).max(dh / sh);
+                let (cw, ch) = (dw / scale, dh / scale);
+                ScaleLayout {
+                    source: ((sw - cw) / 2.0, (sh - ch) / 2.0, cw, ch),
+                    target: full_target,
+                }
+            }
+        }
+    }
+
+    /// Produce a new frame of exactly `output_size`
+    ///
+    /// Packed frames keep their pixel format; planar frames come out as BGRA.
+    /// Row padding (`stride`) in the source is honoured. Timing metadata is
+    /// copied over, damage is reset to unknown.
+    pub fn scale(&self, frame: &CaptureFrame) -> Result<CaptureFrame> {
+        self.check_sizes(frame)?;
+        self.resample_frame(frame, self.layout(frame.width, frame.height))
+    }
+
+    /// Stretch a fractional source area `(x, y, width, height)` over the whole output
+    ///
+    /// Used for magnifying part of a frame; sub-pixel positions keep slow pans smooth.
+    pub fn scale_area(
+        &self,
+        frame: &CaptureFrame,
+        source: (f64, f64, f64, f64),
+    ) -> Result<CaptureFrame> {
+        self.check_sizes(frame)?;
+        if source.2 <= 0.0 || source.3 <= 0.0 {
+            bail!("Source area must be non-empty");
+        }
+        let layout = ScaleLayout {
+            source,
+            target: CaptureRect::new(0, 0, self.width, self.height),
+        };
+        self.resample_frame(frame, layout)
+    }
+
+    fn check_sizes(&self, frame: &CaptureFrame) -> Result<()> {
+        if self.width == 0 || self.height == 0 {
+            bail!("Scaler output size must be non-zero");
+        }
+        if frame.width == 0 || frame.height == 0 {
+            bail!("Cannot scale an empty frame");
+        }
+        Ok(())
+    }
+
+    fn resample_frame(&self, frame: &CaptureFrame, layout: ScaleLayout) -> Result<CaptureFrame> {
+        let converted = if frame.pixel_format.is_planar() {
+            Some(convert::to_packed(frame, PixelFormat::Bgra8)?)
+        } else {
+            None
+        };
+        let (src, format, stride) = if let Some(converted) = &converted {
+            (
+                converted.as_slice(),
+                PixelFormat::Bgra8,
+                PixelFormat::Bgra8.packed_stride(frame.width),
+            )
+        } else {
+            convert::validate_layout(
+                frame.pixel_format,
+                frame.width,
+                frame.height,
+                frame.stride,
+                frame.data.len(),
+            )?;
+            (frame.data.as_slice(), frame.pixel_format, frame.stride)
+        };
+
+        let channels = format.bytes_per_pixel() as usize;
+        let out_stride = self.width as usize * channels;
+        let mut data = FramePool::global().take(out_stride * self.height as usize);
+
+        let target = layout.target;
+        if target.width != self.width || target.height != self.height {
+            fill(&mut data, pixel_bytes(self.background, format));
+        }
+
+        let (sx, sy, sw, sh) = layout.source;
+        let columns = contributions(self.filter, sx, sw, frame.width, target.width);
+        let rows = contributions(self.filter, sy, sh, frame.height, target.height);
+        let image = Image {
+            data: src,
+            stride: stride as usize,
+            channels,
+        };
+        resample(&image, &columns, &rows, &mut data, out_stride, target);
+        if let Some(converted) = converted {
+            FramePool::global().recycle(converted);
+        }
+
+        let mut scaled = CaptureFrame::new(data, self.width, self.height)
+            .with_offset(frame.offset_x, frame.offset_y)
+            .with_pixel_format(format);
+        scaled.captured_at = frame.captured_at;
+        scaled.sequence = frame.sequence;
+        scaled.dropped_frames = frame.dropped_frames;
+        Ok(scaled)
+    }
+}
+
+fn fill(data: &mut [u8], pixel: Vec<u8>) {
+    for px in data.chunks_exact_mut(pixel.len()) {
+        px.copy_from_slice(&pixel);
+    }
+}
+
+struct Image<'a> {
+    data: &'a [u8],
+    stride: usize,
+    channels: usize,
+}
+
+/// Source pixels and weights feeding one output pixel along an axis
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+/// Weights for mapping `src_len` source pixels starting at `src_start` onto `dst_len` outputs
+fn contributions(
+    filter: ScaleFilter,
+    src_start: f64,
+    src_len: f64,
+    src_size: u32,
+    dst_len: u32,
+) -> Vec<Contribution> {
+    let ratio = src_len / dst_len as f64;
+    // Widen the kernel when shrinking so every source pixel contributes
+    let filter_scale = ratio.max(1.0);
+    let support = filter.support() * filter_scale;
+    let last = src_size as i64 - 1;
+
+    (0..dst_len)
+        .map(|i| {
+            let center = src_start + (i as f64 + 0.5) * ratio;
+            if filter == ScaleFilter::Nearest {
+                let index = (center.floor() as i64).clamp(0, last) as usize;
+                return Contribution {
+                    start: index,
+                    weights: vec![1.0],
+                };
+            }
+
+            let left = ((center - support).floor() as i64).clamp(0, last);
+            let right = ((center + support).ceil() as i64).clamp(0, last);
+            let mut weights: Vec<f64> = (left..=right)
+                .map(|j| filter.weight((j as f64 + 0.5 - center) / filter_scale))
+                .collect();
+
+            let sum: f64 = weights.iter().sum();
+            if sum.abs() > f64::EPSILON {
+                weights.iter_mut().for_each(|w| *w /= sum);
+            } else {
+                // Kernel fell entirely between samples; use the nearest one
+                weights = vec![0.0; weights.len()];
+                let nearest = (center.floor() as i64).clamp(left, right) - left;
+                weights[nearest as usize] = 1.0;
+            }
+
+            Contribution {
+                start: left as usize,
+                weights: weights.into_iter().map(|w| w as f32).collect(),
+            }
+        })
+        .collect()
+}
+
+fn resample(
+    src: &Image<'_>,
+    columns: &[Contribution],
+    rows: &[Contribution],
+    out: &mut [u8],
+    out_stride: usize,
+    target: CaptureRect,
+) {
+    let channels = src.channels;
+    let first_row = rows.iter().map(|c| c.start).min().unwrap_or(0);
+    let last_row = rows
+        .iter()
+        .map(|c| c.start + c.weights.len())
+        .max()
+        .unwrap_or(0);
+
+    // Horizontal pass over the rows the vertical pass will read
+    let row_len = columns.len() * channels;
+    let mut horizontal = vec![0f32; (last_row - first_row) * row_len];
+    for (y, dst_row) in (first_row..last_row).zip(horizontal.chunks_exact_mut(row_len)) {
+        let src_row = &src.data[y * src.stride..];
+        for (column, dst_px) in columns.iter().zip(dst_row.chunks_exact_mut(channels)) {
+            for (k, &weight) in column.weights.iter().enumerate() {
+                let offset = (column.start + k) * channels;
+                for (c, acc) in dst_px.iter_mut().enumerate() {
+                    *acc += weight * src_row[offset + c] as f32;
+                }
+            }
+        }
+    }
+
+    // Vertical pass straight into the output
+    let mut acc = vec![0f32; row_len];
+    for (y, row) in rows.iter().enumerate() {
+        acc.iter_mut().for_each(|v| *v = 0.0);
+        for (k, &weight) in row.weights.iter().enumerate() {
+            let start = (row.start + k - first_row) * row_len;
+            for (a, &h) in acc.iter_mut().zip(&horizontal[start..start + row_len]) {
+                *a += weight * h;
+            }
+        }
+
+        let out_start = (target.y as usize + y) * out_stride + target.x as usize * channels;
+        for (dst, &v) in out[out_start..out_start + row_len].iter_mut().zip(&acc) {
+            *dst = v.round().clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32) -> CaptureFrame {
+        let data = (0..height)
+            .flat_map(|_| (0..width).flat_map(|x| [(x * 255 / (width - 1)) as u8, 0, 0, 255]))
+            .collect();
+        CaptureFrame::new(data, width, height)
+    }
+
+    fn px(frame: &CaptureFrame, x: u32, y: u32) -> &[u8] {
+        let bpp = frame.pixel_format.bytes_per_pixel();
+        let start = (y * frame.stride + x * bpp) as usize;
+        &frame.data[start..start + bpp as usize]
+    }
+
+    #[test]
+    fn letterbox_centers_and_pads() {
+        let scaler = FrameScaler::new(160, 90).with_background([10, 20, 30, 255]);
+        let layout = scaler.layout(100, 100);
+        assert_eq!(layout.target, CaptureRect::new(35, 0, 90, 90));
+
+        let frame = CaptureFrame::new(vec![200; 100 * 100 * 4], 100, 100);
+        let scaled = scaler.scale(&frame).unwrap();
+        assert_eq!((scaled.width, scaled.height), (160, 90));
+        // Background is stored in the frame's BGRA order
+        assert_eq!(px(&scaled, 0, 45), &[30, 20, 10, 255]);
+        assert_eq!(px(&scaled, 80, 45), &[200, 200, 200, 200]);
+    }
+
+    #[test]
+    fn crop_to_fill_samples_center() {
+        let scaler = FrameScaler::new(100, 100).with_fit(FitMode::CropToFill);
+        let layout = scaler.layout(200, 100);
+        assert_eq!(layout.source, (50.0, 0.0, 100.0, 100.0));
+        assert_eq!(layout.target, CaptureRect::new(0, 0, 100, 100));
+    }
+
+    #[test]
+    fn stretch_covers_whole_output() {
+        let scaler = FrameScaler::new(64, 8).with_fit(FitMode::Stretch);
+        let scaled = scaler.scale(&gradient(16, 16)).unwrap();
+        assert_eq!((scaled.width, scaled.height), (64, 8));
+        // Gradient still runs left to right
+        assert!(px(&scaled, 0, 4)[0] < px(&scaled, 63, 4)[0]);
+    }
+
+    #[test]
+    fn filters_preserve_flat_color() {
+        let frame = CaptureFrame::new(vec![77; 37 * 23 * 4], 37, 23);
+        for filter in [
+            ScaleFilter::Nearest,
+            ScaleFilter::Bilinear,
+            ScaleFilter::Lanczos3,
+        ] {
+            for (w, h) in [(100, 60), (10, 7)] {
+                let scaled = FrameScaler::new(w, h)
+                    .with_filter(filter)
+                    .with_fit(FitMode::Stretch)
+                    .scale(&frame)
+                    .unwrap();
+                assert!(
+                    scaled.data.iter().all(|&b| b == 77),
+                    "{:?} to {}x{}",
+                    filter,
+                    w,
+                    h
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_doubles_pixels() {
+        let frame = CaptureFrame::new(vec![1, 1, 1, 1, 2, 2, 2, 2], 2, 1);
+        let scaled = FrameScaler::new(4, 2)
+            .with_filter(ScaleFilter::Nearest)
+            .with_fit(FitMode::Stretch)
+            .scale(&frame)
+            .unwrap();
+        let firsts: Vec<u8> = scaled.data.chunks_exact(4).map(|p| p[0]).collect();
+        assert_eq!(firsts, vec![1, 1, 2, 2, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn respects_stride_and_keeps_metadata() {
+        // 2x2 RGB with 2 bytes of padding per row
+        let mut frame = CaptureFrame::new(
+            vec![9, 9, 9, 9, 9, 9, 0xEE, 0xEE, 9, 9, 9, 9, 9, 9, 0xEE, 0xEE],
+            2,
+            2,
+        )
+        .with_pixel_format(PixelFormat::Rgb8)
+        .with_offset(5, 6);
+        frame.stride = 8;
+        frame.sequence = 42;
+
+        let scaled = FrameScaler::new(3, 3)
+            .with_filter(ScaleFilter::Lanczos3)
+            .with_fit(FitMode::Stretch)
+            .scale(&frame)
+            .unwrap();
+        assert_eq!(scaled.pixel_format, PixelFormat::Rgb8);
+        assert_eq!(scaled.stride, 9);
+        assert!(scaled.data.iter().all(|&b| b == 9));
+        assert_eq!((scaled.offset_x, scaled.offset_y), (5, 6));
+        assert_eq!(scaled.sequence, 42);
+        assert_eq!(scaled.captured_at, frame.captured_at);
+    }
+
+    #[test]
+    fn planar_frames_come_out_as_bgra() {
+        let frame =
+            CaptureFrame::new(vec![128; 4 * 4 * 3 / 2], 4, 4).with_pixel_format(PixelFormat::Nv12);
+        let scaled = FrameScaler::new(8, 8).scale(&frame).unwrap();
+        assert_eq!(scaled.pixel_format, PixelFormat::Bgra8);
+        assert_eq!(scaled.data.len(), 8 * 8 * 4);
+    }
+
+    #[test]
+    fn rejects_empty_sizes() {
+        let frame = gradient(4, 4);
+        assert!(FrameScaler::new(0, 10).scale(&frame).is_err());
+        assert!(FrameScaler::new(10, 10)
+            .scale(&CaptureFrame::new(Vec::new(), 0, 0))
+            .is_err());
+    }
+}
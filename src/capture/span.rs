@@ -0,0 +1,478 @@
+// capture/span.rs - Monitor-Spanning Capture
+//
+// Backends capture one display at a time, so a region straddling two monitors
+// used to be clipped to one of them. `SpanningCaptureEngine` splits the region
+// per monitor, runs one engine per covered monitor and stitches their frames
+// into a single frame covering the whole region.
+//
+// Monitors may have different scale factors; every part is resampled to the
+// highest scale among the covered monitors, so the stitched frame keeps the
+// sharpest display's detail. Parts of the region outside every monitor (gaps
+// in an L-shaped layout, for example) are filled with `gap_color`.
+
+use super::compose::{blit, filled_canvas};
+use super::{
+    CaptureEngine, CaptureFrame, CaptureRect, CaptureStats, CursorStyle, FramePool,
+    FrameStatsTracker,
+};
+use crate::window_filter::WindowIdentifier;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Spanning behavior, usually from the `monitor_span` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpanSettings {
+    pub enabled: bool,
+    /// Fill for parts of the region that no monitor covers, as [R, G, B, A]
+    pub gap_color: [u8; 4],
+}
+
+impl Default for SpanSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            gap_color: [0, 0, 0, 255],
+        }
+    }
+}
+
+/// A monitor as seen by the spanning engine
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpanMonitor {
+    /// Bounds in the same coordinate space as capture regions
+    pub bounds: CaptureRect,
+    /// Frame pixels per region unit on this monitor (the backing scale on
+    /// macOS, where regions are in points; 1.0 where regions are in pixels)
+    pub scale_factor: f64,
+}
+
+impl SpanMonitor {
+    pub fn new(bounds: CaptureRect, scale_factor: f64) -> Self {
+        Self {
+            bounds,
+            scale_factor,
+        }
+    }
+}
+
+/// Creates the engine that captures one monitor's part of the region
+pub type EngineFactory = Box<dyn FnMut() -> Result<Box<dyn CaptureEngine>> + Send>;
+
+struct SpanPart {
+    engine: Box<dyn CaptureEngine>,
+    /// Part of the region on this monitor
+    area: CaptureRect,
+    /// Latest frame, reused while the engine has nothing newer
+    last: Option<CaptureFrame>,
+}
+
+/// Capture engine that stitches a region spanning several monitors
+pub struct SpanningCaptureEngine {
+    monitors: Vec<SpanMonitor>,
+    factory: EngineFactory,
+    gap_color: [u8; 4],
+    /// One slot per monitor, filled while the region covers it
+    parts: Vec<Option<SpanPart>>,
+    region: Option<CaptureRect>,
+    show_cursor: bool,
+    excluded_windows: Option<Vec<WindowIdentifier>>,
+    cursor_style: Option<CursorStyle>,
+    cpu_frames_required: bool,
+    is_active: bool,
+    frame_stats: FrameStatsTracker,
+}
+
+impl SpanningCaptureEngine {
+    pub fn new(
+        monitors: Vec<SpanMonitor>,
+        settings: &SpanSettings,
+        factory: EngineFactory,
+    ) -> Self {
+        let parts = monitors.iter().map(|_| None).collect();
+        Self {
+            monitors,
+            factory,
+            gap_color: settings.gap_color,
+            parts,
+            region: None,
+            show_cursor: true,
+            excluded_windows: None,
+            cursor_style: None,
+            cpu_frames_required: false,
+            is_active: false,
+            frame_stats: FrameStatsTracker::new(),
+        }
+    }
+
+    /// Indices of the monitors the current region is captured from
+    pub fn active_monitors(&self) -> Vec<usize> {
+        (0..self.parts.len())
+            .filter(|&i| self.parts[i].is_some())
+            .collect()
+    }
+
+    /// Scale the stitched frame is produced at: the highest among covered monitors
+    pub fn output_scale(&self) -> f64 {
+        let scale = self
+            .active_monitors()
+            .into_iter()
+            .map(|i| self.monitors[i].scale_factor)
+            .filter(|s| s.is_finite())
+            .fold(0.0, f64::max);
+        if scale > 0.0 {
+            scale
+        } else {
+            1.0
+        }
+    }
+
+    /// Start, move or stop per-monitor engines so they match `region`
+    fn sync_parts(&mut self, region: CaptureRect) -> Result<()> {
+        for (index, monitor) in self.monitors.iter().enumerate() {
+            let area = region.intersect(&monitor.bounds);
+            match (area, self.parts[index].as_mut()) {
+                (Some(area), Some(part)) => {
+                    if part.area != area {
+                        part.engine.update_region(area)?;
+                        part.area = area;
+                    }
+                }
+                (Some(area), None) => {
+                    let mut engine = (self.factory)()?;
+                    if let Some(style) = &self.cursor_style {
+                        engine.set_cursor_style(style.clone())?;
+                    }
+                    engine.set_cpu_frames_required(self.cpu_frames_required);
+                    engine.start(area, self.show_cursor, self.excluded_windows.clone())?;
+                    self.parts[index] = Some(SpanPart {
+                        engine,
+                        area,
+                        last: None,
+                    });
+                }
+                (None, Some(_)) => {
+                    if let Some(mut part) = self.parts[index].take() {
+                        stop_part(&mut part);
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn stop_parts(&mut self) {
+        for slot in &mut self.parts {
+            if let Some(mut part) = slot.take() {
+                stop_part(&mut part);
+            }
+        }
+    }
+}
+
+fn stop_part(part: &mut SpanPart) {
+    part.engine.stop();
+    if let Some(frame) = part.last.take() {
+        FramePool::global().recycle(frame.data);
+    }
+}
+
+impl CaptureEngine for SpanningCaptureEngine {
+    fn start(
+        &mut self,
+        region: CaptureRect,
+        show_cursor: bool,
+        excluded_windows: Option<Vec<WindowIdentifier>>,
+    ) -> Result<()> {
+        self.stop_parts();
+        if !self
+            .monitors
+            .iter()
+            .any(|m| region.intersect(&m.bounds).is_some())
+        {
+            bail!("Capture region {:?} is not on any monitor", region);
+        }
+
+        self.show_cursor = show_cursor;
+        self.excluded_windows = excluded_windows;
+        if let Err(e) = self.sync_parts(region) {
+            self.stop_parts();
+            return Err(e);
+        }
+        self.region = Some(region);
+        self.frame_stats.reset();
+        self.is_active = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_parts();
+        self.is_active = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn has_new_frame(&self) -> bool {
+        self.is_active
+            && self
+                .parts
+                .iter()
+                .flatten()
+                .any(|part| part.engine.has_new_frame())
+    }
+
+    fn get_frame(&mut self) -> Option<CaptureFrame> {
+        if !self.is_active {
+            return None;
+        }
+        let region = self.region?;
+
+        let mut updated = false;
+        for part in self.parts.iter_mut().flatten() {
+            if let Some(frame) = part.engine.get_frame() {
+                if let Some(old) = part.last.replace(frame) {
+                    FramePool::global().recycle(old.data);
+                }
+                updated = true;
+            }
+        }
+        if !updated {
+            return None;
+        }
+
+        let scale = self.output_scale();
+        let size = CaptureRect::new(0, 0, region.width, region.height).to_pixels(scale);
+        if size.is_empty() {
+            return None;
+        }
+        let canvas_size = (size.width, size.height);
+        let mut data = filled_canvas(canvas_size, self.gap_color);
+        let mut captured_at = None;
+        for part in self.parts.iter().flatten() {
+            if let Some(frame) = &part.last {
+                let cell = part.area.relative_to(&region).to_pixels(scale);
+                if let Err(e) = blit(&mut data, canvas_size, &cell, frame) {
+                    log::warn!("Monitor part {:?} not stitched: {}", part.area, e);
+                }
+                captured_at = captured_at.max(Some(frame.captured_at));
+            }
+        }
+
+        let mut frame =
+            CaptureFrame::new(data, size.width, size.height).with_offset(region.x, region.y);
+        if let Some(captured_at) = captured_at {
+            frame.captured_at = captured_at;
+        }
+        self.frame_stats.stamp(&mut frame);
+        Some(frame)
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<()> {
+        self.show_cursor = visible;
+        for part in self.parts.iter_mut().flatten() {
+            part.engine.set_cursor_visible(visible)?;
+        }
+        Ok(())
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        for part in self.parts.iter_mut().flatten() {
+            part.engine.set_cursor_style(style.clone())?;
+        }
+        self.cursor_style = Some(style);
+        Ok(())
+    }
+
+    fn set_cpu_frames_required(&mut self, required: bool) {
+        for part in self.parts.iter_mut().flatten() {
+            part.engine.set_cpu_frames_required(required);
+        }
+        self.cpu_frames_required = required;
+    }
+
+    fn get_region(&self) -> Option<CaptureRect> {
+        self.region
+    }
+
+    fn update_region(&mut self, region: CaptureRect) -> Result<()> {
+        if self.is_active {
+            self.sync_parts(region)?;
+        }
+        self.region = Some(region);
+        Ok(())
+    }
+
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{SyntheticCaptureEngine, SyntheticPattern};
+
+    /// Engine returning solid frames at `scale` pixels per region unit
+    struct SolidEngine {
+        bgra: [u8; 4],
+        scale: u32,
+        region: Option<CaptureRect>,
+    }
+
+    impl CaptureEngine for SolidEngine {
+        fn start(
+            &mut self,
+            region: CaptureRect,
+            _show_cursor: bool,
+            _excluded_windows: Option<Vec<WindowIdentifier>>,
+        ) -> Result<()> {
+            self.region = Some(region);
+            Ok(())
+        }
+
+        fn stop(&mut self) {
+            self.region = None;
+        }
+
+        fn is_active(&self) -> bool {
+            self.region.is_some()
+        }
+
+        fn has_new_frame(&self) -> bool {
+            self.region.is_some()
+        }
+
+        fn get_frame(&mut self) -> Option<CaptureFrame> {
+            let region = self.region?;
+            let (w, h) = (region.width * self.scale, region.height * self.scale);
+            let data = self.bgra.repeat((w * h) as usize);
+            Some(CaptureFrame::new(data, w, h).with_offset(region.x, region.y))
+        }
+
+        fn set_cursor_visible(&mut self, _visible: bool) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_region(&self) -> Option<CaptureRect> {
+            self.region
+        }
+
+        fn update_region(&mut self, region: CaptureRect) -> Result<()> {
+            self.region = Some(region);
+            Ok(())
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn pixel(frame: &CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+        let idx = (y * frame.stride + x * 4) as usize;
+        [
+            frame.data[idx],
+            frame.data[idx + 1],
+            frame.data[idx + 2],
+            frame.data[idx + 3],
+        ]
+    }
+
+    /// A 1x display on the left and a 2x display on the right, 100 units wide each
+    /// with the right one shorter, so a tall region leaves a gap below it
+    fn mixed_scale_engine() -> SpanningCaptureEngine {
+        let monitors = vec![
+            SpanMonitor::new(CaptureRect::new(0, 0, 100, 100), 1.0),
+            SpanMonitor::new(CaptureRect::new(100, 0, 100, 50), 2.0),
+        ];
+        let mut created = 0;
+        let factory: EngineFactory = Box::new(move || {
+            created += 1;
+            let (bgra, scale) = if created == 1 {
+                ([255, 0, 0, 255], 1)
+            } else {
+                ([0, 255, 0, 255], 2)
+            };
+            Ok(Box::new(SolidEngine {
+                bgra,
+                scale,
+                region: None,
+            }) as Box<dyn CaptureEngine>)
+        });
+        let settings = SpanSettings {
+            enabled: true,
+            gap_color: [255, 0, 255, 255],
+        };
+        SpanningCaptureEngine::new(monitors, &settings, factory)
+    }
+
+    #[test]
+    fn stitches_mixed_scale_monitors_and_fills_gaps() {
+        let mut engine = mixed_scale_engine();
+        engine
+            .start(CaptureRect::new(80, 10, 40, 60), false, None)
+            .unwrap();
+        assert_eq!(engine.active_monitors(), vec![0, 1]);
+        assert_eq!(engine.output_scale(), 2.0);
+
+        let frame = engine.get_frame().expect("frame");
+        assert_eq!((frame.width, frame.height), (80, 120));
+        assert_eq!((frame.offset_x, frame.offset_y), (80, 10));
+        // Left part upscaled from the 1x monitor (blue in BGRA)
+        assert_eq!(pixel(&frame, 10, 100), [255, 0, 0, 255]);
+        // Right part from the 2x monitor
+        assert_eq!(pixel(&frame, 60, 10), [0, 255, 0, 255]);
+        // Below the shorter right monitor: gap color
+        assert_eq!(pixel(&frame, 60, 100), [255, 0, 255, 255]);
+    }
+
+    #[test]
+    fn moving_the_region_starts_and_stops_monitor_parts() {
+        let mut engine = mixed_scale_engine();
+        engine
+            .start(CaptureRect::new(10, 10, 20, 20), false, None)
+            .unwrap();
+        assert_eq!(engine.active_monitors(), vec![0]);
+        let frame = engine.get_frame().expect("frame");
+        assert_eq!((frame.width, frame.height), (20, 20));
+
+        engine
+            .update_region(CaptureRect::new(90, 10, 20, 20))
+            .unwrap();
+        assert_eq!(engine.active_monitors(), vec![0, 1]);
+        engine
+            .update_region(CaptureRect::new(150, 10, 20, 20))
+            .unwrap();
+        assert_eq!(engine.active_monitors(), vec![1]);
+        let frame = engine.get_frame().expect("frame");
+        assert_eq!((frame.width, frame.height), (40, 40));
+
+        engine.stop();
+        assert!(engine.active_monitors().is_empty());
+        assert!(engine.get_frame().is_none());
+    }
+
+    #[test]
+    fn rejects_regions_off_every_monitor() {
+        let mut engine = SpanningCaptureEngine::new(
+            vec![SpanMonitor::new(CaptureRect::new(0, 0, 100, 100), 1.0)],
+            &SpanSettings::default(),
+            Box::new(|| {
+                Ok(
+                    Box::new(SyntheticCaptureEngine::new(SyntheticPattern::ColorBars))
+                        as Box<dyn CaptureEngine>,
+                )
+            }),
+        );
+        assert!(engine
+            .start(CaptureRect::new(500, 500, 10, 10), false, None)
+            .is_err());
+        assert!(!engine.is_active());
+    }
+}
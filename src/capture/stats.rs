@@ -0,0 +1,134 @@
+// capture/stats.rs - Per-Session Frame Accounting
+//
+// Backends own a FrameStatsTracker, reset it when a session starts and pass every
+// frame they hand out through `stamp`. That gives each frame its sequence number
+// and the running drop count, and keeps the totals behind `CaptureEngine::stats`.
+
+use super::CaptureFrame;
+use std::time::{Duration, Instant};
+
+/// Delivery statistics for the current (or last) capture session
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CaptureStats {
+    /// Frames returned from `get_frame`
+    pub frames_delivered: u64,
+    /// Frames the source produced that were never delivered
+    pub frames_dropped: u64,
+    /// Mean time between consecutive delivered frames
+    pub average_interval: Option<Duration>,
+}
+
+impl CaptureStats {
+    /// Delivered frames per second derived from `average_interval`
+    pub fn average_fps(&self) -> Option<f64> {
+        self.average_interval
+            .filter(|interval| !interval.is_zero())
+            .map(|interval| 1.0 / interval.as_secs_f64())
+    }
+}
+
+/// Sequence numbering and delivery/drop counters for one capture session
+#[derive(Debug, Clone, Default)]
+pub struct FrameStatsTracker {
+    delivered: u64,
+    dropped: u64,
+    first_delivery: Option<Instant>,
+    last_delivery: Option<Instant>,
+}
+
+impl FrameStatsTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new session: sequence numbers restart at zero
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Count frames the source produced but that will never be delivered
+    pub fn record_dropped(&mut self, count: u64) {
+        self.dropped += count;
+    }
+
+    /// Assign the next sequence number and the running drop count to `frame`
+    pub fn stamp(&mut self, frame: &mut CaptureFrame) {
+        let now = Instant::now();
+        frame.sequence = self.delivered;
+        frame.dropped_frames = self.dropped;
+
+        self.delivered += 1;
+        self.first_delivery.get_or_insert(now);
+        self.last_delivery = Some(now);
+    }
+
+    /// Convenience wrapper around `stamp` for `Option` returning code paths
+    pub fn deliver(&mut self, frame: Option<CaptureFrame>) -> Option<CaptureFrame> {
+        frame.map(|mut frame| {
+            self.stamp(&mut frame);
+            frame
+        })
+    }
+
+    pub fn stats(&self) -> CaptureStats {
+        let average_interval = match (self.first_delivery, self.last_delivery) {
+            (Some(first), Some(last)) if self.delivered > 1 => {
+                Some((last - first) / (self.delivered - 1) as u32)
+            }
+            _ => None,
+        };
+
+        CaptureStats {
+            frames_delivered: self.delivered,
+            frames_dropped: self.dropped,
+            average_interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> CaptureFrame {
+        CaptureFrame::new(vec![0; 4], 1, 1)
+    }
+
+    #[test]
+    fn stamps_sequence_and_running_drops() {
+        let mut tracker = FrameStatsTracker::new();
+
+        let first = tracker.deliver(Some(frame())).unwrap();
+        tracker.record_dropped(2);
+        let second = tracker.deliver(Some(frame())).unwrap();
+        assert!(tracker.deliver(None).is_none());
+
+        assert_eq!((first.sequence, first.dropped_frames), (0, 0));
+        assert_eq!((second.sequence, second.dropped_frames), (1, 2));
+
+        let stats = tracker.stats();
+        assert_eq!(stats.frames_delivered, 2);
+        assert_eq!(stats.frames_dropped, 2);
+        assert!(stats.average_interval.is_some());
+    }
+
+    #[test]
+    fn reset_starts_a_new_session() {
+        let mut tracker = FrameStatsTracker::new();
+        tracker.deliver(Some(frame()));
+        tracker.record_dropped(5);
+        tracker.reset();
+
+        assert_eq!(tracker.stats(), CaptureStats::default());
+        assert_eq!(tracker.deliver(Some(frame())).unwrap().sequence, 0);
+    }
+
+    #[test]
+    fn average_fps_needs_two_frames() {
+        let mut stats = CaptureStats::default();
+        assert_eq!(stats.average_fps(), None);
+
+        stats.average_interval = Some(Duration::from_millis(20));
+        assert!((stats.average_fps().unwrap() - 50.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,178 @@
+// capture/stream.rs - Async Frame Stream (feature "stream")
+//
+// Wraps a FramePump and a bounded channel in a `futures_core::Stream`, so async
+// services can consume any CaptureEngine with `while let Some(frame) = stream.next().await`.
+// The stream is runtime-agnostic: the pump wakes the task directly. Dropping the
+// stream drops its receiver, which releases the pump thread and joins it.
+
+use super::delivery::{DropPolicy, FramePump, FrameReceiver, FrameSink, SharedCaptureEngine};
+use super::pacing::FramePacer;
+use super::{CaptureEngine, CaptureFrame, CaptureRect};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Queueing and polling behaviour of a `FrameStream`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStreamOptions {
+    /// Frames buffered while the consumer is busy
+    pub depth: usize,
+    /// What happens to frames when the buffer is full
+    pub policy: DropPolicy,
+    /// How often the engine is polled
+    pub interval: Duration,
+}
+
+impl Default for FrameStreamOptions {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            policy: DropPolicy::DropOldest,
+            interval: FramePacer::interval_for_fps(60),
+        }
+    }
+}
+
+/// `Stream` of frames captured from an engine
+pub struct FrameStream {
+    // Declared before the pump so the receiver is dropped first, which unblocks
+    // a pump waiting under `DropPolicy::Block`
+    receiver: FrameReceiver,
+    pump: FramePump,
+}
+
+impl FrameStream {
+    /// Stream frames from an engine slot that other code may still lock
+    pub fn new(engine: SharedCaptureEngine, options: FrameStreamOptions) -> Self {
+        let (sink, receiver) = FrameSink::channel_with_policy(options.depth, options.policy);
+        let pump = FramePump::start(engine, sink, options.interval);
+        Self { receiver, pump }
+    }
+
+    /// Stream frames from an engine that has already been started
+    pub fn from_engine(engine: Box<dyn CaptureEngine>, options: FrameStreamOptions) -> Self {
+        Self::new(Arc::new(Mutex::new(Some(engine))), options)
+    }
+
+    /// Move the capture region without taking the engine lock
+    pub fn request_region(&self, region: CaptureRect) {
+        self.pump.request_region(region);
+    }
+
+    /// Frames discarded by the drop policy so far
+    pub fn dropped(&self) -> u64 {
+        self.receiver.dropped()
+    }
+}
+
+impl Stream for FrameStream {
+    type Item = CaptureFrame;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<CaptureFrame>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{SyntheticCaptureEngine, SyntheticPattern};
+    use std::future::Future;
+    use std::task::{Wake, Waker};
+    use std::thread::Thread;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    fn next(stream: &mut FrameStream) -> Option<CaptureFrame> {
+        block_on(std::future::poll_fn(|cx| {
+            Pin::new(&mut *stream).poll_next(cx)
+        }))
+    }
+
+    fn started_engine() -> Box<dyn CaptureEngine> {
+        let mut engine = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        engine
+            .start(CaptureRect::new(0, 0, 16, 8), false, None)
+            .unwrap();
+        Box::new(engine)
+    }
+
+    fn options(policy: DropPolicy) -> FrameStreamOptions {
+        FrameStreamOptions {
+            depth: 1,
+            policy,
+            interval: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn yields_frames_in_sequence() {
+        let mut stream = FrameStream::from_engine(started_engine(), options(DropPolicy::Block));
+
+        let sequences: Vec<u64> = (0..3)
+            .map(|_| next(&mut stream).unwrap().sequence)
+            .collect();
+        // Blocking backpressure never skips a frame
+        assert_eq!(sequences, vec![0, 1, 2]);
+        assert_eq!(stream.dropped(), 0);
+    }
+
+    #[test]
+    fn slow_consumer_drops_frames() {
+        let mut stream =
+            FrameStream::from_engine(started_engine(), options(DropPolicy::DropNewest));
+        let first = next(&mut stream).unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+
+        let queued = next(&mut stream).unwrap();
+        assert!(stream.dropped() > 0);
+        // The frame that was queued first is kept, later ones were discarded
+        assert!(queued.sequence > first.sequence);
+        assert!(queued.captured_at.elapsed() >= Duration::from_millis(25));
+    }
+
+    #[test]
+    fn region_requests_reach_the_engine() {
+        let mut stream =
+            FrameStream::from_engine(started_engine(), options(DropPolicy::DropOldest));
+        stream.request_region(CaptureRect::new(4, 4, 6, 6));
+
+        let resized = (0..100)
+            .filter_map(|_| next(&mut stream))
+            .find(|frame| frame.width == 6);
+        assert!(resized.is_some());
+    }
+
+    #[test]
+    fn dropping_blocked_stream_stops_pump() {
+        let engine: SharedCaptureEngine = Arc::new(Mutex::new(Some(started_engine())));
+        let stream = FrameStream::new(engine.clone(), options(DropPolicy::Block));
+        // Let the pump fill the queue and block on the next frame
+        std::thread::sleep(Duration::from_millis(20));
+
+        drop(stream);
+        // The pump thread has been joined, so nobody else holds the engine
+        assert!(engine.try_lock().is_ok());
+        assert_eq!(Arc::strong_count(&engine), 1);
+    }
+}
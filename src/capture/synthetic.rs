@@ -0,0 +1,494 @@
+// capture/synthetic.rs - Procedural Test-Pattern Capture Engine
+//
+// Generates frames without touching the screen, so the capture/render path can be
+// exercised headless (CI, downstream integration tests, demos). Output depends only
+// on the capture region and the frame index, never on wall-clock time, so two
+// engines fed the same calls produce byte-identical frames.
+
+use super::{
+    glyphs, CaptureEngine, CaptureFrame, CaptureRect, CaptureStats, CursorCompositor, CursorImage,
+    CursorStyle, FramePool, FrameStatsTracker,
+};
+use crate::window_filter::WindowIdentifier;
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+/// 75% SMPTE color bars as BGRA
+const COLOR_BARS: [[u8; 4]; 7] = [
+    [191, 191, 191, 255], // white
+    [0, 191, 191, 255],   // yellow
+    [191, 191, 0, 255],   // cyan
+    [0, 191, 0, 255],     // green
+    [191, 0, 191, 255],   // magenta
+    [0, 0, 191, 255],     // red
+    [191, 0, 0, 255],     // blue
+];
+
+/// Time between synthetic frames on the engine's own clock (60 fps)
+const FRAME_INTERVAL: Duration = Duration::from_nanos(16_666_667);
+
+const COUNTER_SCALE: u32 = 2;
+const COUNTER_MARGIN: i32 = 4;
+
+/// Procedural pattern drawn by [`SyntheticCaptureEngine`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyntheticPattern {
+    /// Static vertical color bars spanning the capture region
+    ColorBars,
+    /// Gradient in screen space that scrolls a few pixels every frame
+    MovingGradient,
+}
+
+/// Capture engine that renders test patterns instead of reading the screen
+pub struct SyntheticCaptureEngine {
+    pattern: SyntheticPattern,
+    frame_counter: bool,
+    cursor_position: Option<(i32, i32)>,
+    cursor: CursorCompositor,
+    is_active: bool,
+    show_cursor: bool,
+    region: Option<CaptureRect>,
+    frame_index: u64,
+    /// Time of frame 0; frame N is `FRAME_INTERVAL * N` later
+    clock_start: Instant,
+    frame_stats: FrameStatsTracker,
+}
+
+impl SyntheticCaptureEngine {
+    /// Create an engine drawing `pattern` with the frame counter burned in
+    pub fn new(pattern: SyntheticPattern) -> Self {
+        Self {
+            pattern,
+            frame_counter: true,
+            cursor_position: None,
+            cursor: CursorCompositor::default(),
+            is_active: false,
+            show_cursor: true,
+            region: None,
+            frame_index: 0,
+            clock_start: Instant::now(),
+            frame_stats: FrameStatsTracker::new(),
+        }
+    }
+
+    /// Enable or disable the frame counter drawn in the top-left corner
+    pub fn with_frame_counter(mut self, enabled: bool) -> Self {
+        self.frame_counter = enabled;
+        self
+    }
+
+    /// Switch the pattern for subsequent frames
+    pub fn set_pattern(&mut self, pattern: SyntheticPattern) {
+        self.pattern = pattern;
+    }
+
+    /// Pin the fake cursor to a screen position, or `None` to let it wander
+    pub fn set_cursor_position(&mut self, position: Option<(i32, i32)>) {
+        self.cursor_position = position;
+    }
+
+    /// Number of frames generated in the current session
+    pub fn frames_generated(&self) -> u64 {
+        self.frame_index
+    }
+
+    fn fill_pattern(&self, frame: &mut CaptureFrame) {
+        let width = frame.width as usize;
+        let stride = frame.stride as usize;
+        let n = self.frame_index as i64;
+
+        for (row_idx, row) in frame.data.chunks_exact_mut(stride).enumerate() {
+            let screen_y = frame.offset_y as i64 + row_idx as i64;
+            for (col, pixel) in row[..width * 4].chunks_exact_mut(4).enumerate() {
+                let color = match self.pattern {
+                    SyntheticPattern::ColorBars => COLOR_BARS[col * COLOR_BARS.len() / width],
+                    SyntheticPattern::MovingGradient => {
+                        let screen_x = frame.offset_x as i64 + col as i64;
+                        [
+                            ((screen_x + screen_y) / 2 + n) as u8,
+                            (screen_y + n * 2) as u8,
+                            (screen_x + n * 4) as u8,
+                            255,
+                        ]
+                    }
+                };
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+
+    fn draw_counter(&self, frame: &mut CaptureFrame) {
+        let label = format!("#{}", self.frame_index);
+        let (text_w, text_h) = glyphs::text_size(&label, COUNTER_SCALE);
+        let pad = COUNTER_SCALE as i32;
+
+        // Black plate so the digits stay readable on any pattern
+        for y in 0..text_h as i32 + pad * 2 {
+            for x in 0..text_w as i32 + pad * 2 {
+                put_pixel(
+                    frame,
+                    COUNTER_MARGIN - pad + x,
+                    COUNTER_MARGIN - pad + y,
+                    [0, 0, 0, 255],
+                );
+            }
+        }
+
+        glyphs::draw_text(
+            frame,
+            COUNTER_MARGIN,
+            COUNTER_MARGIN,
+            &label,
+            COUNTER_SCALE,
+            [255, 255, 255, 255],
+        );
+    }
+
+    /// Cursor hotspot in screen coordinates
+    fn cursor_hotspot(&self, region: &CaptureRect) -> (i32, i32) {
+        if let Some(position) = self.cursor_position {
+            return position;
+        }
+
+        // Bounce along a triangle wave so the cursor sweeps the whole region
+        let bounce = |t: u64, span: u32| -> i32 {
+            let span = span.max(1) as u64;
+            let phase = t % (span * 2);
+            (if phase < span {
+                phase
+            } else {
+                span * 2 - phase
+            }) as i32
+        };
+        (
+            region.x + bounce(self.frame_index * 3, region.width.saturating_sub(1)),
+            region.y + bounce(self.frame_index * 2, region.height.saturating_sub(1)),
+        )
+    }
+
+    /// Time of the current frame on the synthetic clock, independent of how
+    /// fast frames are requested
+    fn frame_time(&self) -> Instant {
+        self.clock_start + FRAME_INTERVAL * self.frame_index.min(u32::MAX as u64) as u32
+    }
+
+    fn draw_cursor(&mut self, frame: &mut CaptureFrame, hotspot: (i32, i32)) {
+        let arrow = CursorImage::arrow();
+        let now = self.frame_time();
+        if let Err(e) = self
+            .cursor
+            .draw_screen(frame, &arrow, hotspot.0, hotspot.1, now)
+        {
+            log::warn!("Synthetic cursor not drawn: {}", e);
+        }
+    }
+}
+
+fn put_pixel(frame: &mut CaptureFrame, x: i32, y: i32, color: [u8; 4]) {
+    if x < 0 || y < 0 || x >= frame.width as i32 || y >= frame.height as i32 {
+        return;
+    }
+    let idx = y as usize * frame.stride as usize + x as usize * 4;
+    frame.data[idx..idx + 4].copy_from_slice(&color);
+}
+
+impl CaptureEngine for SyntheticCaptureEngine {
+    fn start(
+        &mut self,
+        region: CaptureRect,
+        show_cursor: bool,
+        _excluded_windows: Option<Vec<WindowIdentifier>>,
+    ) -> Result<()> {
+        self.region = Some(region);
+        self.show_cursor = show_cursor;
+        self.frame_index = 0;
+        self.clock_start = Instant::now();
+        // Auto-hide starts over with the new clock
+        self.cursor = CursorCompositor::new(self.cursor.style().clone());
+        self.frame_stats.reset();
+        self.is_active = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.is_active = false;
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    fn has_new_frame(&self) -> bool {
+        self.is_active
+    }
+
+    fn get_frame(&mut self) -> Option<CaptureFrame> {
+        if !self.is_active {
+            return None;
+        }
+
+        let region = self.region?;
+        if region.width == 0 || region.height == 0 {
+            return None;
+        }
+
+        // Every pixel is painted by fill_pattern, so stale pool contents are fine
+        let data = FramePool::global().take(region.width as usize * region.height as usize * 4);
+        let mut frame =
+            CaptureFrame::new(data, region.width, region.height).with_offset(region.x, region.y);
+
+        self.fill_pattern(&mut frame);
+        if self.frame_counter {
+            self.draw_counter(&mut frame);
+        }
+        if self.show_cursor {
+            let hotspot = self.cursor_hotspot(&region);
+            self.draw_cursor(&mut frame, hotspot);
+        }
+
+        self.frame_index += 1;
+        self.frame_stats.stamp(&mut frame);
+        Some(frame)
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<()> {
+        self.show_cursor = visible;
+        Ok(())
+    }
+
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        self.cursor.set_style(style);
+        Ok(())
+    }
+
+    fn get_region(&self) -> Option<CaptureRect> {
+        self.region
+    }
+
+    fn update_region(&mut self, region: CaptureRect) -> Result<()> {
+        self.region = Some(region);
+        Ok(())
+    }
+
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(frame: &CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+        let idx = (y * frame.stride + x * 4) as usize;
+        [
+            frame.data[idx],
+            frame.data[idx + 1],
+            frame.data[idx + 2],
+            frame.data[idx + 3],
+        ]
+    }
+
+    fn started(
+        pattern: SyntheticPattern,
+        region: CaptureRect,
+        show_cursor: bool,
+    ) -> SyntheticCaptureEngine {
+        let mut engine = SyntheticCaptureEngine::new(pattern);
+        engine.start(region, show_cursor, None).unwrap();
+        engine
+    }
+
+    #[test]
+    fn frames_match_region() {
+        let mut engine = started(
+            SyntheticPattern::ColorBars,
+            CaptureRect::new(30, 40, 70, 20),
+            false,
+        );
+        let frame = engine.get_frame().unwrap();
+
+        assert_eq!((frame.width, frame.height, frame.stride), (70, 20, 280));
+        assert_eq!((frame.offset_x, frame.offset_y), (30, 40));
+        assert_eq!(frame.data.len(), 70 * 20 * 4);
+        assert!(frame.data.chunks_exact(4).all(|px| px[3] == 255));
+    }
+
+    #[test]
+    fn default_start_target_accepts_regions_only() {
+        use crate::capture::{CaptureTarget, CaptureTargetKind};
+
+        let mut engine = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        assert_eq!(engine.supported_targets(), &[CaptureTargetKind::Region]);
+        assert!(engine.start_target(CaptureTarget::Window(7), false, None).is_err());
+        assert!(engine.start_target(CaptureTarget::Monitor(0), false, None).is_err());
+        assert!(!engine.is_active());
+
+        let region = CaptureRect::new(1, 2, 8, 4);
+        engine
+            .start_target(CaptureTarget::Region(region), false, None)
+            .unwrap();
+        assert_eq!(engine.get_region(), Some(region));
+    }
+
+    #[test]
+    fn color_bars_span_region() {
+        let mut engine = started(
+            SyntheticPattern::ColorBars,
+            CaptureRect::new(0, 0, 70, 40),
+            false,
+        )
+        .with_frame_counter(false);
+        let frame = engine.get_frame().unwrap();
+
+        assert_eq!(pixel(&frame, 0, 39), COLOR_BARS[0]);
+        assert_eq!(pixel(&frame, 35, 39), COLOR_BARS[3]);
+        assert_eq!(pixel(&frame, 69, 39), COLOR_BARS[6]);
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        let region = CaptureRect::new(5, 5, 64, 48);
+        let mut a = started(SyntheticPattern::MovingGradient, region, true);
+        let mut b = started(SyntheticPattern::MovingGradient, region, true);
+
+        for sequence in 0..3 {
+            let frame = a.get_frame().unwrap();
+            assert_eq!(frame.sequence, sequence);
+            assert_eq!(frame.data, b.get_frame().unwrap().data);
+        }
+        assert_eq!(a.frames_generated(), 3);
+        assert_eq!(a.stats().frames_delivered, 3);
+        assert_eq!(a.stats().frames_dropped, 0);
+    }
+
+    #[test]
+    fn gradient_moves_between_frames() {
+        let mut engine = started(
+            SyntheticPattern::MovingGradient,
+            CaptureRect::new(0, 0, 64, 64),
+            false,
+        )
+        .with_frame_counter(false);
+        let first = engine.get_frame().unwrap();
+        let second = engine.get_frame().unwrap();
+
+        assert_ne!(pixel(&first, 40, 40), pixel(&second, 40, 40));
+    }
+
+    #[test]
+    fn gradient_follows_screen_coordinates() {
+        let mut left = started(
+            SyntheticPattern::MovingGradient,
+            CaptureRect::new(0, 0, 32, 32),
+            false,
+        )
+        .with_frame_counter(false);
+        let mut right = started(
+            SyntheticPattern::MovingGradient,
+            CaptureRect::new(16, 0, 32, 32),
+            false,
+        )
+        .with_frame_counter(false);
+
+        let left = left.get_frame().unwrap();
+        let right = right.get_frame().unwrap();
+        assert_eq!(pixel(&left, 20, 10), pixel(&right, 4, 10));
+    }
+
+    #[test]
+    fn frame_counter_is_burned_in() {
+        let region = CaptureRect::new(0, 0, 80, 40);
+        let mut with = started(SyntheticPattern::ColorBars, region, false);
+        let mut without =
+            started(SyntheticPattern::ColorBars, region, false).with_frame_counter(false);
+
+        let with = with.get_frame().unwrap();
+        let without = without.get_frame().unwrap();
+        // The plate behind the digits is black, which no color bar is
+        assert_eq!(pixel(&with, 3, 3), [0, 0, 0, 255]);
+        assert_ne!(pixel(&without, 3, 3), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn cursor_respects_show_cursor() {
+        let region = CaptureRect::new(100, 100, 64, 64);
+        let mut engine =
+            started(SyntheticPattern::ColorBars, region, true).with_frame_counter(false);
+        engine.set_cursor_position(Some((130, 120)));
+
+        let frame = engine.get_frame().unwrap();
+        // Hotspot is the outline tip, one row below sits white fill
+        assert_eq!(pixel(&frame, 30, 20), [0, 0, 0, 255]);
+        assert_eq!(pixel(&frame, 31, 22), [255, 255, 255, 255]);
+
+        engine.set_cursor_visible(false).unwrap();
+        let frame = engine.get_frame().unwrap();
+        assert_ne!(pixel(&frame, 30, 20), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn cursor_auto_hide_follows_frame_clock() {
+        let region = CaptureRect::new(0, 0, 64, 64);
+        let mut engine =
+            started(SyntheticPattern::ColorBars, region, true).with_frame_counter(false);
+        engine.set_cursor_position(Some((30, 20)));
+        engine
+            .set_cursor_style(CursorStyle {
+                hide_after_ms: 100,
+                ..CursorStyle::default()
+            })
+            .unwrap();
+
+        let bars = started(SyntheticPattern::ColorBars, region, false)
+            .with_frame_counter(false)
+            .get_frame()
+            .unwrap();
+
+        // Requested back to back, but 60 fps on the synthetic clock: the pinned
+        // cursor is solid up to 100ms (frame 6) and gone after the 250ms fade
+        for index in 0..30 {
+            let frame = engine.get_frame().unwrap();
+            if index <= 6 {
+                assert_eq!(pixel(&frame, 30, 20), [0, 0, 0, 255], "frame {}", index);
+            } else if index >= 22 {
+                assert_eq!(frame.data, bars.data, "frame {}", index);
+            }
+        }
+    }
+
+    #[test]
+    fn update_region_resizes_frames() {
+        let mut engine = started(
+            SyntheticPattern::ColorBars,
+            CaptureRect::new(0, 0, 10, 10),
+            true,
+        );
+        engine
+            .update_region(CaptureRect::new(-20, 5, 40, 30))
+            .unwrap();
+
+        let frame = engine.get_frame().unwrap();
+        assert_eq!((frame.width, frame.height), (40, 30));
+        assert_eq!((frame.offset_x, frame.offset_y), (-20, 5));
+        assert_eq!(engine.get_region(), Some(CaptureRect::new(-20, 5, 40, 30)));
+    }
+
+    #[test]
+    fn inactive_engine_yields_nothing() {
+        let mut engine = SyntheticCaptureEngine::new(SyntheticPattern::ColorBars);
+        assert!(!engine.has_new_frame());
+        assert!(engine.get_frame().is_none());
+
+        engine
+            .start(CaptureRect::new(0, 0, 8, 8), false, None)
+            .unwrap();
+        assert!(engine.has_new_frame());
+        engine.stop();
+        assert!(engine.get_frame().is_none());
+    }
+}
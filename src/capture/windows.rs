@@ -27,14 +27,18 @@ use windows::Win32::Graphics::Gdi::{
 use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
 
 use windows::Win32::Graphics::Gdi::{
-    CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDC, ReleaseDC, SelectObject,
-    BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP, HDC,
+    CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GdiFlush, GetDC, GetObjectW,
+    ReleaseDC, SelectObject, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    HBITMAP,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    DrawIconEx, GetCursorInfo, GetIconInfo, CURSORINFO, DI_NORMAL, ICONINFO,
+    DrawIconEx, GetCursorInfo, GetIconInfo, CURSORINFO, DI_NORMAL, HICON, ICONINFO,
 };
 
-use super::{CaptureEngine, CaptureFrame};
+use super::{
+    CaptureEngine, CaptureFrame, CaptureStats, CursorCompositor, CursorImage, CursorStyle,
+    FrameStatsTracker,
+};
 use crate::capture::CaptureRect;
 use crate::window_filter::WindowIdentifier;
 
@@ -106,7 +110,11 @@ pub struct WindowsGdiCopyCaptureEngine {
     capture_region: Option<CaptureRect>,
     is_active: bool,
     show_cursor: bool,
+    cursor: CursorCompositor,
+    /// Last cursor bitmap, keyed by its HCURSOR
+    cursor_cache: Option<(isize, CursorImage)>,
     excluded_windows: Vec<WindowIdentifier>,
+    frame_stats: FrameStatsTracker,
 }
 
 impl WindowsGdiCopyCaptureEngine {
@@ -115,7 +123,10 @@ impl WindowsGdiCopyCaptureEngine {
             capture_region: None,
             is_active: false,
             show_cursor: true,
+            cursor: CursorCompositor::default(),
+            cursor_cache: None,
             excluded_windows: Vec::new(),
+            frame_stats: FrameStatsTracker::new(),
         })
     }
 
@@ -136,32 +147,26 @@ impl WindowsGdiCopyCaptureEngine {
 
     fn clip_region(region: &CaptureRect) -> Option<(i32, i32, u32, u32)> {
         let (vx0, vy0, vx1, vy1) = Self::virtual_screen_bounds();
+        let screen = CaptureRect::from_signed(vx0, vy0, vx1 - vx0, vy1 - vy0);
 
-        let x0 = region.x.max(vx0);
-        let y0 = region.y.max(vy0);
-        let x1 = (region.x + region.width as i32).min(vx1);
-        let y1 = (region.y + region.height as i32).min(vy1);
-
-        if x0 >= x1 || y0 >= y1 {
-            return None;
-        }
-
-        Some((x0, y0, (x1 - x0) as u32, (y1 - y0) as u32))
+        let clipped = region.intersect(&screen)?;
+        Some((clipped.x, clipped.y, clipped.width, clipped.height))
     }
 
-    unsafe fn draw_cursor_if_needed(mem_dc: HDC, offset_x: i32, offset_y: i32) {
+    /// Current cursor image and its hotspot in screen coordinates, if one should be drawn
+    unsafe fn current_cursor(&mut self) -> Option<(CursorImage, i32, i32)> {
         let mut ci = CURSORINFO {
             cbSize: std::mem::size_of::<CURSORINFO>() as u32,
             ..Default::default()
         };
 
         if GetCursorInfo(&mut ci).is_err() {
-            return;
+            return None;
         }
 
         // CURSOR_SHOWING = 0x00000001
         if (ci.flags.0 & 0x00000001) == 0 {
-            return;
+            return None;
         }
 
         // Optimization: Only check preview overlap if filtering is enabled
@@ -189,40 +194,119 @@ impl WindowsGdiCopyCaptureEngine {
                         if cursor_x >= px && cursor_x < px + pw &&
                            cursor_y >= py && cursor_y < py + ph {
                             log::debug!("🎯 Cursor filtered (inside preview window)");
-                            return; // Skip drawing cursor over preview window
+                            return None; // Skip drawing cursor over preview window
                         }
                     }
                 }
             }
         }
 
+        // Cursor handles are shared and stable, so the bitmap is only read on change
+        let handle = ci.hCursor.0 as isize;
+        if !matches!(&self.cursor_cache, Some((cached, _)) if *cached == handle) {
+            let image = Self::read_cursor_image(ci.hCursor.into())?;
+            self.cursor_cache = Some((handle, image));
+        }
+        let (_, image) = self.cursor_cache.as_ref()?;
+        Some((image.clone(), ci.ptScreenPos.x, ci.ptScreenPos.y))
+    }
+
+    /// Render `icon` over black and over white to recover color and alpha
+    ///
+    /// Monochrome XOR cursors (the I-beam) have no real alpha; their inverting
+    /// pixels are drawn black with a white outline instead.
+    unsafe fn read_cursor_image(icon: HICON) -> Option<CursorImage> {
         let mut icon_info = ICONINFO::default();
-        if GetIconInfo(ci.hCursor.into(), &mut icon_info).is_err() {
-            return;
+        if GetIconInfo(icon, &mut icon_info).is_err() {
+            return None;
+        }
+
+        let mut mask = BITMAP::default();
+        let got = GetObjectW(
+            icon_info.hbmMask.into(),
+            size_of::<BITMAP>() as i32,
+            Some(&mut mask as *mut BITMAP as *mut std::ffi::c_void),
+        );
+        let width = mask.bmWidth.max(0) as u32;
+        // Monochrome cursors stack the AND and XOR masks in one bitmap
+        let height = if icon_info.hbmColor.is_invalid() {
+            mask.bmHeight.max(0) as u32 / 2
+        } else {
+            mask.bmHeight.max(0) as u32
+        };
+        let hotspot = (icon_info.xHotspot as i32, icon_info.yHotspot as i32);
+
+        if !icon_info.hbmMask.is_invalid() {
+            let _ = DeleteObject(icon_info.hbmMask.into());
+        }
+        if !icon_info.hbmColor.is_invalid() {
+            let _ = DeleteObject(icon_info.hbmColor.into());
+        }
+        if got == 0 || width == 0 || height == 0 {
+            return None;
+        }
+
+        let on_black = Self::render_icon(icon, width, height, 0x00)?;
+        let on_white = Self::render_icon(icon, width, height, 0xFF)?;
+
+        CursorImage::from_renders(&on_black, &on_white, width, height, hotspot).ok()
+    }
+
+    /// Draw `icon` into a `width` x `height` BGRA buffer filled with `background`
+    unsafe fn render_icon(icon: HICON, width: u32, height: u32, background: u8) -> Option<Vec<u8>> {
+        let screen_dc = GetDC(None);
+        if screen_dc.is_invalid() {
+            return None;
+        }
+        let mem_dc = CreateCompatibleDC(Some(screen_dc));
+        let _ = ReleaseDC(None, screen_dc);
+        if mem_dc.is_invalid() {
+            return None;
         }
 
-        // hotspot relative to cursor position
-        let cursor_x = ci.ptScreenPos.x - offset_x - icon_info.xHotspot as i32;
-        let cursor_y = ci.ptScreenPos.y - offset_y - icon_info.yHotspot as i32;
+        let bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+        let bitmap = match CreateDIBSection(Some(mem_dc), &bmi, DIB_RGB_COLORS, &mut bits, None, 0) {
+            Ok(bitmap) if !bits.is_null() => bitmap,
+            _ => {
+                let _ = DeleteDC(mem_dc);
+                return None;
+            }
+        };
 
+        let len = width as usize * height as usize * 4;
+        let old = SelectObject(mem_dc, bitmap.into());
+        std::ptr::write_bytes(bits as *mut u8, background, len);
         let _ = DrawIconEx(
             mem_dc,
-            cursor_x,
-            cursor_y,
-            ci.hCursor.into(),
             0,
             0,
+            icon,
+            width as i32,
+            height as i32,
             0,
             None,
             DI_NORMAL,
         );
+        let _ = GdiFlush();
 
-        if !icon_info.hbmMask.is_invalid() {
-            let _ = DeleteObject(icon_info.hbmMask.into());
-        }
-        if !icon_info.hbmColor.is_invalid() {
-            let _ = DeleteObject(icon_info.hbmColor.into());
-        }
+        let pixels = std::slice::from_raw_parts(bits as *const u8, len).to_vec();
+
+        SelectObject(mem_dc, old);
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(mem_dc);
+        Some(pixels)
     }
 }
 
@@ -232,6 +316,7 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
         self.is_active = true;
         self.show_cursor = show_cursor;
         self.excluded_windows = _excluded_windows.unwrap_or_default();
+        self.frame_stats.reset();
         Ok(())
     }
 
@@ -257,7 +342,7 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
         let (x, y, width, height) = Self::clip_region(region)?;
 
         let row_bytes = (width * 4) as usize;
-        let mut data = vec![0u8; row_bytes * height as usize];
+        let mut data = super::FramePool::global().take(row_bytes * height as usize);
 
         unsafe {
             let screen_dc = GetDC(None);
@@ -313,11 +398,6 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
                 SRCCOPY,
             );
 
-            // Optionally draw cursor on top
-            if self.show_cursor {
-                Self::draw_cursor_if_needed(mem_dc, x, y);
-            }
-
             // Copy DIB memory to Vec<u8>
             if !bits.is_null() {
                 std::ptr::copy_nonoverlapping(bits as *const u8, data.as_mut_ptr(), data.len());
@@ -333,15 +413,35 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
         // Note: Window exclusion is not supported on Windows due to OS limitations
         // See docs/technical/WINDOWS_LIMITATIONS.md for details
 
-        Some(CaptureFrame {
+        let mut frame = CaptureFrame {
             data,
+            pixel_format: super::PixelFormat::Bgra8,
             width,
             height,
             stride: row_bytes as u32,
             offset_x: x,
             offset_y: y,
             gpu_texture: None,
-        })
+            captured_at: std::time::Instant::now(),
+            sequence: 0,
+            dropped_frames: 0,
+            damage: None,
+        };
+
+        // Optionally draw cursor on top
+        if self.show_cursor {
+            if let Some((image, cursor_x, cursor_y)) = unsafe { self.current_cursor() } {
+                let now = frame.captured_at;
+                if let Err(e) = self
+                    .cursor
+                    .draw_screen(&mut frame, &image, cursor_x, cursor_y, now)
+                {
+                    warn!("Failed to draw cursor: {}", e);
+                }
+            }
+        }
+
+        self.frame_stats.deliver(Some(frame))
     }
 
     fn set_cursor_visible(&mut self, visible: bool) -> Result<()> {
@@ -349,6 +449,11 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
         Ok(())
     }
 
+    fn set_cursor_style(&mut self, style: CursorStyle) -> Result<()> {
+        self.cursor.set_style(style);
+        Ok(())
+    }
+
     fn get_region(&self) -> Option<CaptureRect> {
         self.capture_region.clone()
     }
@@ -358,6 +463,10 @@ impl CaptureEngine for WindowsGdiCopyCaptureEngine {
         Ok(())
     }
 
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
@@ -384,7 +493,9 @@ pub struct WindowsCaptureEngine {
     show_cursor: bool,
     current_cursor_state: bool, // Track actual cursor state in session
     gpu_acceleration: bool, // Enable GPU texture passthrough (zero-copy)
+    cpu_frames_required: bool, // Pipeline must see every frame's pixels (redaction, watermark)
     excluded_windows: Vec<WindowIdentifier>,
+    frame_stats: FrameStatsTracker,
 }
 
 impl WindowsCaptureEngine {
@@ -407,7 +518,9 @@ impl WindowsCaptureEngine {
             show_cursor: true,
             current_cursor_state: true,
             gpu_acceleration: false, // TEMPORARILY DISABLED - Different D3D devices cause crash
+            cpu_frames_required: false,
             excluded_windows: Vec::new(),
+            frame_stats: FrameStatsTracker::new(),
         })
     }
 
@@ -522,6 +635,27 @@ impl WindowsCaptureEngine {
         Ok((item, origin, size))
     }
 
+    /// Clip `region` to the captured monitor
+    ///
+    /// Returns the visible part in screen coordinates and the same area in
+    /// texture coordinates (relative to the monitor origin), or None when the
+    /// region is entirely off the monitor.
+    fn clip_to_monitor(&self, region: &CaptureRect) -> Option<(CaptureRect, CaptureRect)> {
+        let monitor = CaptureRect::new(
+            self.monitor_origin.0,
+            self.monitor_origin.1,
+            self.monitor_size.0,
+            self.monitor_size.1,
+        );
+        match region.intersect(&monitor) {
+            Some(clipped) => Some((clipped, clipped.relative_to(&monitor))),
+            None => {
+                warn!("Capture region entirely outside monitor bounds");
+                None
+            }
+        }
+    }
+
     /// Get frame as GPU texture (zero-copy) - preferred for performance
     /// Returns texture handle for GPU-accelerated rendering
     /// Falls back to CPU copy if clicks need to be drawn
@@ -534,31 +668,10 @@ impl WindowsCaptureEngine {
         let mut desc = D3D11_TEXTURE2D_DESC::default();
         unsafe { source_texture.GetDesc(&mut desc) };
 
-        // Calculate monitor bounds in screen coordinates
-        let monitor_left = self.monitor_origin.0;
-        let monitor_top = self.monitor_origin.1;
-        let monitor_right = monitor_left + self.monitor_size.0 as i32;
-        let monitor_bottom = monitor_top + self.monitor_size.1 as i32;
-
-        // Clip region to monitor bounds
-        let clipped_left = region.x.max(monitor_left);
-        let clipped_top = region.y.max(monitor_top);
-        let clipped_right = (region.x + region.width as i32).min(monitor_right);
-        let clipped_bottom = (region.y + region.height as i32).min(monitor_bottom);
-
-        // Check if there's any visible region
-        if clipped_left >= clipped_right || clipped_top >= clipped_bottom {
-            warn!("Capture region entirely outside monitor bounds");
-            return None;
-        }
-
-        // Calculate clipped dimensions
-        let clipped_width = (clipped_right - clipped_left) as u32;
-        let clipped_height = (clipped_bottom - clipped_top) as u32;
-
-        // Calculate source position in texture coordinates (relative to monitor origin)
-        let src_x = (clipped_left - monitor_left) as i32;
-        let src_y = (clipped_top - monitor_top) as i32;
+        let (clipped, texture_crop) = self.clip_to_monitor(region)?;
+        let (clipped_left, clipped_top) = (clipped.x, clipped.y);
+        let (clipped_width, clipped_height) = (clipped.width, clipped.height);
+        let (src_x, src_y) = (texture_crop.x, texture_crop.y);
 
         // Clone texture COM pointer for safe cross-thread usage
         // SAFETY: Clone increments reference count (AddRef)
@@ -576,6 +689,7 @@ impl WindowsCaptureEngine {
 
         Some(CaptureFrame {
             data: Vec::new(), // No CPU data for GPU path
+            pixel_format: super::PixelFormat::Bgra8,
             width: clipped_width,
             height: clipped_height,
             stride: clipped_width * 4, // BGRA format
@@ -589,6 +703,10 @@ impl WindowsCaptureEngine {
                 crop_width: clipped_width,
                 crop_height: clipped_height,
             }),
+            captured_at: std::time::Instant::now(),
+            sequence: 0,
+            dropped_frames: 0,
+            damage: None,
         })
     }
 
@@ -603,28 +721,9 @@ impl WindowsCaptureEngine {
         let d3d_device = self.d3d_device.as_ref()?;
         let d3d_context = self.d3d_context.as_ref()?;
 
-        // Calculate monitor bounds in screen coordinates
-        let monitor_left = self.monitor_origin.0;
-        let monitor_top = self.monitor_origin.1;
-        let monitor_right = monitor_left + self.monitor_size.0 as i32;
-        let monitor_bottom = monitor_top + self.monitor_size.1 as i32;
-
-        // Clip region to monitor bounds
-        let clipped_left = region.x.max(monitor_left);
-        let clipped_top = region.y.max(monitor_top);
-        let clipped_right = (region.x + region.width as i32).min(monitor_right);
-        let clipped_bottom = (region.y + region.height as i32).min(monitor_bottom);
-
-        // Check if there's any visible region
-        if clipped_left >= clipped_right || clipped_top >= clipped_bottom {
-            // Entire region is outside monitor - return empty/minimal frame
-            warn!("Capture region entirely outside monitor bounds");
-            return None;
-        }
-
-        // Calculate clipped dimensions
-        let clipped_width = (clipped_right - clipped_left) as u32;
-        let clipped_height = (clipped_bottom - clipped_top) as u32;
+        let (clipped, texture_crop) = self.clip_to_monitor(region)?;
+        let (clipped_left, clipped_top) = (clipped.x, clipped.y);
+        let (clipped_width, clipped_height) = (clipped.width, clipped.height);
 
         // Log once per 60 frames
         static COPY_COUNT: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
@@ -666,9 +765,9 @@ impl WindowsCaptureEngine {
 
         let staging_texture = staging_texture?;
 
-        // Calculate source position in texture coordinates (relative to monitor origin)
-        let src_x = (clipped_left - monitor_left) as u32;
-        let src_y = (clipped_top - monitor_top) as u32;
+        // Source position in texture coordinates (relative to monitor origin)
+        let src_x = texture_crop.x as u32;
+        let src_y = texture_crop.y as u32;
 
         // Copy region from source to staging
         let src_box = D3D11_BOX {
@@ -709,7 +808,7 @@ impl WindowsCaptureEngine {
         let row_bytes = (clipped_width * 4) as usize; // 4 bytes per pixel (BGRA)
 
         // Copy row by row, removing stride padding
-        let mut data = vec![0u8; row_bytes * clipped_height as usize];
+        let mut data = super::FramePool::global().take(row_bytes * clipped_height as usize);
 
         unsafe {
             let src_ptr = mapped.pData as *const u8;
@@ -726,12 +825,17 @@ impl WindowsCaptureEngine {
 
         Some(CaptureFrame {
             data,
+            pixel_format: super::PixelFormat::Bgra8,
             width: clipped_width,
             height: clipped_height,
             stride: row_bytes as u32,
             offset_x: clipped_left,
             offset_y: clipped_top,
             gpu_texture: None,
+            captured_at: std::time::Instant::now(),
+            sequence: 0,
+            dropped_frames: 0,
+            damage: None,
         })
     }
 }
@@ -815,6 +919,7 @@ impl CaptureEngine for WindowsCaptureEngine {
         self.monitor_size = monitor_size;
         self.show_cursor = show_cursor;
         self.current_cursor_state = show_cursor;
+        self.frame_stats.reset();
         self.is_active = true;
         // excluded_windows already stored above, no need to set again
 
@@ -878,7 +983,7 @@ impl CaptureEngine for WindowsCaptureEngine {
         let region = self.capture_region.as_ref()?;
 
         // Try to get frame from pool (non-blocking)
-        let frame = match frame_pool.TryGetNextFrame() {
+        let mut frame = match frame_pool.TryGetNextFrame() {
             Ok(f) => {
                 //debug!("Got frame from pool!");
                 f
@@ -894,6 +999,14 @@ impl CaptureEngine for WindowsCaptureEngine {
             }
         };
 
+        // Frames queued behind this one arrived while we were busy; skip to the newest
+        // so latency doesn't build up, and count the rest as dropped
+        while let Ok(newer) = frame_pool.TryGetNextFrame() {
+            let _ = frame.Close();
+            frame = newer;
+            self.frame_stats.record_dropped(1);
+        }
+
         // Get surface from frame
         let surface = match frame.Surface() {
             Ok(s) => s,
@@ -925,19 +1038,24 @@ impl CaptureEngine for WindowsCaptureEngine {
         // Choose GPU or CPU path based on gpu_acceleration setting
         // GPU path: Return texture handle for zero-copy rendering (fast)
         // CPU path: Copy to system memory for compatibility (slower)
-        // If GPU path is enabled but we need to apply exclusions, fall back to CPU path
-        if self.gpu_acceleration && self.excluded_windows.is_empty() {
+        // If GPU path is enabled but we need to apply exclusions or the pipeline
+        // needs the pixels, fall back to CPU path
+        if self.gpu_acceleration
+            && self.excluded_windows.is_empty()
+            && !self.cpu_frames_required
+        {
             // Try GPU path first
             let result = self.get_frame_gpu(&texture, region);
             if result.is_some() {
-                return result;
+                return self.frame_stats.deliver(result);
             }
             // Fallback to CPU if GPU path fails
             warn!("GPU path failed, falling back to CPU");
         }
 
         // CPU fallback path
-        self.copy_frame_to_cpu(&texture, region)
+        let result = self.copy_frame_to_cpu(&texture, region);
+        self.frame_stats.deliver(result)
     }
 
     fn set_cursor_visible(&mut self, visible: bool) -> Result<()> {
@@ -948,6 +1066,10 @@ impl CaptureEngine for WindowsCaptureEngine {
         Ok(())
     }
 
+    fn set_cpu_frames_required(&mut self, required: bool) {
+        self.cpu_frames_required = required;
+    }
+
     fn get_region(&self) -> Option<CaptureRect> {
         self.capture_region.clone()
     }
@@ -960,6 +1082,10 @@ impl CaptureEngine for WindowsCaptureEngine {
         Ok(())
     }
 
+    fn stats(&self) -> CaptureStats {
+        self.frame_stats.stats()
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
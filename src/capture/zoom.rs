@@ -0,0 +1,392 @@
+// capture/zoom.rs - Follow-Cursor Zoom
+//
+// Magnifies part of the captured region without moving the border: each frame
+// is replaced by a sub-rectangle around the pointer, scaled back up to the
+// frame's own size. The view only pans once the pointer leaves a dead zone in
+// its middle, eases towards the pointer instead of jumping, and zoom level
+// changes animate over a short transition.
+
+use super::pipeline::{replace_frame, FrameProcessor};
+use super::{CaptureFrame, FrameScaler, ScaleFilter};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Zoom behavior, usually from the `zoom` settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ZoomSettings {
+    /// Magnifications stepped through by zoom in/out; 1.0 is unzoomed
+    pub levels: Vec<f32>,
+    /// Time constant of the view catching up with the pointer; 0 follows instantly
+    pub smoothing_ms: u64,
+    /// Share of the view, from its center to its edge, the pointer can roam without panning
+    pub dead_zone: f32,
+    /// Length of the animation between zoom levels
+    pub transition_ms: u64,
+    pub filter: ScaleFilter,
+}
+
+impl ZoomSettings {
+    /// Whether any configured level magnifies at all
+    pub fn is_enabled(&self) -> bool {
+        self.levels.iter().any(|&level| level > 1.0 + f32::EPSILON)
+    }
+}
+
+impl Default for ZoomSettings {
+    fn default() -> Self {
+        Self {
+            levels: vec![1.0, 1.5, 2.0, 3.0, 4.0],
+            smoothing_ms: 150,
+            dead_zone: 0.5,
+            transition_ms: 250,
+            filter: ScaleFilter::Bilinear,
+        }
+    }
+}
+
+/// Zoom level and view position, shared between the pipeline and the controls
+#[derive(Debug, Clone)]
+pub struct FollowZoom {
+    settings: ZoomSettings,
+    from_level: f32,
+    to_level: f32,
+    transition_start: Instant,
+    /// View center in frame pixels, once a frame has been seen
+    center: Option<(f64, f64)>,
+    last_update: Option<Instant>,
+}
+
+/// Handle for changing the zoom from outside the pipeline (shortcuts, commands)
+pub type ZoomControl = Arc<Mutex<FollowZoom>>;
+
+impl Default for FollowZoom {
+    fn default() -> Self {
+        Self::new(ZoomSettings::default())
+    }
+}
+
+impl FollowZoom {
+    pub fn new(settings: ZoomSettings) -> Self {
+        Self {
+            settings,
+            from_level: 1.0,
+            to_level: 1.0,
+            transition_start: Instant::now(),
+            center: None,
+            last_update: None,
+        }
+    }
+
+    pub fn settings(&self) -> &ZoomSettings {
+        &self.settings
+    }
+
+    /// Level being animated towards
+    pub fn target_level(&self) -> f32 {
+        self.to_level
+    }
+
+    /// Magnification at `now`, eased between the previous and the target level
+    pub fn level(&self, now: Instant) -> f32 {
+        let duration = Duration::from_millis(self.settings.transition_ms);
+        let elapsed = now.saturating_duration_since(self.transition_start);
+        if duration.is_zero() || elapsed >= duration {
+            return self.to_level;
+        }
+        let t = elapsed.as_secs_f32() / duration.as_secs_f32();
+        let eased = t * t * (3.0 - 2.0 * t);
+        self.from_level + (self.to_level - self.from_level) * eased
+    }
+
+    /// Whether frames are currently magnified (or animating out of it)
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.level(now) > 1.0 + f32::EPSILON
+    }
+
+    /// Animate to `level`, clamped to 1.0..=the highest configured level
+    pub fn set_level(&mut self, level: f32, now: Instant) {
+        let max = self.settings.levels.iter().copied().fold(1.0f32, f32::max);
+        let level = if level.is_finite() {
+            level.clamp(1.0, max)
+        } else {
+            1.0
+        };
+        if !self.is_active(now) {
+            // Forget the old view so the next zoom starts at the pointer
+            self.center = None;
+        }
+        self.from_level = self.level(now);
+        self.to_level = level;
+        self.transition_start = now;
+    }
+
+    /// Step to the next configured level above the target; returns the new target
+    pub fn zoom_in(&mut self, now: Instant) -> f32 {
+        let current = self.to_level;
+        let next = self
+            .settings
+            .levels
+            .iter()
+            .copied()
+            .filter(|&level| level > current + f32::EPSILON)
+            .fold(f32::INFINITY, f32::min);
+        if next.is_finite() {
+            self.set_level(next, now);
+        }
+        self.to_level
+    }
+
+    /// Step to the next configured level below the target; returns the new target
+    pub fn zoom_out(&mut self, now: Instant) -> f32 {
+        let current = self.to_level;
+        let next = self
+            .settings
+            .levels
+            .iter()
+            .copied()
+            .filter(|&level| level < current - f32::EPSILON)
+            .fold(1.0f32, f32::max);
+        self.set_level(next, now);
+        self.to_level
+    }
+
+    /// Area of a `width` x `height` frame to show at `now`, as (x, y, width, height)
+    ///
+    /// `pointer` is in frame pixels; without one the view holds still.
+    pub fn view(
+        &mut self,
+        (width, height): (u32, u32),
+        pointer: Option<(f64, f64)>,
+        now: Instant,
+    ) -> (f64, f64, f64, f64) {
+        let (width, height) = (width as f64, height as f64);
+        let level = self.level(now).max(1.0) as f64;
+        let (view_w, view_h) = (width / level, height / level);
+
+        let (mut cx, mut cy) = self
+            .center
+            .or(pointer)
+            .unwrap_or((width / 2.0, height / 2.0));
+        if let Some((px, py)) = pointer {
+            let target_x = follow(cx, px, view_w / 2.0 * self.settings.dead_zone as f64);
+            let target_y = follow(cy, py, view_h / 2.0 * self.settings.dead_zone as f64);
+
+            let dt = self
+                .last_update
+                .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+            let alpha = if self.settings.smoothing_ms == 0 || self.center.is_none() {
+                1.0
+            } else {
+                1.0 - (-dt.as_secs_f64() * 1000.0 / self.settings.smoothing_ms as f64).exp()
+            };
+            cx += (target_x - cx) * alpha;
+            cy += (target_y - cy) * alpha;
+        }
+
+        // Keep the view inside the frame
+        cx = cx.clamp(view_w / 2.0, width - view_w / 2.0);
+        cy = cy.clamp(view_h / 2.0, height - view_h / 2.0);
+        self.center = Some((cx, cy));
+        self.last_update = Some(now);
+
+        (cx - view_w / 2.0, cy - view_h / 2.0, view_w, view_h)
+    }
+}
+
+/// Where the view center should be so `pointer` sits inside the dead zone around it
+fn follow(center: f64, pointer: f64, dead_zone: f64) -> f64 {
+    if pointer > center + dead_zone {
+        pointer - dead_zone
+    } else if pointer < center - dead_zone {
+        pointer + dead_zone
+    } else {
+        center
+    }
+}
+
+/// Source of the pointer position for a frame, in that frame's pixels
+pub type PointerSource = Box<dyn FnMut(&CaptureFrame) -> Option<(f64, f64)> + Send>;
+
+/// Pipeline stage replacing each frame with its magnified view
+pub struct ZoomProcessor {
+    zoom: ZoomControl,
+    pointer: PointerSource,
+}
+
+impl ZoomProcessor {
+    pub fn new(zoom: ZoomControl, pointer: PointerSource) -> Self {
+        Self { zoom, pointer }
+    }
+
+    pub fn control(&self) -> ZoomControl {
+        self.zoom.clone()
+    }
+}
+
+impl FrameProcessor for ZoomProcessor {
+    fn name(&self) -> &str {
+        "zoom"
+    }
+
+    fn is_active(&self, _frame: &CaptureFrame) -> bool {
+        self.zoom
+            .lock()
+            .map_or(true, |zoom| zoom.is_active(Instant::now()))
+    }
+
+    fn process(&mut self, frame: &mut CaptureFrame) -> Result<()> {
+        let now = Instant::now();
+        let (view, filter) = {
+            let mut zoom = self
+                .zoom
+                .lock()
+                .map_err(|_| anyhow!("Zoom state lock poisoned"))?;
+            if !zoom.is_active(now) {
+                return Ok(());
+            }
+            let pointer = (self.pointer)(frame);
+            (
+                zoom.view((frame.width, frame.height), pointer, now),
+                zoom.settings.filter,
+            )
+        };
+
+        let zoomed = FrameScaler::new(frame.width, frame.height)
+            .with_filter(filter)
+            .scale_area(frame, view)?;
+        replace_frame(frame, zoomed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instant_zoom() -> FollowZoom {
+        FollowZoom::new(ZoomSettings {
+            smoothing_ms: 0,
+            transition_ms: 0,
+            ..ZoomSettings::default()
+        })
+    }
+
+    #[test]
+    fn steps_through_levels() {
+        let now = Instant::now();
+        let mut zoom = instant_zoom();
+        assert_eq!(zoom.zoom_in(now), 1.5);
+        assert_eq!(zoom.zoom_in(now), 2.0);
+        assert_eq!(zoom.zoom_out(now), 1.5);
+        zoom.set_level(10.0, now);
+        assert_eq!(zoom.target_level(), 4.0);
+        assert_eq!(zoom.zoom_in(now), 4.0);
+        zoom.set_level(0.2, now);
+        assert!(!zoom.is_active(now));
+    }
+
+    #[test]
+    fn level_changes_are_animated() {
+        let start = Instant::now();
+        let mut zoom = FollowZoom::new(ZoomSettings {
+            transition_ms: 200,
+            ..ZoomSettings::default()
+        });
+        zoom.set_level(3.0, start);
+        assert_eq!(zoom.level(start), 1.0);
+        assert_eq!(zoom.level(start + Duration::from_millis(100)), 2.0);
+        assert_eq!(zoom.level(start + Duration::from_millis(200)), 3.0);
+    }
+
+    #[test]
+    fn view_stays_put_inside_dead_zone() {
+        let now = Instant::now();
+        let mut zoom = instant_zoom();
+        zoom.set_level(2.0, now);
+
+        // 100x100 view centered on the first pointer position
+        assert_eq!(
+            zoom.view((200, 200), Some((100.0, 100.0)), now),
+            (50.0, 50.0, 100.0, 100.0)
+        );
+        // Dead zone is 25 px either side of the center
+        assert_eq!(
+            zoom.view((200, 200), Some((120.0, 80.0)), now),
+            (50.0, 50.0, 100.0, 100.0)
+        );
+        // Past it the view pans just enough to keep the pointer at its edge
+        assert_eq!(
+            zoom.view((200, 200), Some((135.0, 100.0)), now),
+            (60.0, 50.0, 100.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn view_is_clamped_to_frame() {
+        let now = Instant::now();
+        let mut zoom = instant_zoom();
+        zoom.set_level(4.0, now);
+        assert_eq!(
+            zoom.view((400, 200), Some((0.0, 200.0)), now),
+            (0.0, 150.0, 100.0, 50.0)
+        );
+    }
+
+    #[test]
+    fn smoothing_eases_towards_pointer() {
+        let start = Instant::now();
+        let mut zoom = FollowZoom::new(ZoomSettings {
+            smoothing_ms: 100,
+            dead_zone: 0.0,
+            transition_ms: 0,
+            ..ZoomSettings::default()
+        });
+        zoom.set_level(2.0, start);
+        zoom.view((400, 400), Some((200.0, 200.0)), start);
+
+        let (x, _, w, _) = zoom.view(
+            (400, 400),
+            Some((260.0, 200.0)),
+            start + Duration::from_millis(100),
+        );
+        // One time constant covers ~63% of the 60 px jump
+        let center = x + w / 2.0;
+        assert!((center - 237.9).abs() < 0.5, "center {}", center);
+    }
+
+    #[test]
+    fn processor_magnifies_around_pointer() {
+        // Left half black, right half white
+        let data = (0..8)
+            .flat_map(|_| (0..8).flat_map(|x| if x < 4 { [0; 4] } else { [255; 4] }))
+            .collect();
+        let mut frame = CaptureFrame::new(data, 8, 8).with_offset(10, 20);
+        frame.sequence = 3;
+
+        let zoom: ZoomControl = Arc::new(Mutex::new(FollowZoom::new(ZoomSettings {
+            smoothing_ms: 0,
+            transition_ms: 0,
+            filter: ScaleFilter::Nearest,
+            ..ZoomSettings::default()
+        })));
+        let mut processor = ZoomProcessor::new(zoom.clone(), Box::new(|_| Some((7.0, 4.0))));
+
+        // Unzoomed frames pass through untouched
+        let before = frame.data.clone();
+        processor.process(&mut frame).unwrap();
+        assert_eq!(frame.data, before);
+
+        zoom.lock().unwrap().set_level(2.0, Instant::now());
+        processor.process(&mut frame).unwrap();
+        assert_eq!((frame.width, frame.height), (8, 8));
+        assert_eq!(
+            (frame.offset_x, frame.offset_y, frame.sequence),
+            (10, 20, 3)
+        );
+        // The view is the white right half
+        assert!(frame.data.iter().all(|&v| v == 255));
+    }
+}
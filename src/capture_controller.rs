@@ -2,19 +2,32 @@ use std::sync::Arc;
 use tauri::State;
 
 use crate::capture_deps::{CapturePlatform, RealCapturePlatform};
+use crate::monitors::MonitorInfo;
 use crate::{hollow_border, platform::services, profiles, settings_io, AppState};
 use crate::settings::Settings;
 #[cfg(windows)]
 use crate::settings::CaptureMethod;
 
-use rustframe_capture::capture::CaptureRect;
+use std::sync::mpsc::RecvTimeoutError;
+
+use rustframe_capture::window_filter::WindowIdentifier;
+use rustframe_capture::window_follow::{FollowEvent, WindowFollower};
+use rustframe_capture::capture::{
+    CaptureEngine, CaptureFrame, CaptureRect, ClickButton, ClickHighlightProcessor,
+    ClickHighlighter, EngineFactory, FollowZoom, FramePacer, FramePipeline, FramePool, FramePump,
+    FrameScaler, FrameSink, IdleDetector, MetricsSnapshot, MultiRegionCaptureEngine,
+    OverlayProcessor, RedactionProcessor, SpanningCaptureEngine, ZoomControl, ZoomProcessor,
+};
 
 // Platform-specific engine creation moved to platform::services
 
-#[derive(Clone, Copy)]
 struct CaptureStartOptions {
     spawn_render_thread: bool,
     from_shortcut: bool,
+    /// Keep the region on this window instead of the fixed start rect
+    follow: Option<WindowFollower>,
+    /// Fixed screen regions composed next to the main one
+    extra_regions: Vec<CaptureRect>,
 }
 
 impl Default for CaptureStartOptions {
@@ -22,10 +35,15 @@ impl Default for CaptureStartOptions {
         Self {
             spawn_render_thread: true,
             from_shortcut: false,
+            follow: None,
+            extra_regions: Vec::new(),
         }
     }
 }
 
+/// How often a followed window's bounds are looked up
+const WINDOW_FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
 fn start_capture_with_platform(
     x: i32,
     y: i32,
@@ -121,6 +139,21 @@ fn start_capture_with_platform(
         "Capture settings loaded"
     );
 
+    // A watermark that can't be drawn at all stops capture rather than being dropped
+    let watermark = if settings.watermark.enabled {
+        let overlay = OverlayProcessor::new(settings.watermark.clone())
+            .map_err(|e| format!("Watermark: {:#}", e))?;
+        Some(overlay)
+    } else {
+        None
+    };
+
+    // Window-follow capture puts the border around the window's current bounds
+    let (x, y, width, height) = match options.follow.as_ref().and_then(|f| f.bounds()) {
+        Some(bounds) => border_rect_around(bounds, settings.border_width as i32),
+        None => (x, y, width, height),
+    };
+
     // Create hollow border
     log::info!("[MAIN] Creating hollow border...");
     platform.create_and_store_hollow_border(x, y, width, height, &settings)?;
@@ -166,17 +199,19 @@ fn start_capture_with_platform(
         capture_method = ?settings.capture_method,
         "Creating new capture engine"
     );
-    *engine_lock = Some(platform.create_capture_engine_for_settings(&settings)?);
+    let monitors = state.monitors.lock().unwrap().clone();
+    *engine_lock = Some(create_capture_engine(
+        &platform,
+        &settings,
+        &options.extra_regions,
+        &monitors,
+    )?);
 
     if let Some(ref mut engine) = *engine_lock {
         // Offset capture region inward by border_width to exclude border from capture
-        let border_offset = settings.border_width as i32;
-        let region = CaptureRect {
-            x: x + border_offset,
-            y: y + border_offset,
-            width: (width as i32 - border_offset * 2).max(1) as u32,
-            height: (height as i32 - border_offset * 2).max(1) as u32,
-        };
+        let region = CaptureRect::new(x, y, width, height)
+            .inset(settings.border_width as i32)
+            .at_least(1, 1);
 
         // Note: Separation layer is already hidden from screen sharing via NSWindowSharingNone
         // No need to explicitly exclude it from capture
@@ -188,6 +223,9 @@ fn start_capture_with_platform(
             exclusion_list.len()
         );
         let exclusion_list = exclusion_list.clone();
+        if let Err(e) = engine.set_cursor_style(settings.cursor_style.clone()) {
+            tracing::warn!(error = %e, "Failed to apply cursor style");
+        }
         let start_result = engine.start(region, settings.show_cursor, Some(exclusion_list.clone()));
         if let Err(e) = start_result {
             tracing::error!(error = %e, "Capture engine start failed");
@@ -200,6 +238,11 @@ fn start_capture_with_platform(
                     *engine_lock =
                         Some(platform.create_capture_engine_for_settings(&fallback_settings)?);
                     if let Some(ref mut fallback_engine) = *engine_lock {
+                        if let Err(e) =
+                            fallback_engine.set_cursor_style(settings.cursor_style.clone())
+                        {
+                            tracing::warn!(error = %e, "Failed to apply cursor style");
+                        }
                         fallback_engine
                             .start(region, settings.show_cursor, Some(exclusion_list))
                             .map_err(|e| {
@@ -257,16 +300,51 @@ fn start_capture_with_platform(
     let settings_clone = state.settings.clone(); // Clone settings for GPU check
     let stop_flag = state.render_thread_stop.clone();
     let target_fps = settings.target_fps;
-    let capture_clicks_enabled = settings.capture_clicks;
-    let click_color = settings.click_highlight_color;
-    let click_dissolve_ms = settings.click_dissolve_ms as u64;
-    let click_radius = settings.click_highlight_radius;
+    let pacing = settings.frame_pacing.clone();
+    let idle_settings = settings.idle_detection.clone();
+    // Stage timings start over with every capture; 0 disables the periodic log line
+    state.metrics.lock().unwrap().reset();
+    let metrics = state.metrics.clone();
+    let metrics_log_interval = (settings.metrics_log_interval_secs > 0)
+        .then(|| std::time::Duration::from_secs(settings.metrics_log_interval_secs as u64));
+    // Drawn into CPU frames by the pipeline; GPU renderers get it to overlay their own
+    let click_highlighter = settings
+        .capture_clicks
+        .then(|| click_highlighter_from_settings(&settings));
+    // Every capture starts unzoomed with the levels from the current settings
+    *state.zoom.lock().unwrap() = FollowZoom::new(settings.zoom.clone());
+    let pipeline = build_frame_pipeline(
+        &settings,
+        click_highlighter.clone(),
+        state.zoom.clone(),
+        watermark,
+    );
+    // Redaction and watermarks must never be bypassed by a zero-copy texture
+    if let Some(engine) = state.capture_engine.lock().unwrap().as_mut() {
+        engine.set_cpu_frames_required(pipeline.requires_cpu_frames());
+    }
 
     if options.spawn_render_thread {
         let platform_for_thread = Arc::clone(&platform);
+        let mut follower = options.follow;
+        let border_w = settings.border_width as i32;
         let render_handle = std::thread::spawn(move || {
             log::info!("Frame rendering thread started");
-            let frame_duration = std::time::Duration::from_millis(1000 / target_fps as u64);
+            let frame_duration = FramePacer::interval_for_fps(target_fps);
+
+            // Frames are pushed from a pump thread, so this loop never holds the
+            // engine lock and border callbacks can update the region at any time.
+            // A depth of one keeps only the freshest frame for the preview.
+            let (sink, frames) = FrameSink::channel(1);
+            let pump = FramePump::start(engine_clone, sink, frame_duration);
+            pump.set_pacing(pacing);
+            pump.set_metrics(metrics.clone());
+            // Processing runs on the pump thread, before any sink sees the frame
+            pump.set_pipeline(pipeline);
+            let mut last_follow_poll = std::time::Instant::now();
+            let mut last_metrics_log = std::time::Instant::now();
+            let mut idle = IdleDetector::new(idle_settings);
+            let mut last_pointer = None;
 
             loop {
                 // Check stop flag
@@ -274,56 +352,79 @@ fn start_capture_with_platform(
                     break;
                 }
 
-                let frame_start = std::time::Instant::now();
+                if let Some(follower) = follower.as_mut() {
+                    if last_follow_poll.elapsed() >= WINDOW_FOLLOW_POLL_INTERVAL {
+                        last_follow_poll = std::time::Instant::now();
+                        let platform = platform_for_thread.as_ref();
+                        apply_window_follow(follower, &pump, platform, border_w);
+                    }
+                }
 
                 // User requested higher update frequency during drag/resize.
                 // We keep capturing during interaction and bump FPS below.
                 let is_interacting = hollow_border::is_border_interacting();
 
-                // Get frame from capture engine
-                let frame = {
-                    let mut engine = engine_clone.lock().unwrap();
-                    if let Some(ref mut eng) = *engine {
-                        eng.get_frame()
-                    } else {
-                        None
+                // Pointer motion and clicks wake idle capture, so highlights, the
+                // cursor and zoom panning never wait for the idle rate
+                if idle.settings().enabled {
+                    let pointer = crate::platform::input::pointer_position();
+                    if pointer != last_pointer {
+                        last_pointer = pointer;
+                        idle.note_input(std::time::Instant::now());
                     }
-                };
-
-                // Render frame to destination window (use try_lock to avoid blocking)
-                if let Some(frame) = frame {
-                    // Check if GPU acceleration is available and enabled
-                    let gpu_enabled = settings_clone.lock().unwrap().gpu_acceleration;
-                    let use_gpu = gpu_enabled && frame.gpu_texture.is_some();
-
-                    platform_for_thread.render_frame_to_destination_if_available(
-                        is_interacting,
-                        frame,
-                        use_gpu,
-                        capture_clicks_enabled,
-                        click_color,
-                        click_dissolve_ms,
-                        click_radius,
-                    );
-                } // DESTINATION_WINDOW lock released here
-
-                // Frame rate limiting
-                let elapsed = frame_start.elapsed();
+                    if let Some(clicked) = crate::platform::input::last_click_time() {
+                        idle.note_input(clicked);
+                    }
+                }
 
                 // During border interaction (drag/resize), use faster update rate for Meet sync
-                let is_interacting_for_fps = hollow_border::is_border_interacting();
-
-                let min_frame_duration = if is_interacting_for_fps {
+                pump.set_interval(if is_interacting {
                     // 5ms during interaction = ~200 FPS max for smooth Meet updates
                     std::time::Duration::from_millis(5)
                 } else {
-                    frame_duration
+                    // Static content drops to the idle rate until something changes
+                    idle.interval(frame_duration, std::time::Instant::now())
+                });
+
+                // Wait for the next frame from the capture engine
+                let frame = match frames.recv_timeout(frame_duration) {
+                    Ok(frame) => frame,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
                 };
 
-                if elapsed < min_frame_duration {
-                    std::thread::sleep(min_frame_duration - elapsed);
+                // Unchanged frames are skipped, apart from an occasional refresh
+                if !idle.observe(&frame, std::time::Instant::now()) && !is_interacting {
+                    metrics.lock().unwrap().record_unchanged();
+                    FramePool::global().recycle(frame.data);
+                    if let Some(texture) = frame.gpu_texture {
+                        texture.release();
+                    }
+                    continue;
+                }
+
+                // Check if GPU acceleration is available and enabled
+                let gpu_enabled = settings_clone.lock().unwrap().gpu_acceleration;
+                let use_gpu = gpu_enabled && frame.gpu_texture.is_some();
+
+                let timing = platform_for_thread.render_frame_to_destination_if_available(
+                    is_interacting,
+                    frame,
+                    use_gpu,
+                    click_highlighter.as_ref(),
+                );
+                let mut metrics = metrics.lock().unwrap();
+                metrics.record_render(timing);
+                metrics.set_queue_dropped(frames.dropped());
+                if let Some(interval) = metrics_log_interval {
+                    if last_metrics_log.elapsed() >= interval {
+                        last_metrics_log = std::time::Instant::now();
+                        tracing::info!("Capture metrics: {}", metrics.snapshot());
+                    }
                 }
             }
+
+            pump.stop();
         });
 
         *state.render_thread_handle.lock().unwrap() = Some(render_handle);
@@ -335,6 +436,169 @@ fn start_capture_with_platform(
     Ok(())
 }
 
+/// Engine for the main region, composed with one engine per extra region
+///
+/// With `monitor_span` enabled and monitors known, the main region is stitched
+/// from one engine per monitor it covers.
+fn create_capture_engine(
+    platform: &Arc<dyn CapturePlatform>,
+    settings: &Settings,
+    extra_regions: &[CaptureRect],
+    monitors: &[MonitorInfo],
+) -> Result<Box<dyn CaptureEngine>, String> {
+    let span = settings.monitor_span.enabled && !monitors.is_empty();
+    let engine: Box<dyn CaptureEngine> = if span {
+        tracing::info!(monitors = monitors.len(), "Capturing across monitors");
+        let platform = Arc::clone(platform);
+        let engine_settings = settings.clone();
+        let factory: EngineFactory = Box::new(move || {
+            platform
+                .create_capture_engine_for_settings(&engine_settings)
+                .map_err(anyhow::Error::msg)
+        });
+        Box::new(SpanningCaptureEngine::new(
+            monitors.iter().map(MonitorInfo::span_monitor).collect(),
+            &settings.monitor_span,
+            factory,
+        ))
+    } else {
+        if settings.monitor_span.enabled {
+            tracing::warn!("Monitor spanning enabled but no monitors are known");
+        }
+        platform.create_capture_engine_for_settings(settings)?
+    };
+    if extra_regions.is_empty() {
+        return Ok(engine);
+    }
+
+    tracing::info!(
+        regions = extra_regions.len() + 1,
+        layout = ?settings.composition.layout,
+        "Composing capture regions"
+    );
+    let mut composed = MultiRegionCaptureEngine::new(engine, settings.composition.clone());
+    for region in extra_regions {
+        let engine = platform.create_capture_engine_for_settings(settings)?;
+        composed = composed.with_region(engine, *region);
+    }
+    Ok(Box::new(composed))
+}
+
+/// Border window rect that places the capture region exactly on `bounds`
+fn border_rect_around(bounds: CaptureRect, border_w: i32) -> (i32, i32, u32, u32) {
+    let outer = bounds.inset(-border_w);
+    (outer.x, outer.y, outer.width, outer.height)
+}
+
+/// Move the border and capture region with a followed window; pause while it's hidden
+fn apply_window_follow(
+    follower: &mut WindowFollower,
+    pump: &FramePump,
+    platform: &dyn CapturePlatform,
+    border_w: i32,
+) {
+    let event = match follower.poll() {
+        Ok(event) => event,
+        Err(e) => {
+            tracing::warn!(error = %e, "Followed window lookup failed");
+            return;
+        }
+    };
+
+    match event {
+        FollowEvent::Unchanged => {}
+        FollowEvent::Moved(bounds) | FollowEvent::Restored(bounds) => {
+            tracing::debug!(?bounds, "Followed window changed");
+            let (x, y, width, height) = border_rect_around(bounds, border_w);
+            platform.move_capture_border(x, y, width as i32, height as i32, border_w);
+            pump.request_region(bounds);
+            if matches!(event, FollowEvent::Restored(_)) {
+                tracing::info!("Followed window is back, resuming capture");
+                platform.set_capture_border_visible(true);
+                pump.set_paused(false);
+            }
+        }
+        FollowEvent::Minimized | FollowEvent::Lost => {
+            tracing::info!(event = ?event, "Followed window hidden, pausing capture");
+            platform.set_capture_border_visible(false);
+            pump.set_paused(true);
+        }
+    }
+}
+
+/// Click highlight appearance from the `click_highlight_*` settings, in physical pixels
+fn click_highlighter_from_settings(settings: &Settings) -> ClickHighlighter {
+    let display = rustframe_capture::display_info::get();
+    let radius = display.points_to_pixels(settings.click_highlight_radius as f64);
+    let color = settings.click_highlight_color;
+    ClickHighlighter::new(
+        color,
+        radius as f32,
+        std::time::Duration::from_millis(settings.click_dissolve_ms as u64),
+    )
+    .with_style(settings.click_highlight_style)
+    .with_button_color(
+        ClickButton::Right,
+        settings.click_highlight_right_color.unwrap_or(color),
+    )
+    .with_button_color(
+        ClickButton::Middle,
+        settings.click_highlight_middle_color.unwrap_or(color),
+    )
+}
+
+/// Redaction, click highlights, the processing stages configured in settings,
+/// the fixed output scaler, then the watermark
+fn build_frame_pipeline(
+    settings: &Settings,
+    clicks: Option<ClickHighlighter>,
+    zoom: ZoomControl,
+    watermark: Option<OverlayProcessor>,
+) -> FramePipeline {
+    let mut pipeline = FramePipeline::new();
+    // Zones are region-relative, so they must run before anything crops or scales
+    if !settings.redaction_zones.is_empty() {
+        pipeline.push(Box::new(RedactionProcessor::new(
+            settings.redaction_zones.clone(),
+        )));
+    }
+    if let Some(highlighter) = clicks {
+        let max_age = highlighter.dissolve();
+        pipeline.push(Box::new(ClickHighlightProcessor::new(
+            highlighter,
+            Box::new(move |frame: &CaptureFrame| {
+                crate::platform::input::recent_clicks_in_frame(frame, max_age)
+            }),
+        )));
+    }
+    // Zooms after the overlays so clicks are magnified with the content under them
+    if settings.zoom.is_enabled() {
+        pipeline.push(Box::new(ZoomProcessor::new(
+            zoom,
+            Box::new(|frame: &CaptureFrame| crate::platform::input::pointer_in_frame(frame)),
+        )));
+    }
+    for config in &settings.processing_pipeline {
+        pipeline.push(config.build());
+    }
+    // Scaling last keeps a constant resolution whatever earlier stages did
+    if let Some([width, height]) = settings.output_resolution {
+        pipeline.push(Box::new(
+            FrameScaler::new(width, height)
+                .with_filter(settings.output_scale_filter)
+                .with_fit(settings.output_fit_mode),
+        ));
+    }
+    // Drawn on the final frame so its size and margin don't change with scaling
+    if let Some(overlay) = watermark {
+        pipeline.push(Box::new(overlay));
+    }
+    if !pipeline.is_empty() {
+        tracing::debug!(stages = ?pipeline.stage_names(), "Frame processing pipeline");
+    }
+    pipeline
+}
+
 fn stop_capture_with_platform(
     state: &AppState,
     platform: Arc<dyn CapturePlatform>,
@@ -379,6 +643,20 @@ fn stop_capture_with_platform(
     // Stop capture engine
     let mut engine_lock = state.capture_engine.lock().unwrap();
     if let Some(ref mut engine) = *engine_lock {
+        let stats = engine.stats();
+        log::info!(
+            "Capture session stats: {} frames delivered, {} dropped, average interval {:?}",
+            stats.frames_delivered,
+            stats.frames_dropped,
+            stats.average_interval
+        );
+        let pool = FramePool::global().stats();
+        log::info!(
+            "Frame pool: {} hits, {} misses, {} buffers pooled",
+            pool.hits,
+            pool.misses,
+            pool.pooled
+        );
         engine.stop();
         log::info!("Capture engine stopped");
     }
@@ -451,6 +729,74 @@ pub async fn start_capture(
     )
 }
 
+/// Capture the bordered region plus `regions` (x, y, width, height in screen
+/// coordinates), arranged on one canvas by the `composition` settings
+#[tauri::command]
+pub async fn start_multi_region_capture(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    regions: Vec<(i32, i32, u32, u32)>,
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    if regions.iter().any(|r| r.2 == 0 || r.3 == 0) {
+        return Err("Capture regions must not be empty".to_string());
+    }
+    let platform: Arc<dyn CapturePlatform> = Arc::new(RealCapturePlatform);
+    let options = CaptureStartOptions {
+        extra_regions: regions
+            .into_iter()
+            .map(|(x, y, width, height)| CaptureRect::new(x, y, width, height))
+            .collect(),
+        ..CaptureStartOptions::default()
+    };
+    start_capture_with_platform(
+        x,
+        y,
+        width,
+        height,
+        state.inner(),
+        Some(app),
+        platform,
+        options,
+    )
+}
+
+/// Capture a window and keep the region on it as it moves, resizes or is minimized
+#[tauri::command]
+pub async fn start_window_capture(
+    window: WindowIdentifier,
+    state: State<'_, AppState>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut follower = WindowFollower::for_platform(window).map_err(|e| e.to_string())?;
+    let bounds = match follower.poll().map_err(|e| e.to_string())? {
+        FollowEvent::Moved(bounds) => bounds,
+        FollowEvent::Minimized => return Err("Window is minimized".to_string()),
+        _ => return Err("Window not found".to_string()),
+    };
+    tracing::info!(window = ?follower.target(), ?bounds, "Starting window-follow capture");
+
+    let platform: Arc<dyn CapturePlatform> = Arc::new(RealCapturePlatform);
+    let options = CaptureStartOptions {
+        follow: Some(follower),
+        ..CaptureStartOptions::default()
+    };
+    // The start rect is recomputed around `bounds` once the border width is known
+    start_capture_with_platform(
+        bounds.x,
+        bounds.y,
+        bounds.width,
+        bounds.height,
+        state.inner(),
+        Some(app),
+        platform,
+        options,
+    )
+}
+
 #[tauri::command]
 pub async fn stop_capture(state: State<'_, AppState>) -> Result<Settings, String> {
     let platform: Arc<dyn CapturePlatform> = Arc::new(RealCapturePlatform);
@@ -470,11 +816,44 @@ pub async fn is_capturing(state: State<'_, AppState>) -> Result<bool, String> {
     Ok(*state.is_capturing.lock().unwrap())
 }
 
+/// Step the capture zoom up one level; returns the new target level
+#[tauri::command]
+pub async fn capture_zoom_in(state: State<'_, AppState>) -> Result<f32, String> {
+    let level = state.zoom.lock().unwrap().zoom_in(std::time::Instant::now());
+    tracing::debug!(level, "Capture zoom in");
+    Ok(level)
+}
+
+/// Step the capture zoom down one level; returns the new target level
+#[tauri::command]
+pub async fn capture_zoom_out(state: State<'_, AppState>) -> Result<f32, String> {
+    let level = state.zoom.lock().unwrap().zoom_out(std::time::Instant::now());
+    tracing::debug!(level, "Capture zoom out");
+    Ok(level)
+}
+
+/// Set an explicit capture zoom level (1.0 = off); returns the clamped target level
+#[tauri::command]
+pub async fn set_capture_zoom(level: f32, state: State<'_, AppState>) -> Result<f32, String> {
+    let mut zoom = state.zoom.lock().unwrap();
+    zoom.set_level(level, std::time::Instant::now());
+    Ok(zoom.target_level())
+}
+
+/// Stage latency percentiles and drop counts of the current (or last) capture
+#[tauri::command]
+pub async fn get_capture_metrics(state: State<'_, AppState>) -> Result<MetricsSnapshot, String> {
+    Ok(state.metrics.lock().unwrap().snapshot())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rustframe_capture::capture::{CaptureEngine, CaptureFrame, CaptureRect};
+    use rustframe_capture::capture::{
+        CaptureEngine, CaptureFrame, CaptureMetrics, CaptureRect, RenderTiming,
+    };
     use rustframe_capture::window_filter::WindowIdentifier;
+    use rustframe_capture::window_follow::{WindowLocator, WindowState};
     use std::sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Mutex,
@@ -655,11 +1034,28 @@ mod tests {
             _is_interacting: bool,
             _frame: CaptureFrame,
             _use_gpu: bool,
-            _capture_clicks_enabled: bool,
-            _click_color: [u8; 4],
-            _click_dissolve_ms: u64,
-            _click_radius: u32,
+            _clicks: Option<&ClickHighlighter>,
+        ) -> RenderTiming {
+            RenderTiming::default()
+        }
+
+        fn move_capture_border(
+            &self,
+            _x: i32,
+            _y: i32,
+            _width: i32,
+            _height: i32,
+            _border_w: i32,
         ) {
+            self.record("move_capture_border");
+        }
+
+        fn set_capture_border_visible(&self, visible: bool) {
+            self.record(if visible {
+                "show_capture_border"
+            } else {
+                "hide_capture_border"
+            });
         }
 
         fn get_capture_rect(&self) -> Option<(i32, i32, i32, i32)> {
@@ -704,6 +1100,8 @@ mod tests {
             render_thread_stop: Arc::new(Mutex::new(false)),
             render_thread_handle: Arc::new(Mutex::new(None)),
             monitors: Arc::new(Mutex::new(Vec::new())),
+            zoom: Arc::new(Mutex::new(FollowZoom::default())),
+            metrics: Arc::new(Mutex::new(CaptureMetrics::new())),
         }
     }
 
@@ -730,6 +1128,8 @@ mod tests {
         let options = CaptureStartOptions {
             spawn_render_thread: false,
             from_shortcut: false,
+            follow: None,
+            extra_regions: Vec::new(),
         };
 
         let result = start_capture_with_platform(
@@ -804,6 +1204,8 @@ mod tests {
         let options = CaptureStartOptions {
             spawn_render_thread: false,
             from_shortcut: false,
+            follow: None,
+            extra_regions: Vec::new(),
         };
 
         let result = start_capture_with_platform(
@@ -840,6 +1242,8 @@ mod tests {
         let options = CaptureStartOptions {
             spawn_render_thread: false,
             from_shortcut: false,
+            follow: None,
+            extra_regions: Vec::new(),
         };
 
         let result = start_capture_with_platform(
@@ -858,6 +1262,186 @@ mod tests {
         assert_eq!(existing_engine_state.stop_calls.load(Ordering::SeqCst), 2);
     }
 
+    /// Replays a fixed sequence of window lookups
+    struct ScriptedLocator(std::collections::VecDeque<Option<WindowState>>);
+
+    impl WindowLocator for ScriptedLocator {
+        fn locate(&mut self, _target: &WindowIdentifier) -> anyhow::Result<Option<WindowState>> {
+            Ok(self.0.pop_front().flatten())
+        }
+    }
+
+    fn scripted_follower(states: Vec<(CaptureRect, bool)>) -> WindowFollower {
+        let states = states
+            .into_iter()
+            .map(|(bounds, minimized)| Some(WindowState { bounds, minimized }))
+            .collect();
+        WindowFollower::new(
+            WindowIdentifier::app_window("app", "Editor"),
+            Box::new(ScriptedLocator(states)),
+        )
+    }
+
+    #[test]
+    fn start_capture_places_region_on_followed_window() {
+        let mut settings = Settings::default();
+        settings.capture_clicks = false;
+        settings.border_width = 3;
+
+        let state = build_test_state(settings);
+        let platform = Arc::new(MockCapturePlatform::default());
+        let platform_dyn: Arc<dyn CapturePlatform> = platform.clone();
+
+        let window = CaptureRect::new(100, 50, 640, 480);
+        let mut follower = scripted_follower(vec![(window, false)]);
+        follower.poll().unwrap();
+        let options = CaptureStartOptions {
+            spawn_render_thread: false,
+            from_shortcut: false,
+            follow: Some(follower),
+            extra_regions: Vec::new(),
+        };
+
+        let result =
+            start_capture_with_platform(0, 0, 10, 10, &state, None, platform_dyn, options);
+
+        assert!(result.is_ok());
+        let region = *platform.engine_state.start_region.lock().unwrap();
+        assert_eq!(region, Some(window));
+    }
+
+    #[test]
+    fn window_follow_pauses_while_minimized() {
+        let window = CaptureRect::new(0, 0, 320, 200);
+        let mut follower = scripted_follower(vec![
+            (window, false),
+            (window, true),
+            (CaptureRect::new(40, 0, 320, 200), false),
+        ]);
+        let platform = MockCapturePlatform::default();
+        let (sink, _frames) = FrameSink::channel(1);
+        let pump = FramePump::start(
+            Arc::new(Mutex::new(None)),
+            sink,
+            std::time::Duration::from_millis(5),
+        );
+
+        apply_window_follow(&mut follower, &pump, &platform, 2);
+        assert_eq!(platform.calls(), vec!["move_capture_border"]);
+
+        apply_window_follow(&mut follower, &pump, &platform, 2);
+        assert!(pump.is_paused());
+        assert!(platform.calls().contains(&"hide_capture_border"));
+
+        apply_window_follow(&mut follower, &pump, &platform, 2);
+        assert!(!pump.is_paused());
+        assert!(platform.calls().ends_with(&["move_capture_border", "show_capture_border"]));
+        pump.stop();
+    }
+
+    #[test]
+    fn start_capture_composes_extra_regions() {
+        let mut settings = Settings::default();
+        settings.capture_clicks = false;
+        settings.border_width = 0;
+
+        let state = build_test_state(settings);
+        let platform = Arc::new(MockCapturePlatform::default());
+        let platform_dyn: Arc<dyn CapturePlatform> = platform.clone();
+
+        let options = CaptureStartOptions {
+            spawn_render_thread: false,
+            extra_regions: vec![CaptureRect::new(800, 0, 400, 300)],
+            ..CaptureStartOptions::default()
+        };
+
+        let result =
+            start_capture_with_platform(0, 0, 640, 480, &state, None, platform_dyn, options);
+
+        assert!(result.is_ok());
+        let calls = platform.calls();
+        let engines = calls
+            .iter()
+            .filter(|&&c| c == "create_capture_engine_for_settings")
+            .count();
+        assert_eq!(engines, 2);
+
+        let engine_lock = state.capture_engine.lock().unwrap();
+        let engine = engine_lock.as_ref().expect("engine");
+        let composed = engine
+            .as_any()
+            .downcast_ref::<MultiRegionCaptureEngine>()
+            .expect("composed engine");
+        assert_eq!(
+            composed.regions(),
+            vec![
+                CaptureRect::new(0, 0, 640, 480),
+                CaptureRect::new(800, 0, 400, 300)
+            ]
+        );
+    }
+
+    #[test]
+    fn start_capture_resets_metrics() {
+        let mut settings = Settings::default();
+        settings.capture_clicks = false;
+
+        let state = build_test_state(settings);
+        state.metrics.lock().unwrap().set_queue_dropped(7);
+        let platform: Arc<dyn CapturePlatform> = Arc::new(MockCapturePlatform::default());
+        let options = CaptureStartOptions {
+            spawn_render_thread: false,
+            ..CaptureStartOptions::default()
+        };
+
+        let result = start_capture_with_platform(0, 0, 640, 480, &state, None, platform, options);
+
+        assert!(result.is_ok());
+        assert_eq!(state.metrics.lock().unwrap().snapshot(), MetricsSnapshot::default());
+    }
+
+    #[test]
+    fn start_capture_spans_monitors() {
+        let mut settings = Settings::default();
+        settings.capture_clicks = false;
+        settings.border_width = 0;
+        settings.monitor_span.enabled = true;
+
+        let state = build_test_state(settings);
+        *state.monitors.lock().unwrap() = (0..2)
+            .map(|i| MonitorInfo {
+                id: i,
+                name: format!("Monitor {}", i),
+                x: i as i32 * 1000,
+                y: 0,
+                width: 1000,
+                height: 800,
+                scale_factor: 1.0,
+                is_primary: i == 0,
+                refresh_rate: 60,
+            })
+            .collect();
+        let platform = Arc::new(MockCapturePlatform::default());
+        let platform_dyn: Arc<dyn CapturePlatform> = platform.clone();
+
+        let options = CaptureStartOptions {
+            spawn_render_thread: false,
+            ..CaptureStartOptions::default()
+        };
+
+        let result =
+            start_capture_with_platform(900, 0, 200, 100, &state, None, platform_dyn, options);
+
+        assert!(result.is_ok());
+        let engine_lock = state.capture_engine.lock().unwrap();
+        let engine = engine_lock.as_ref().expect("engine");
+        let spanning = engine
+            .as_any()
+            .downcast_ref::<SpanningCaptureEngine>()
+            .expect("spanning engine");
+        assert_eq!(spanning.active_monitors(), vec![0, 1]);
+    }
+
     #[test]
     fn cleanup_on_capture_failed_clears_state() {
         let settings = Settings::default();
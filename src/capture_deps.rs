@@ -1,6 +1,6 @@
 use crate::app_state::AppState;
 use crate::settings::Settings;
-use rustframe_capture::capture::{CaptureEngine, CaptureFrame};
+use rustframe_capture::capture::{CaptureEngine, CaptureFrame, ClickHighlighter, RenderTiming};
 use rustframe_capture::window_filter::WindowIdentifier;
 
 pub trait CapturePlatform: Send + Sync {
@@ -37,11 +37,10 @@ pub trait CapturePlatform: Send + Sync {
         is_interacting: bool,
         frame: CaptureFrame,
         use_gpu: bool,
-        capture_clicks_enabled: bool,
-        click_color: [u8; 4],
-        click_dissolve_ms: u64,
-        click_radius: u32,
-    );
+        clicks: Option<&ClickHighlighter>,
+    ) -> RenderTiming;
+    fn move_capture_border(&self, x: i32, y: i32, width: i32, height: i32, border_w: i32);
+    fn set_capture_border_visible(&self, visible: bool);
     fn get_capture_rect(&self) -> Option<(i32, i32, i32, i32)>;
     fn get_capture_inner_rect(&self) -> Option<(i32, i32, i32, i32)>;
     fn cleanup_after_capture_stop(&self);
@@ -132,20 +131,22 @@ impl CapturePlatform for RealCapturePlatform {
         is_interacting: bool,
         frame: CaptureFrame,
         use_gpu: bool,
-        capture_clicks_enabled: bool,
-        click_color: [u8; 4],
-        click_dissolve_ms: u64,
-        click_radius: u32,
-    ) {
+        clicks: Option<&ClickHighlighter>,
+    ) -> RenderTiming {
         crate::platform::services::render_frame_to_destination_if_available(
             is_interacting,
             frame,
             use_gpu,
-            capture_clicks_enabled,
-            click_color,
-            click_dissolve_ms,
-            click_radius,
-        );
+            clicks,
+        )
+    }
+
+    fn move_capture_border(&self, x: i32, y: i32, width: i32, height: i32, border_w: i32) {
+        crate::platform::services::move_capture_border(x, y, width, height, border_w);
+    }
+
+    fn set_capture_border_visible(&self, visible: bool) {
+        crate::platform::services::set_capture_border_visible(visible);
     }
 
     fn get_capture_rect(&self) -> Option<(i32, i32, i32, i32)> {
@@ -41,9 +41,15 @@ pub fn handlers() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sy
         super::preview_border::update_preview_border,
         super::preview_border::get_preview_border_rect,
         super::capture_controller::start_capture,
+        super::capture_controller::start_window_capture,
+        super::capture_controller::start_multi_region_capture,
         super::capture_controller::stop_capture,
         super::capture_controller::cleanup_on_capture_failed,
         super::capture_controller::is_capturing,
+        super::capture_controller::capture_zoom_in,
+        super::capture_controller::capture_zoom_out,
+        super::capture_controller::set_capture_zoom,
+        super::capture_controller::get_capture_metrics,
         system::get_screen_dimensions,
         system::get_monitor_refresh_rate,
         system::get_monitors,
@@ -102,6 +102,12 @@ pub mod debug {
     /// - Windows: `set RUSTFRAME_ALLOW_SCREEN_CAPTURE=1`
     /// - macOS/Linux: `export RUSTFRAME_ALLOW_SCREEN_CAPTURE=1`
     pub const ALLOW_SCREEN_CAPTURE_ENV: &str = "RUSTFRAME_ALLOW_SCREEN_CAPTURE";
+
+    /// Environment variable pointing at a recording to play back instead of capturing the screen
+    /// Accepts a directory of PNG frames or a raw frame dump; playback loops.
+    /// - Windows: `set RUSTFRAME_REPLAY_SOURCE=C:\path\to\frames`
+    /// - macOS/Linux: `export RUSTFRAME_REPLAY_SOURCE=/path/to/frames`
+    pub const REPLAY_SOURCE_ENV: &str = "RUSTFRAME_REPLAY_SOURCE";
 }
 
 /// Remote Resources
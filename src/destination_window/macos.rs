@@ -10,6 +10,7 @@
 
 use crate::traits::PreviewWindow;
 use crate::platform;
+use rustframe_capture::capture::FramePool;
 use cocoa::appkit::{NSBackingStoreType, NSColor, NSWindow, NSWindowStyleMask};
 use cocoa::base::{id, nil, BOOL, NO, YES};
 use cocoa::foundation::{NSAutoreleasePool, NSPoint, NSRect, NSSize};
@@ -521,6 +522,9 @@ impl DestinationWindow {
                 update_on_main_thread(&context as *const _ as *mut std::ffi::c_void);
             }
         }
+
+        // The CGImage holds its own copy, so the frame buffer can be reused
+        FramePool::global().recycle(data);
     }
 
     pub fn render(&mut self, pixels: &[u8], width: u32, height: u32) {
@@ -10,6 +10,7 @@
 //! - Selectable via gpu_acceleration setting
 
 use crate::traits::PreviewWindow;
+use rustframe_capture::capture::FramePool;
 use rustframe_capture::display_info;
 use std::mem;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -188,11 +189,15 @@ impl DestinationWindow {
 
         // Update the global buffer
         if let Ok(mut buffer) = FRAME_BUFFER.lock() {
-            *buffer = Some(FrameData {
+            let previous = buffer.replace(FrameData {
                 data,
                 width,
                 height,
             });
+            // The frame that was on screen is no longer needed
+            if let Some(previous) = previous {
+                FramePool::global().recycle(previous.data);
+            }
         }
 
         // Optionally signal window thread to repaint immediately
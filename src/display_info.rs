@@ -44,6 +44,8 @@
 
 use lazy_static::lazy_static;
 use log::info;
+#[cfg(target_os = "linux")]
+use log::warn;
 use std::sync::{Arc, RwLock};
 #[cfg(target_os = "macos")]
 use std::sync::atomic::{AtomicU8, Ordering};
@@ -9,6 +9,9 @@ pub mod config;
 // Window filtering (exclusion/inclusion logic)
 pub mod window_filter;
 
+// Window-follow capture (region bound to a window's bounds)
+pub mod window_follow;
+
 // Platform-agnostic utilities
 pub mod platform_utils;
 
@@ -17,7 +20,22 @@ pub mod capture;
 pub mod display_info;
 
 // Re-export commonly used types
-pub use capture::{CaptureEngine, CaptureFrame, CaptureRect, CaptureSettings};
+pub use capture::{CaptureEngine, CaptureFrame, CaptureRect, CaptureSettings, PixelFormat};
+pub use capture::{CaptureTarget, CaptureTargetKind};
+pub use capture::{ReplayCaptureEngine, SyntheticCaptureEngine, SyntheticPattern};
+pub use capture::{DropPolicy, FrameBroadcaster, FramePump, FrameReceiver, FrameSink, SharedFrame};
+pub use capture::{FramePacer, IdleDetector, IdleSettings, PacingSettings, PacingStats};
+pub use capture::{CaptureMetrics, MetricsSnapshot, PipelineStage, RenderTiming};
+pub use capture::{FramePool, PoolStats};
+pub use capture::{FitMode, FrameScaler, ScaleFilter};
+pub use capture::{FramePipeline, FrameProcessor, ProcessorConfig};
+pub use capture::{RedactionProcessor, RedactionStyle, RedactionZone};
+pub use capture::{OverlayPosition, OverlayProcessor, OverlaySettings};
+pub use capture::{ClickButton, ClickEvent, ClickHighlightProcessor, ClickHighlightStyle, ClickHighlighter};
+pub use capture::{CursorCompositor, CursorImage, CursorStyle};
+pub use capture::{FollowZoom, ZoomControl, ZoomProcessor, ZoomSettings};
+#[cfg(feature = "stream")]
+pub use capture::{FrameStream, FrameStreamOptions};
 
 #[cfg(target_os = "windows")]
 pub use capture::windows::WindowsCaptureEngine;
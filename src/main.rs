@@ -1,6 +1,8 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(unexpected_cfgs)]
+// settings and settings_io keep their tests ahead of the loading helpers
+#![allow(clippy::items_after_test_module)]
 
 use tauri::Manager;
 
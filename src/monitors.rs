@@ -1,3 +1,4 @@
+use rustframe_capture::capture::{CaptureRect, SpanMonitor};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,3 +13,20 @@ pub struct MonitorInfo {
     pub is_primary: bool,
     pub refresh_rate: u32,
 }
+
+impl MonitorInfo {
+    /// This monitor in capture-region coordinates, for monitor-spanning capture
+    pub fn span_monitor(&self) -> SpanMonitor {
+        let bounds = CaptureRect::new(self.x, self.y, self.width, self.height);
+        if cfg!(target_os = "macos") {
+            // Regions are in points, frames come back in backing pixels
+            SpanMonitor::new(bounds, self.scale_factor)
+        } else if cfg!(target_os = "windows") {
+            // Win32 reports physical pixels, the same space as regions and frames
+            SpanMonitor::new(bounds, 1.0)
+        } else {
+            // Tauri reports logical bounds; X11 regions and frames are in pixels
+            SpanMonitor::new(bounds.to_pixels(self.scale_factor), 1.0)
+        }
+    }
+}
@@ -54,7 +54,6 @@ pub mod input {
     pub struct ClickEvent {
         pub x: i32,
         pub y: i32,
-        #[allow(dead_code)]
         pub button: MouseButton,
         pub timestamp: std::time::Instant,
     }
@@ -451,6 +450,141 @@ pub mod input {
         }
     }
 
+    /// Time of the latest click, for waking idle capture
+    pub fn last_click_time() -> Option<std::time::Instant> {
+        CLICK_POSITIONS
+            .lock()
+            .ok()?
+            .iter()
+            .map(|click| click.timestamp)
+            .max()
+    }
+
+    /// Recent clicks inside `frame`, in that frame's pixels, for the click highlight compositor
+    pub fn recent_clicks_in_frame(
+        frame: &rustframe_capture::capture::CaptureFrame,
+        max_age: std::time::Duration,
+    ) -> Vec<rustframe_capture::capture::ClickEvent> {
+        use rustframe_capture::capture::ClickButton;
+
+        // Same display info the platform renderers convert frame offsets with
+        let display = rustframe_capture::display_info::get();
+        let frame_rect = rustframe_capture::capture::CaptureRect::new(
+            frame.offset_x,
+            frame.offset_y,
+            frame.width,
+            frame.height,
+        );
+        let frame_pixels = frame_rect.to_pixels(display.scale_factor);
+
+        get_recent_clicks(
+            frame_pixels.x,
+            frame_pixels.y,
+            frame_pixels.width,
+            frame_pixels.height,
+            max_age.as_millis() as u64,
+        )
+        .into_iter()
+        .map(|click| rustframe_capture::capture::ClickEvent {
+            x: click.x - frame_pixels.x,
+            y: click.y - frame_pixels.y,
+            button: match click.button {
+                MouseButton::Left => ClickButton::Left,
+                MouseButton::Right => ClickButton::Right,
+                MouseButton::Middle => ClickButton::Middle,
+            },
+            timestamp: click.timestamp,
+        })
+        .collect()
+    }
+
+    /// Pointer position inside `frame`, in that frame's pixels, for the follow-cursor zoom.
+    /// `None` when the pointer can't be read or lies outside the frame.
+    pub fn pointer_in_frame(
+        frame: &rustframe_capture::capture::CaptureFrame,
+    ) -> Option<(f64, f64)> {
+        let (x, y) = pointer_position()?;
+        let display = rustframe_capture::display_info::get();
+        let x = (x - display.points_to_pixels(frame.offset_x as f64)) as f64;
+        let y = (y - display.points_to_pixels(frame.offset_y as f64)) as f64;
+        let inside = x >= 0.0 && y >= 0.0 && x < frame.width as f64 && y < frame.height as f64;
+        inside.then(|| (x, y))
+    }
+
+    /// Current pointer position in screen pixels
+    #[cfg(windows)]
+    pub fn pointer_position() -> Option<(i32, i32)> {
+        use windows::Win32::Foundation::POINT;
+        use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        let mut point = POINT::default();
+        unsafe { GetCursorPos(&mut point) }.ok()?;
+        Some((point.x, point.y))
+    }
+
+    /// Current pointer position in screen pixels
+    #[cfg(target_os = "macos")]
+    pub fn pointer_position() -> Option<(i32, i32)> {
+        #[link(name = "ApplicationServices", kind = "framework")]
+        extern "C" {
+            fn CGEventCreate(source: *const std::ffi::c_void) -> *mut std::ffi::c_void;
+            fn CGEventGetLocation(event: *mut std::ffi::c_void)
+                -> core_graphics::geometry::CGPoint;
+            fn CFRelease(cf: *const std::ffi::c_void);
+        }
+
+        unsafe {
+            // A null-sourced event carries the current pointer location
+            let event = CGEventCreate(std::ptr::null());
+            if event.is_null() {
+                return None;
+            }
+            let location = CGEventGetLocation(event);
+            CFRelease(event);
+            Some(crate::platform::coords::macos_event_to_screen_pixels(
+                location.x, location.y,
+            ))
+        }
+    }
+
+    /// Current pointer position in screen pixels
+    #[cfg(target_os = "linux")]
+    pub fn pointer_position() -> Option<(i32, i32)> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::ConnectionExt;
+        use x11rb::rust_connection::RustConnection;
+
+        lazy_static::lazy_static! {
+            // Queried every frame while zoomed, so the connection is kept open
+            static ref POINTER_CONN: Mutex<Option<(RustConnection, u32)>> = Mutex::new(None);
+        }
+
+        let mut guard = POINTER_CONN.lock().ok()?;
+        if guard.is_none() {
+            let (conn, screen) = x11rb::connect(None).ok()?;
+            let root = conn.setup().roots.get(screen)?.root;
+            *guard = Some((conn, root));
+        }
+        let (conn, root) = guard.as_ref()?;
+        let reply = conn
+            .query_pointer(*root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok());
+        match reply {
+            Some(reply) => Some((reply.root_x as i32, reply.root_y as i32)),
+            None => {
+                // Reconnect on the next call if the server went away
+                *guard = None;
+                None
+            }
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
+    pub fn pointer_position() -> Option<(i32, i32)> {
+        None
+    }
+
     /// Clear all stored click events
     pub fn clear_clicks() {
         if let Ok(mut clicks) = CLICK_POSITIONS.lock() {
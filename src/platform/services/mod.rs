@@ -5,10 +5,14 @@ use crate::rec_indicator::RecIndicator;
 use crate::platform;
 use crate::settings::{should_allow_screen_capture, Settings};
 use crate::AppState;
-use rustframe_capture::capture::{CaptureEngine, CaptureFrame};
+use rustframe_capture::capture::{
+    CaptureEngine, CaptureFrame, CaptureRect, ClickHighlighter, RenderTiming,
+    ReplayCaptureEngine,
+};
+use rustframe_capture::config;
 use rustframe_capture::window_filter::WindowIdentifier;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
 #[cfg(target_os = "windows")]
@@ -31,6 +35,12 @@ use linux as imp;
 pub fn create_capture_engine_for_settings(
     settings: &Settings,
 ) -> Result<Box<dyn CaptureEngine>, String> {
+    if let Some(source) = std::env::var_os(config::debug::REPLAY_SOURCE_ENV) {
+        tracing::info!("Replaying recorded frames from {:?} instead of capturing", source);
+        return ReplayCaptureEngine::open(&source)
+            .map(|e| Box::new(e.with_looping(true)) as Box<dyn CaptureEngine>)
+            .map_err(|e| format!("{:#}", e));
+    }
     imp::create_capture_engine_for_settings(settings)
 }
 
@@ -110,53 +120,46 @@ pub fn update_capture_engine_during_live_move(
     imp::update_capture_engine_during_live_move(engine, x, y, width, height, border_w);
 }
 
+/// Show `frame` in the destination window
+///
+/// CPU frames arrive with click highlights already drawn by the frame pipeline;
+/// `clicks` is only needed to overlay them on GPU frames.
 pub fn render_frame_to_destination(
     window: &mut DestinationWindow,
     frame: CaptureFrame,
     use_gpu: bool,
-    capture_clicks_enabled: bool,
-    click_color: [u8; 4],
-    click_dissolve_ms: u64,
-    click_radius: u32,
+    clicks: Option<&ClickHighlighter>,
 ) {
-    imp::render_frame_to_destination(
-        window,
-        frame,
-        use_gpu,
-        capture_clicks_enabled,
-        click_color,
-        click_dissolve_ms,
-        click_radius,
-    );
+    imp::render_frame_to_destination(window, frame, use_gpu, clicks);
 }
 
+/// Render into the destination window if it exists and, outside interaction, isn't busy
+///
+/// Returns how long the lock took and how long rendering took, for the capture metrics.
 pub fn render_frame_to_destination_if_available(
     is_interacting: bool,
     frame: CaptureFrame,
     use_gpu: bool,
-    capture_clicks_enabled: bool,
-    click_color: [u8; 4],
-    click_dissolve_ms: u64,
-    click_radius: u32,
-) {
+    clicks: Option<&ClickHighlighter>,
+) -> RenderTiming {
+    let lock_start = Instant::now();
     let dest_lock = if is_interacting {
         crate::app_state::DESTINATION_WINDOW.lock().ok()
     } else {
         crate::app_state::DESTINATION_WINDOW.try_lock().ok()
     };
+    let mut timing = RenderTiming {
+        lock_wait: lock_start.elapsed(),
+        render: None,
+    };
     if let Some(mut dest_lock) = dest_lock {
         if let Some(window) = dest_lock.as_mut() {
-            render_frame_to_destination(
-                window,
-                frame,
-                use_gpu,
-                capture_clicks_enabled,
-                click_color,
-                click_dissolve_ms,
-                click_radius,
-            );
+            let render_start = Instant::now();
+            render_frame_to_destination(window, frame, use_gpu, clicks);
+            timing.render = Some(render_start.elapsed());
         }
     }
+    timing
 }
 
 pub fn create_separation_layer_for_capture(
@@ -478,6 +481,41 @@ pub fn store_destination_window(window: DestinationWindow) {
     *crate::app_state::DESTINATION_WINDOW.lock().unwrap() = Some(window);
 }
 
+/// Move the capture border (and what follows it) to `(x, y, width, height)`
+///
+/// Used by window-follow capture, where the border tracks a window instead of
+/// being dragged; the capture region itself is updated through the frame pump.
+pub fn move_capture_border(x: i32, y: i32, width: i32, height: i32, border_w: i32) {
+    // Blocks briefly rather than dropping the move: follow only polls now and then
+    if let Some(border) = crate::app_state::HOLLOW_BORDER.lock().unwrap().as_ref() {
+        border.update_rect(x, y, width, height);
+    }
+
+    let inner = CaptureRect::from_signed(x, y, width, height)
+        .inset(border_w)
+        .at_least(1, 1);
+    handle_border_interaction_platform_updates(
+        x,
+        y,
+        width,
+        height,
+        inner.width as i32,
+        inner.height as i32,
+    );
+    update_rec_indicator_position(x, y, width, border_w);
+}
+
+/// Hide the capture border while a followed window is minimized
+pub fn set_capture_border_visible(visible: bool) {
+    if let Some(border) = crate::app_state::HOLLOW_BORDER.lock().unwrap().as_ref() {
+        if visible {
+            border.show();
+        } else {
+            border.hide();
+        }
+    }
+}
+
 pub fn store_rec_indicator(rec: RecIndicator) {
     *crate::app_state::REC_INDICATOR.lock().unwrap() = Some(rec);
 }
@@ -1,10 +1,13 @@
 use crate::destination_window::{DestinationWindow, DestinationWindowConfig};
 use crate::monitors::MonitorInfo;
 use crate::settings::{CaptureMethod, PreviewMode, Settings};
-use rustframe_capture::capture::{CaptureEngine, CaptureFrame, CaptureRect, GpuTextureHandle};
+use rustframe_capture::capture::{
+    convert, CaptureEngine, CaptureFrame, CaptureRect, ClickHighlighter, GpuTextureHandle,
+    PixelFormat,
+};
 use rustframe_capture::window_filter::WindowIdentifier;
 use std::sync::{Arc, Mutex};
-use rustframe_capture::{config, display_info};
+use rustframe_capture::config;
 
 pub fn create_capture_engine_for_settings(
     settings: &Settings,
@@ -530,72 +533,34 @@ pub fn render_frame_to_destination(
     window: &mut DestinationWindow,
     mut frame: CaptureFrame,
     use_gpu: bool,
-    capture_clicks_enabled: bool,
-    click_color: [u8; 4],
-    click_dissolve_ms: u64,
-    click_radius: u32,
+    clicks: Option<&ClickHighlighter>,
 ) {
-    let mut click_shader_data: Option<(f32, f32, f32, f32, [f32; 4])> = None;
-
-    if capture_clicks_enabled {
-        let display = display_info::get();
-
-        let offset_x_pixels = display.points_to_pixels(frame.offset_x as f64);
-        let offset_y_pixels = display.points_to_pixels(frame.offset_y as f64);
-        let width_pixels = display.points_to_pixels(frame.width as f64) as u32;
-        let height_pixels = display.points_to_pixels(frame.height as f64) as u32;
-
-        let clicks = crate::platform::input::get_recent_clicks(
-            offset_x_pixels,
-            offset_y_pixels,
-            width_pixels,
-            height_pixels,
-            click_dissolve_ms,
-        );
-
-        if !clicks.is_empty() {
-            if let Some(latest_click) = clicks.last() {
-                let age_ms = latest_click.timestamp.elapsed().as_millis() as f32;
-                let alpha_factor = 1.0 - (age_ms / click_dissolve_ms as f32).min(1.0);
-                let scaled_radius = display.points_to_pixels(click_radius as f64);
-
-                let frame_x = latest_click.x as f32 - offset_x_pixels as f32;
-                let frame_y = latest_click.y as f32 - offset_y_pixels as f32;
-
-                let [r, g, b, a] = config::colors::rgba_u8_to_f32(click_color);
-
-                click_shader_data = Some((
-                    frame_x,
-                    frame_y,
-                    scaled_radius as f32,
-                    alpha_factor,
-                    [r, g, b, a],
-                ));
-            }
-
-            if !use_gpu {
-                for click in clicks {
-                    let frame_x = click.x - offset_x_pixels;
-                    let frame_y = click.y - offset_y_pixels;
-                    let age_ms = click.timestamp.elapsed().as_millis() as f32;
-                    let alpha = 1.0 - (age_ms / click_dissolve_ms as f32).min(1.0);
-                    let radius = display.points_to_pixels(click_radius as f64);
-
-                    draw_click_highlight(
-                        &mut frame.data,
-                        frame.width as i32,
-                        frame.height as i32,
-                        frame_x,
-                        frame_y,
-                        click_color,
-                        alpha,
-                        radius,
-                    );
-                }
-            }
+    // The destination window takes tightly packed BGRA (GDI DIB / D3D11 upload)
+    if !frame.data.is_empty() {
+        if let Err(e) = convert::convert_frame(&mut frame, PixelFormat::Bgra8) {
+            log::warn!("Dropping frame with unusable pixel layout: {:#}", e);
+            return;
         }
     }
 
+    // CPU frames already carry their click highlights from the frame pipeline.
+    // The D3D11 shader draws one highlight: the most recent click still visible.
+    let click_shader_data = if use_gpu {
+        clicks.and_then(|highlighter| {
+            let recent =
+                crate::platform::input::recent_clicks_in_frame(&frame, highlighter.dissolve());
+            highlighter
+                .marks(&recent, std::time::Instant::now())
+                .last()
+                .map(|mark| {
+                    let [r, g, b, a] = config::colors::rgba_u8_to_f32(mark.color);
+                    (mark.x, mark.y, mark.radius, mark.fade, [r, g, b, a])
+                })
+        })
+    } else {
+        None
+    };
+
     if use_gpu {
         if let Some(GpuTextureHandle::D3D11 {
             texture_ptr,
@@ -622,59 +587,6 @@ pub fn render_frame_to_destination(
     }
 }
 
-fn draw_click_highlight(
-    data: &mut [u8],
-    width: i32,
-    height: i32,
-    center_x: i32,
-    center_y: i32,
-    color: [u8; 4],
-    alpha_factor: f32,
-    radius: i32,
-) {
-    let inner_radius = (radius as f32 * 0.4).max(4.0) as i32;
-
-    for dy in -radius..=radius {
-        for dx in -radius..=radius {
-            let px = center_x + dx;
-            let py = center_y + dy;
-
-            if px < 0 || px >= width || py < 0 || py >= height {
-                continue;
-            }
-
-            let dist_sq = dx * dx + dy * dy;
-            let dist = (dist_sq as f32).sqrt();
-            if dist > radius as f32 {
-                continue;
-            }
-
-            let ring_alpha = if dist <= inner_radius as f32 {
-                1.0
-            } else {
-                1.0 - (dist - inner_radius as f32) / (radius - inner_radius) as f32
-            };
-
-            let final_alpha = config::colors::normalize_alpha(color[3]) * alpha_factor * ring_alpha;
-            if final_alpha <= 0.0 {
-                continue;
-            }
-
-            let idx = ((py * width + px) * 4) as usize;
-            if idx + 3 >= data.len() {
-                continue;
-            }
-
-            let inv_alpha = 1.0 - final_alpha;
-            data[idx] = (color[2] as f32 * final_alpha + data[idx] as f32 * inv_alpha) as u8;
-            data[idx + 1] =
-                (color[1] as f32 * final_alpha + data[idx + 1] as f32 * inv_alpha) as u8;
-            data[idx + 2] =
-                (color[0] as f32 * final_alpha + data[idx + 2] as f32 * inv_alpha) as u8;
-        }
-    }
-}
-
 fn position_preview_below_separation() {
     if let (Ok(sep_lock), Ok(dest_lock)) = (
         crate::app_state::SEPARATION_LAYER.try_lock(),
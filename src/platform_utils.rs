@@ -3,6 +3,8 @@
 //! Common helper functions that can be used across all platforms.
 //! These utilities help reduce code duplication and provide consistent behavior.
 
+use crate::capture::CaptureRect;
+
 /// Convert BGR color (Windows format) to RGBA
 ///
 /// # Arguments
@@ -48,12 +50,8 @@ pub fn calculate_inner_rect(
     height: i32,
     border_width: i32,
 ) -> (i32, i32, i32, i32) {
-    (
-        x + border_width,
-        y + border_width,
-        (width - 2 * border_width).max(0),
-        (height - 2 * border_width).max(0),
-    )
+    let inner = CaptureRect::from_signed(x, y, width, height).inset(border_width);
+    (inner.x, inner.y, inner.width as i32, inner.height as i32)
 }
 
 /// Validate window dimensions
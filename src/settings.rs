@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use rustframe_capture::capture::{
+    ClickHighlightStyle, CompositeSettings, CursorStyle, FitMode, IdleSettings, OverlaySettings,
+    PacingSettings, ProcessorConfig, RedactionZone, ScaleFilter, SpanSettings, ZoomSettings,
+};
 use rustframe_capture::config;
 use rustframe_capture::window_filter::WindowFilterSettings;
 
@@ -91,6 +95,9 @@ impl Default for ShortcutSettings {
 pub struct Settings {
     // Mouse & Cursor
     pub show_cursor: bool,
+    // Scale, halo and auto-hide for the software-drawn cursor
+    #[serde(default)]
+    pub cursor_style: CursorStyle,
     #[serde(default = "default_capture_clicks")]
     pub capture_clicks: bool,
     #[serde(default = "default_click_color")]
@@ -99,6 +106,13 @@ pub struct Settings {
     pub click_dissolve_ms: u32,
     #[serde(default = "default_click_radius")]
     pub click_highlight_radius: u32,
+    #[serde(default)]
+    pub click_highlight_style: ClickHighlightStyle,
+    // Per-button colors; None uses click_highlight_color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_highlight_right_color: Option<[u8; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub click_highlight_middle_color: Option<[u8; 4]>,
 
     // Border
     pub show_border: bool,
@@ -109,6 +123,12 @@ pub struct Settings {
     pub target_fps: u32,
     #[serde(default = "default_gpu_acceleration")]
     pub gpu_acceleration: bool,
+    // Drift-free schedule for target_fps; adaptive slowdown and frame duplication
+    #[serde(default)]
+    pub frame_pacing: PacingSettings,
+    // Skip unchanged frames and capture at a low rate while content is static
+    #[serde(default)]
+    pub idle_detection: IdleSettings,
 
     // Capture Method
     #[serde(default)]
@@ -119,6 +139,47 @@ pub struct Settings {
     #[serde(default)]
     pub capture_preview_window: bool,
 
+    // Output Scaling
+    // When set, every CPU frame is resampled to this fixed [width, height] so the
+    // destination window keeps one resolution however the border is resized.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_resolution: Option<[u32; 2]>,
+    #[serde(default)]
+    pub output_scale_filter: ScaleFilter,
+    #[serde(default)]
+    pub output_fit_mode: FitMode,
+
+    // Frame Processing
+    // Stages applied to every CPU frame before it is rendered, in order.
+    // The output_resolution scaler, when set, always runs last.
+    #[serde(default)]
+    pub processing_pipeline: Vec<ProcessorConfig>,
+
+    // Privacy Redaction
+    // Region-relative rectangles hidden (solid, blur, pixelate) before any other stage
+    #[serde(default)]
+    pub redaction_zones: Vec<RedactionZone>,
+
+    // Watermark
+    // PNG logo and/or templated text composited onto every frame, after scaling
+    #[serde(default)]
+    pub watermark: OverlaySettings,
+
+    // Follow-Cursor Zoom
+    // Levels, pointer smoothing/dead zone and transition for zoom_in/zoom_out during capture
+    #[serde(default)]
+    pub zoom: ZoomSettings,
+
+    // Multi-Region Composition
+    // Layout, gap and background when extra regions are captured alongside the main one
+    #[serde(default)]
+    pub composition: CompositeSettings,
+
+    // Monitor Spanning
+    // Stitch regions that straddle monitors from one capture per monitor
+    #[serde(default)]
+    pub monitor_span: SpanSettings,
+
     // Advanced (hidden) WinAPI Destination Window overrides (Windows-only behavior)
     // These are intentionally not exposed in the UI by default.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -162,6 +223,9 @@ pub struct Settings {
     pub log_to_file: bool,
     #[serde(default = "default_log_retention_days")]
     pub log_retention_days: u32,
+    // Seconds between capture latency summaries in the log; 0 disables them
+    #[serde(default)]
+    pub metrics_log_interval_secs: u32,
 
     // UI
     #[serde(default = "default_ui_zoom")]
@@ -235,18 +299,33 @@ impl Default for Settings {
         let capture_preview_window = cfg!(target_os = "macos");
         Self {
             show_cursor: false, // Shadow cursor disabled by default to avoid double cursor in screen sharing
+            cursor_style: CursorStyle::default(),
             capture_clicks: true, // Default to enabled for testing
             click_highlight_color: config::capture::DEFAULT_CLICK_HIGHLIGHT_COLOR,
             click_dissolve_ms: 300, // Reduced from 5000ms - 300ms is plenty for click feedback
             click_highlight_radius: 20,
+            click_highlight_style: ClickHighlightStyle::default(),
+            click_highlight_right_color: None,
+            click_highlight_middle_color: None,
             show_border: true,
             border_color: [255, 0, 0, 255],
             border_width: config::window::DEFAULT_BORDER_WIDTH as u32,
             target_fps: config::capture::DEFAULT_TARGET_FPS,
             gpu_acceleration: true,
+            frame_pacing: PacingSettings::default(),
+            idle_detection: IdleSettings::default(),
             capture_method: CaptureMethod::default(),
             preview_mode: PreviewMode::default(),
             capture_preview_window,
+            output_resolution: None,
+            output_scale_filter: ScaleFilter::default(),
+            output_fit_mode: FitMode::default(),
+            processing_pipeline: Vec::new(),
+            redaction_zones: Vec::new(),
+            watermark: OverlaySettings::default(),
+            zoom: ZoomSettings::default(),
+            composition: CompositeSettings::default(),
+            monitor_span: SpanSettings::default(),
             winapi_destination_alpha: None,
             winapi_destination_topmost: None,
             winapi_destination_click_through: None,
@@ -264,6 +343,7 @@ impl Default for Settings {
             log_level: "Error".to_string(),
             log_to_file: true,
             log_retention_days: config::capture::LOG_RETENTION_DAYS as u32,
+            metrics_log_interval_secs: 0,
             ui_zoom: 1.0,
             shortcuts: ShortcutSettings::default(),
             debug_allow_screen_capture: None,
@@ -95,7 +95,9 @@ pub trait PreviewWindow: Send + Sync {
     fn hwnd_value(&self) -> isize;
 
     /// Update frame with new pixel data
-    /// - data: RGBA pixel data (owned Vec for platform flexibility)
+    /// - data: tightly packed pixels in the window's native format, BGRA on Windows and
+    ///   RGBA on macOS (owned Vec for platform flexibility); convert with
+    ///   `rustframe_capture::capture::convert` before calling
     /// - width, height: frame dimensions
     fn update_frame(&self, data: Vec<u8>, width: u32, height: u32);
 
@@ -67,6 +67,17 @@ impl WindowIdentifier {
             window_name: window_name.to_string(),
         }
     }
+
+    /// Check whether a live window with this app id and title is the one identified
+    /// Empty fields match anything; the title matches when it contains `window_name`
+    pub fn matches(&self, app_id: &str, title: &str) -> bool {
+        if self.app_id.is_empty() && self.window_name.is_empty() {
+            return false;
+        }
+        let app_matches = self.app_id.is_empty() || self.app_id.eq_ignore_ascii_case(app_id);
+        let title_matches = self.window_name.is_empty() || title.contains(&self.window_name);
+        app_matches && title_matches
+    }
 }
 
 /// Window filtering mode
@@ -242,6 +253,17 @@ mod tests {
         assert!(!settings.should_capture(&other, None));
     }
 
+    #[test]
+    fn identifier_matching_treats_empty_fields_as_wildcards() {
+        let window = WindowIdentifier::app_window("com.test.app", "Editor");
+        assert!(window.matches("com.test.app", "Editor - notes.txt"));
+        assert!(!window.matches("com.other.app", "Editor"));
+
+        assert!(WindowIdentifier::app_all_windows("code").matches("Code", "anything"));
+        assert!(WindowIdentifier::window_by_name("Inspector").matches("any", "Web Inspector"));
+        assert!(!WindowIdentifier::new(String::new(), String::new()).matches("any", "any"));
+    }
+
     #[test]
     fn exclusions_add_preview_when_auto_exclude_enabled() {
         let preview = WindowIdentifier::preview_window();
@@ -0,0 +1,640 @@
+//! Window-follow capture: keep the capture region on a target window
+//!
+//! A `WindowLocator` finds the window described by a `WindowIdentifier` and
+//! reports where it is. `WindowFollower` turns successive lookups into
+//! move/minimize/restore events for the capture loop. Bounds use the capture
+//! engine's coordinate space: points on macOS, physical pixels elsewhere.
+
+use anyhow::Result;
+
+use crate::capture::CaptureRect;
+use crate::window_filter::WindowIdentifier;
+
+/// Where a window is and whether it can be captured right now
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowState {
+    /// Content area of the window
+    pub bounds: CaptureRect,
+    /// Minimized, hidden or otherwise not on screen
+    pub minimized: bool,
+}
+
+/// Platform lookup of a window by identifier
+pub trait WindowLocator: Send {
+    /// State of the window matching `target`, or `None` if no such window exists
+    ///
+    /// Implementations keep following the window they matched first, so a
+    /// changing title (browser tabs, documents) doesn't lose it.
+    fn locate(&mut self, target: &WindowIdentifier) -> Result<Option<WindowState>>;
+}
+
+/// What changed since the previous `WindowFollower::poll`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FollowEvent {
+    Unchanged,
+    /// The window moved or resized; capture should switch to these bounds
+    Moved(CaptureRect),
+    /// The window was minimized; capture should pause
+    Minimized,
+    /// The window is back on screen after being minimized or lost
+    Restored(CaptureRect),
+    /// The window no longer exists; capture should pause
+    Lost,
+}
+
+/// Tracks one window and reports changes to its bounds
+pub struct WindowFollower {
+    target: WindowIdentifier,
+    locator: Box<dyn WindowLocator>,
+    bounds: Option<CaptureRect>,
+    paused: bool,
+}
+
+impl WindowFollower {
+    pub fn new(target: WindowIdentifier, locator: Box<dyn WindowLocator>) -> Self {
+        Self {
+            target,
+            locator,
+            bounds: None,
+            paused: false,
+        }
+    }
+
+    /// Follow `target` with the locator for the current platform
+    pub fn for_platform(target: WindowIdentifier) -> Result<Self> {
+        Ok(Self::new(target, platform_locator()?))
+    }
+
+    pub fn target(&self) -> &WindowIdentifier {
+        &self.target
+    }
+
+    /// Last known on-screen bounds
+    pub fn bounds(&self) -> Option<CaptureRect> {
+        self.bounds
+    }
+
+    /// True while the window is minimized or gone
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Look the window up again and report what changed
+    pub fn poll(&mut self) -> Result<FollowEvent> {
+        let state = self.locator.locate(&self.target)?;
+        let bounds = match state {
+            Some(state)
+                if !state.minimized && state.bounds.width > 0 && state.bounds.height > 0 =>
+            {
+                state.bounds
+            }
+            Some(_) | None => {
+                if self.paused {
+                    return Ok(FollowEvent::Unchanged);
+                }
+                self.paused = true;
+                return Ok(if state.is_some() {
+                    FollowEvent::Minimized
+                } else {
+                    FollowEvent::Lost
+                });
+            }
+        };
+
+        let was_paused = std::mem::replace(&mut self.paused, false);
+        let moved = self.bounds != Some(bounds);
+        self.bounds = Some(bounds);
+        Ok(if was_paused {
+            FollowEvent::Restored(bounds)
+        } else if moved {
+            FollowEvent::Moved(bounds)
+        } else {
+            FollowEvent::Unchanged
+        })
+    }
+}
+
+/// Window locator for the current platform
+pub fn platform_locator() -> Result<Box<dyn WindowLocator>> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(Box::new(x11::X11WindowLocator::new()?))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Ok(Box::new(win32::Win32WindowLocator::default()))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Box::new(macos::CgWindowLocator::default()))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+    {
+        anyhow::bail!("Window-follow capture is not supported on this platform")
+    }
+}
+
+// --- X11 ------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use super::{WindowLocator, WindowState};
+    use crate::capture::CaptureRect;
+    use crate::window_filter::WindowIdentifier;
+    use anyhow::{Context, Result};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, MapState, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    pub struct X11WindowLocator {
+        conn: RustConnection,
+        root: Window,
+        net_client_list: Atom,
+        net_wm_name: Atom,
+        net_wm_state: Atom,
+        net_wm_state_hidden: Atom,
+        utf8_string: Atom,
+        /// Window matched by an earlier lookup
+        current: Option<Window>,
+    }
+
+    fn intern(conn: &RustConnection, name: &str) -> Result<Atom> {
+        Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+    }
+
+    impl X11WindowLocator {
+        pub fn new() -> Result<Self> {
+            let (conn, screen) = x11rb::connect(None).context("Failed to connect to X server")?;
+            let root = conn.setup().roots[screen].root;
+            Ok(Self {
+                net_client_list: intern(&conn, "_NET_CLIENT_LIST")?,
+                net_wm_name: intern(&conn, "_NET_WM_NAME")?,
+                net_wm_state: intern(&conn, "_NET_WM_STATE")?,
+                net_wm_state_hidden: intern(&conn, "_NET_WM_STATE_HIDDEN")?,
+                utf8_string: intern(&conn, "UTF8_STRING")?,
+                conn,
+                root,
+                current: None,
+            })
+        }
+
+        /// Managed top-level windows, as listed by the window manager
+        fn clients(&self) -> Result<Vec<Window>> {
+            let reply = self
+                .conn
+                .get_property(
+                    false,
+                    self.root,
+                    self.net_client_list,
+                    AtomEnum::WINDOW,
+                    0,
+                    u32::MAX,
+                )?
+                .reply()?;
+            Ok(reply.value32().map(|v| v.collect()).unwrap_or_default())
+        }
+
+        fn text_property(&self, window: Window, property: Atom, kind: Atom) -> Option<String> {
+            let reply = self
+                .conn
+                .get_property(false, window, property, kind, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?;
+            (!reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).into_owned())
+        }
+
+        fn title(&self, window: Window) -> String {
+            self.text_property(window, self.net_wm_name, self.utf8_string)
+                .or_else(|| {
+                    self.text_property(window, AtomEnum::WM_NAME.into(), AtomEnum::STRING.into())
+                })
+                .unwrap_or_default()
+        }
+
+        /// Both halves of WM_CLASS (instance and class name)
+        fn classes(&self, window: Window) -> Vec<String> {
+            self.text_property(window, AtomEnum::WM_CLASS.into(), AtomEnum::STRING.into())
+                .map(|value| {
+                    value
+                        .split('\0')
+                        .filter(|part| !part.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
+        fn matches(&self, window: Window, target: &WindowIdentifier) -> bool {
+            let title = self.title(window);
+            let classes = self.classes(window);
+            if classes.is_empty() {
+                return target.matches("", &title);
+            }
+            classes.iter().any(|class| target.matches(class, &title))
+        }
+
+        fn state(&self, window: Window) -> Result<WindowState> {
+            let geometry = self.conn.get_geometry(window)?.reply()?;
+            let origin = self
+                .conn
+                .translate_coordinates(window, self.root, 0, 0)?
+                .reply()?;
+            let attributes = self.conn.get_window_attributes(window)?.reply()?;
+            let hidden = self
+                .conn
+                .get_property(false, window, self.net_wm_state, AtomEnum::ATOM, 0, 64)?
+                .reply()?
+                .value32()
+                .map(|mut atoms| atoms.any(|atom| atom == self.net_wm_state_hidden))
+                .unwrap_or(false);
+
+            Ok(WindowState {
+                bounds: CaptureRect::new(
+                    origin.dst_x as i32,
+                    origin.dst_y as i32,
+                    geometry.width as u32,
+                    geometry.height as u32,
+                ),
+                minimized: hidden || attributes.map_state != MapState::VIEWABLE,
+            })
+        }
+    }
+
+    impl WindowLocator for X11WindowLocator {
+        fn locate(&mut self, target: &WindowIdentifier) -> Result<Option<WindowState>> {
+            if let Some(window) = self.current {
+                match self.state(window) {
+                    Ok(state) => return Ok(Some(state)),
+                    // Destroyed; look for another window with the same identity
+                    Err(_) => self.current = None,
+                }
+            }
+
+            for window in self.clients()? {
+                if self.matches(window, target) {
+                    self.current = Some(window);
+                    return self.state(window).map(Some);
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+// --- Windows --------------------------------------------------------------
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use super::{WindowLocator, WindowState};
+    use crate::capture::CaptureRect;
+    use crate::window_filter::WindowIdentifier;
+    use anyhow::Result;
+    use windows::core::{BOOL, PWSTR};
+    use windows::Win32::Foundation::{CloseHandle, HWND, LPARAM, RECT};
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindow,
+        IsWindowVisible,
+    };
+
+    #[derive(Default)]
+    pub struct Win32WindowLocator {
+        /// HWND matched by an earlier lookup
+        current: Option<isize>,
+    }
+
+    struct Search<'a> {
+        target: &'a WindowIdentifier,
+        found: Option<isize>,
+    }
+
+    fn title(hwnd: HWND) -> String {
+        let mut buffer = [0u16; 512];
+        let len = unsafe { GetWindowTextW(hwnd, &mut buffer) };
+        String::from_utf16_lossy(&buffer[..len.max(0) as usize])
+    }
+
+    /// Executable name without extension, e.g. "chrome"
+    fn exe_name(hwnd: HWND) -> String {
+        unsafe {
+            let mut pid = 0u32;
+            GetWindowThreadProcessId(hwnd, Some(&mut pid));
+            let process = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+                Ok(process) => process,
+                Err(_) => return String::new(),
+            };
+            let mut buffer = [0u16; 1024];
+            let mut len = buffer.len() as u32;
+            let result = QueryFullProcessImageNameW(
+                process,
+                PROCESS_NAME_WIN32,
+                PWSTR(buffer.as_mut_ptr()),
+                &mut len,
+            );
+            let _ = CloseHandle(process);
+            if result.is_err() {
+                return String::new();
+            }
+            let path = String::from_utf16_lossy(&buffer[..len as usize]);
+            std::path::Path::new(&path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }
+    }
+
+    unsafe extern "system" fn enum_proc(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        if IsWindowVisible(hwnd).as_bool() && search.target.matches(&exe_name(hwnd), &title(hwnd)) {
+            search.found = Some(hwnd.0 as isize);
+            return BOOL::from(false);
+        }
+        BOOL::from(true)
+    }
+
+    fn state(hwnd: HWND) -> WindowState {
+        let mut rect = RECT::default();
+        unsafe {
+            // The extended frame excludes the invisible resize borders of Windows 10+
+            if DwmGetWindowAttribute(
+                hwnd,
+                DWMWA_EXTENDED_FRAME_BOUNDS,
+                &mut rect as *mut RECT as *mut std::ffi::c_void,
+                std::mem::size_of::<RECT>() as u32,
+            )
+            .is_err()
+            {
+                let _ = GetWindowRect(hwnd, &mut rect);
+            }
+        }
+        WindowState {
+            bounds: CaptureRect::new(
+                rect.left,
+                rect.top,
+                (rect.right - rect.left).max(0) as u32,
+                (rect.bottom - rect.top).max(0) as u32,
+            ),
+            minimized: unsafe { IsIconic(hwnd).as_bool() || !IsWindowVisible(hwnd).as_bool() },
+        }
+    }
+
+    impl WindowLocator for Win32WindowLocator {
+        fn locate(&mut self, target: &WindowIdentifier) -> Result<Option<WindowState>> {
+            if let Some(value) = self.current {
+                let hwnd = HWND(value as *mut std::ffi::c_void);
+                if unsafe { IsWindow(Some(hwnd)).as_bool() } {
+                    return Ok(Some(state(hwnd)));
+                }
+                self.current = None;
+            }
+
+            let mut search = Search {
+                target,
+                found: None,
+            };
+            unsafe {
+                // Stopping early makes EnumWindows report an error, so the result is ignored
+                let _ = EnumWindows(Some(enum_proc), LPARAM(&mut search as *mut _ as isize));
+            }
+            self.current = search.found;
+            Ok(search
+                .found
+                .map(|value| state(HWND(value as *mut std::ffi::c_void))))
+        }
+    }
+}
+
+// --- macOS ----------------------------------------------------------------
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{WindowLocator, WindowState};
+    use crate::capture::CaptureRect;
+    use crate::window_filter::WindowIdentifier;
+    use anyhow::Result;
+    use core_foundation::array::{CFArray, CFArrayRef};
+    use core_foundation::base::TCFType;
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::number::CFNumber;
+    use core_foundation::string::CFString;
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    extern "C" {
+        fn CGWindowListCopyWindowInfo(option: u32, relative_to_window: u32) -> CFArrayRef;
+        fn CGRectMakeWithDictionaryRepresentation(dict: CFDictionaryRef, rect: *mut CGRect)
+            -> bool;
+    }
+
+    const KCG_WINDOW_LIST_OPTION_ALL: u32 = 0;
+    const KCG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS: u32 = 1 << 4;
+
+    #[derive(Default)]
+    pub struct CgWindowLocator {
+        /// CGWindowID matched by an earlier lookup
+        current: Option<u32>,
+    }
+
+    fn number(dict: &CFDictionary, key: &'static str) -> Option<i64> {
+        let key = CFString::from_static_string(key);
+        dict.find(key.as_CFTypeRef() as *const _)
+            .and_then(|value| unsafe { CFNumber::wrap_under_get_rule(value.cast()) }.to_i64())
+    }
+
+    fn string(dict: &CFDictionary, key: &'static str) -> String {
+        let key = CFString::from_static_string(key);
+        dict.find(key.as_CFTypeRef() as *const _)
+            .map(|value| unsafe { CFString::wrap_under_get_rule(value.cast()) }.to_string())
+            .unwrap_or_default()
+    }
+
+    fn bundle_id(pid: i64) -> String {
+        unsafe {
+            let app: *mut objc::runtime::Object = msg_send![
+                class!(NSRunningApplication),
+                runningApplicationWithProcessIdentifier: pid as i32
+            ];
+            if app.is_null() {
+                return String::new();
+            }
+            let bundle_id: *mut objc::runtime::Object = msg_send![app, bundleIdentifier];
+            if bundle_id.is_null() {
+                return String::new();
+            }
+            let cstr: *const std::os::raw::c_char = msg_send![bundle_id, UTF8String];
+            if cstr.is_null() {
+                return String::new();
+            }
+            std::ffi::CStr::from_ptr(cstr)
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    fn state(dict: &CFDictionary) -> Option<WindowState> {
+        let key = CFString::from_static_string("kCGWindowBounds");
+        let bounds = dict.find(key.as_CFTypeRef() as *const _)?;
+        let mut rect = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(0.0, 0.0));
+        if !unsafe { CGRectMakeWithDictionaryRepresentation(bounds.cast(), &mut rect) } {
+            return None;
+        }
+        let key = CFString::from_static_string("kCGWindowIsOnscreen");
+        let on_screen = dict
+            .find(key.as_CFTypeRef() as *const _)
+            .map(|value| bool::from(unsafe { CFBoolean::wrap_under_get_rule(value.cast()) }))
+            .unwrap_or(false);
+        Some(WindowState {
+            bounds: CaptureRect::new(
+                rect.origin.x.round() as i32,
+                rect.origin.y.round() as i32,
+                rect.size.width.round().max(0.0) as u32,
+                rect.size.height.round().max(0.0) as u32,
+            ),
+            // Minimized windows and windows on other Spaces are off screen
+            minimized: !on_screen,
+        })
+    }
+
+    impl WindowLocator for CgWindowLocator {
+        fn locate(&mut self, target: &WindowIdentifier) -> Result<Option<WindowState>> {
+            let list_ref = unsafe {
+                CGWindowListCopyWindowInfo(
+                    KCG_WINDOW_LIST_OPTION_ALL | KCG_WINDOW_LIST_EXCLUDE_DESKTOP_ELEMENTS,
+                    0,
+                )
+            };
+            if list_ref.is_null() {
+                return Ok(None);
+            }
+            let list = unsafe { CFArray::<CFDictionary>::wrap_under_create_rule(list_ref) };
+
+            if let Some(current) = self.current {
+                let dict = list
+                    .iter()
+                    .find(|dict| number(dict, "kCGWindowNumber") == Some(current as i64));
+                if let Some(dict) = dict {
+                    return Ok(state(&dict));
+                }
+                self.current = None;
+            }
+
+            for dict in list.iter() {
+                // Layer 0 holds normal application windows
+                if number(&dict, "kCGWindowLayer").unwrap_or(0) != 0 {
+                    continue;
+                }
+                let title = string(&dict, "kCGWindowName");
+                let pid = number(&dict, "kCGWindowOwnerPID").unwrap_or(0);
+                if target.matches(&bundle_id(pid), &title) {
+                    self.current = number(&dict, "kCGWindowNumber").map(|id| id as u32);
+                    return Ok(state(&dict));
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Replays a fixed sequence of lookups
+    struct Scripted(VecDeque<Option<WindowState>>);
+
+    impl WindowLocator for Scripted {
+        fn locate(&mut self, _target: &WindowIdentifier) -> Result<Option<WindowState>> {
+            Ok(self.0.pop_front().flatten())
+        }
+    }
+
+    fn shown(x: i32, y: i32, width: u32, height: u32) -> Option<WindowState> {
+        Some(WindowState {
+            bounds: CaptureRect::new(x, y, width, height),
+            minimized: false,
+        })
+    }
+
+    fn follower(states: Vec<Option<WindowState>>) -> WindowFollower {
+        WindowFollower::new(
+            WindowIdentifier::app_window("app", "Editor"),
+            Box::new(Scripted(states.into())),
+        )
+    }
+
+    #[test]
+    fn reports_moves_only_when_bounds_change() {
+        let mut follower = follower(vec![
+            shown(10, 20, 800, 600),
+            shown(10, 20, 800, 600),
+            shown(50, 20, 640, 600),
+        ]);
+
+        let first = CaptureRect::new(10, 20, 800, 600);
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Moved(first));
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Unchanged);
+        let moved = CaptureRect::new(50, 20, 640, 600);
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Moved(moved));
+        assert_eq!(follower.bounds(), Some(moved));
+    }
+
+    #[test]
+    fn pauses_while_minimized_and_restores() {
+        let minimized = Some(WindowState {
+            bounds: CaptureRect::new(10, 20, 800, 600),
+            minimized: true,
+        });
+        let mut follower = follower(vec![
+            shown(10, 20, 800, 600),
+            minimized,
+            minimized,
+            shown(10, 20, 800, 600),
+        ]);
+
+        follower.poll().unwrap();
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Minimized);
+        assert!(follower.is_paused());
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Unchanged);
+        assert_eq!(
+            follower.poll().unwrap(),
+            FollowEvent::Restored(CaptureRect::new(10, 20, 800, 600))
+        );
+        assert!(!follower.is_paused());
+    }
+
+    #[test]
+    fn lost_window_pauses_until_found_again() {
+        let mut follower = follower(vec![
+            shown(0, 0, 100, 100),
+            None,
+            None,
+            shown(5, 5, 100, 100),
+        ]);
+
+        follower.poll().unwrap();
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Lost);
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Unchanged);
+        assert_eq!(
+            follower.poll().unwrap(),
+            FollowEvent::Restored(CaptureRect::new(5, 5, 100, 100))
+        );
+    }
+
+    #[test]
+    fn zero_sized_window_counts_as_minimized() {
+        let mut follower = follower(vec![shown(0, 0, 100, 100), shown(0, 0, 0, 0)]);
+
+        follower.poll().unwrap();
+        assert_eq!(follower.poll().unwrap(), FollowEvent::Minimized);
+    }
+}
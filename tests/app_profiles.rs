@@ -1,4 +1,6 @@
 #![allow(dead_code)]
+// The settings modules keep their tests ahead of the loading helpers
+#![allow(clippy::items_after_test_module)]
 
 use serde_json::json;
 use std::fs;
@@ -102,6 +104,26 @@ fn apply_profile_overrides_merges_settings() {
     assert!(!merged.capture_clicks);
 }
 
+#[test]
+fn profile_can_define_redaction_zones() {
+    use rustframe_capture::capture::RedactionStyle;
+
+    let base = settings::Settings::default();
+    let overrides = json!({
+        "redaction_zones": [
+            {"x": 0, "y": 0, "width": 300, "height": 40, "style": {"type": "pixelate", "block": 12}}
+        ]
+    });
+
+    let merged = settings_io::apply_profile_overrides(&base, overrides).unwrap();
+    assert_eq!(merged.redaction_zones.len(), 1);
+    assert_eq!(merged.redaction_zones[0].width, 300);
+    assert_eq!(
+        merged.redaction_zones[0].style,
+        RedactionStyle::Pixelate { block: 12 }
+    );
+}
+
 #[test]
 fn write_and_read_active_profile() {
     let temp = TempDir::new("active_profile");
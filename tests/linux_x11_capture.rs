@@ -0,0 +1,275 @@
+//! X11 capture tests against a headless Xvfb server.
+//!
+//! Each test starts its own `Xvfb` instance. They are ignored by default; run
+//! them with `cargo test --test linux_x11_capture -- --ignored` on a machine with
+//! Xvfb installed. A missing server or extension then fails the test instead of
+//! skipping it.
+#![cfg(target_os = "linux")]
+
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use rustframe_capture::capture::linux::LinuxCaptureEngine;
+use rustframe_capture::capture::{CaptureEngine, CaptureRect, CaptureTarget, CaptureTargetKind};
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{ConnectionExt as _, CreateWindowAux, Window, WindowClass};
+use x11rb::rust_connection::RustConnection;
+use x11rb::COPY_DEPTH_FROM_PARENT;
+
+struct Xvfb {
+    child: Child,
+    display: String,
+}
+
+impl Xvfb {
+    fn start(slot: u32) -> Self {
+        let number = 90 + (std::process::id() % 50) * 4 + slot;
+        let display = format!(":{}", number);
+        let child = Command::new("Xvfb")
+            .args([
+                display.as_str(),
+                "-screen",
+                "0",
+                "320x240x24",
+                "-nolisten",
+                "tcp",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Xvfb is required for the X11 capture tests");
+
+        let socket = format!("/tmp/.X11-unix/X{}", number);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !Path::new(&socket).exists() {
+            assert!(
+                Instant::now() <= deadline,
+                "Xvfb {} did not come up",
+                display
+            );
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        Self { child, display }
+    }
+
+    /// Map a solid-colored window at the given position
+    ///
+    /// The connection is returned alongside the window id to keep it alive.
+    fn show_window(
+        &self,
+        x: i16,
+        y: i16,
+        width: u16,
+        height: u16,
+        pixel: u32,
+    ) -> (RustConnection, Window) {
+        let (conn, screen_num) = x11rb::connect(Some(&self.display)).unwrap();
+        let screen = &conn.setup().roots[screen_num];
+        let window = conn.generate_id().unwrap();
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            window,
+            screen.root,
+            x,
+            y,
+            width,
+            height,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            0,
+            &CreateWindowAux::new()
+                .background_pixel(pixel)
+                .override_redirect(1),
+        )
+        .unwrap();
+        conn.map_window(window).unwrap();
+        conn.get_input_focus().unwrap().reply().unwrap();
+        // Give the server a moment to paint the background
+        std::thread::sleep(Duration::from_millis(50));
+        (conn, window)
+    }
+}
+
+impl Drop for Xvfb {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn pixel_at(frame: &rustframe_capture::CaptureFrame, x: u32, y: u32) -> [u8; 4] {
+    let idx = (y * frame.stride + x * 4) as usize;
+    [
+        frame.data[idx],
+        frame.data[idx + 1],
+        frame.data[idx + 2],
+        frame.data[idx + 3],
+    ]
+}
+
+fn capture_solid_window(use_shm: bool, slot: u32) {
+    let xvfb = Xvfb::start(slot);
+    let _window = xvfb.show_window(40, 30, 50, 40, 0x00FF0000);
+
+    let mut engine = LinuxCaptureEngine::with_display(&xvfb.display).unwrap();
+    engine.set_use_shm(use_shm);
+    engine
+        .start(CaptureRect::new(30, 20, 80, 60), false, None)
+        .unwrap();
+    assert!(engine.is_active());
+    if !use_shm {
+        assert!(!engine.is_using_shm());
+    }
+
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!((frame.width, frame.height), (80, 60));
+    assert_eq!(frame.stride, 80 * 4);
+    assert_eq!((frame.offset_x, frame.offset_y), (30, 20));
+
+    // Inside the red window (BGRA)
+    assert_eq!(pixel_at(&frame, 20, 20), [0, 0, 255, 255]);
+    // Outside it: the default Xvfb root is not red
+    assert_ne!(pixel_at(&frame, 2, 2), [0, 0, 255, 255]);
+
+    engine.stop();
+    assert!(!engine.is_active());
+    assert!(engine.get_frame().is_none());
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn captures_region_with_shm() {
+    capture_solid_window(true, 0);
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn captures_region_with_get_image() {
+    capture_solid_window(false, 1);
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn update_region_moves_and_clips_capture() {
+    let xvfb = Xvfb::start(2);
+    let _window = xvfb.show_window(0, 0, 20, 20, 0x0000FF00);
+
+    let mut engine = LinuxCaptureEngine::with_display(&xvfb.display).unwrap();
+    engine
+        .start(CaptureRect::new(100, 100, 10, 10), false, None)
+        .unwrap();
+
+    // Straddle the top-left screen corner: the frame is clipped and the offset reports it
+    engine
+        .update_region(CaptureRect::new(-10, -5, 25, 20))
+        .unwrap();
+    assert_eq!(engine.get_region(), Some(CaptureRect::new(-10, -5, 25, 20)));
+
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!((frame.width, frame.height), (15, 15));
+    assert_eq!((frame.offset_x, frame.offset_y), (0, 0));
+    assert_eq!(pixel_at(&frame, 5, 5), [0, 255, 0, 255]);
+
+    // Growing the region while running reallocates the transfer buffer
+    engine
+        .update_region(CaptureRect::new(0, 0, 320, 240))
+        .unwrap();
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!((frame.width, frame.height), (320, 240));
+    assert_eq!(frame.data.len(), 320 * 240 * 4);
+
+    // Entirely off-screen regions produce no frame
+    engine
+        .update_region(CaptureRect::new(400, 400, 10, 10))
+        .unwrap();
+    assert!(engine.get_frame().is_none());
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn reports_native_damage() {
+    let xvfb = Xvfb::start(3);
+
+    let mut engine = LinuxCaptureEngine::with_display(&xvfb.display).unwrap();
+    engine
+        .start(CaptureRect::new(100, 100, 80, 60), false, None)
+        .unwrap();
+    assert!(engine.has_native_damage(), "Xvfb without DAMAGE");
+
+    // First frame has nothing to compare against
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!(frame.damage, Some(vec![CaptureRect::new(0, 0, 80, 60)]));
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!(frame.damage, Some(Vec::new()));
+
+    // A window mapped across the region corner damages only the overlap
+    let _window = xvfb.show_window(150, 140, 50, 50, 0x000000FF);
+    let frame = engine.get_frame().expect("frame");
+    let damage = frame.damage.expect("damage");
+    assert!(!damage.is_empty());
+    for rect in &damage {
+        assert!(rect.x >= 0 && rect.y >= 0);
+        assert!(rect.x as u32 + rect.width <= 80 && rect.y as u32 + rect.height <= 60);
+    }
+    assert!(damage
+        .iter()
+        .any(|rect| rect.x <= 50 && rect.y <= 40 && rect.x as u32 + rect.width >= 80));
+}
+
+#[test]
+fn start_fails_without_server() {
+    let mut engine = LinuxCaptureEngine::with_display(":4999").unwrap();
+    assert!(engine
+        .start(CaptureRect::new(0, 0, 10, 10), false, None)
+        .is_err());
+    assert!(!engine.is_active());
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn captures_covered_window_target() {
+    let xvfb = Xvfb::start(4);
+    let (_conn, window) = xvfb.show_window(40, 30, 60, 50, 0x00FF0000);
+    // A second window covering most of the first
+    let _cover = xvfb.show_window(30, 20, 60, 50, 0x0000FF00);
+
+    let mut engine = LinuxCaptureEngine::with_display(&xvfb.display).unwrap();
+    assert!(engine.supported_targets().contains(&CaptureTargetKind::Window));
+    engine
+        .start_target(CaptureTarget::Window(u64::from(window)), false, None)
+        .expect("Xvfb without Composite");
+    // Give the server a moment to paint into the redirected pixmap
+    std::thread::sleep(Duration::from_millis(50));
+
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!((frame.width, frame.height), (60, 50));
+    assert_eq!((frame.offset_x, frame.offset_y), (40, 30));
+    assert_eq!(engine.get_region(), Some(CaptureRect::new(40, 30, 60, 50)));
+    // The covered corner still shows the window's own red content
+    assert_eq!(pixel_at(&frame, 5, 5), [0, 0, 255, 255]);
+
+    assert!(engine
+        .update_region(CaptureRect::new(0, 0, 10, 10))
+        .is_err());
+}
+
+#[test]
+#[ignore = "needs Xvfb"]
+fn captures_monitor_target() {
+    let xvfb = Xvfb::start(5);
+
+    let mut engine = LinuxCaptureEngine::with_display(&xvfb.display).unwrap();
+    assert!(engine
+        .start_target(CaptureTarget::Monitor(7), false, None)
+        .is_err());
+    assert!(!engine.is_active());
+
+    engine
+        .start_target(CaptureTarget::Monitor(0), false, None)
+        .unwrap();
+    let frame = engine.get_frame().expect("frame");
+    assert_eq!((frame.width, frame.height), (320, 240));
+    assert_eq!((frame.offset_x, frame.offset_y), (0, 0));
+}
@@ -0,0 +1,261 @@
+//! Playback tests for the replay capture engine using recordings written to a temp dir.
+
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::Duration;
+
+use rustframe_capture::capture::{
+    CaptureEngine, CaptureFrame, CaptureRect, PixelFormat, RawFrameWriter, ReplayAnchor,
+    ReplayCaptureEngine,
+};
+
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("rustframe-replay-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn write_png(path: &Path, width: u32, height: u32, rgba: [u8; 4]) {
+    let file = BufWriter::new(File::create(path).unwrap());
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    let data: Vec<u8> = (0..width * height).flat_map(|_| rgba).collect();
+    writer.write_image_data(&data).unwrap();
+}
+
+fn solid_frame(width: u32, height: u32, bgra: [u8; 4]) -> CaptureFrame {
+    let data = (0..width * height).flat_map(|_| bgra).collect();
+    CaptureFrame::new(data, width, height)
+}
+
+fn next_frame(engine: &mut ReplayCaptureEngine) -> CaptureFrame {
+    for _ in 0..200 {
+        if let Some(frame) = engine.get_frame() {
+            return frame;
+        }
+        sleep(Duration::from_millis(5));
+    }
+    panic!("replay did not produce a frame");
+}
+
+#[test]
+fn plays_png_directory_at_recorded_timing() {
+    let dir = TempDir::new("png");
+    write_png(&dir.0.join("frame_000.png"), 16, 8, [255, 0, 0, 255]);
+    write_png(&dir.0.join("frame_001.png"), 16, 8, [0, 255, 0, 255]);
+    fs::write(dir.0.join("timestamps.txt"), "0\n150\n").unwrap();
+
+    let mut engine = ReplayCaptureEngine::open(&dir.0).unwrap();
+    assert_eq!(engine.frame_count(), 2);
+    engine
+        .start(CaptureRect::new(200, 100, 16, 8), false, None)
+        .unwrap();
+
+    // First frame is due immediately, kept as RGBA and anchored at the region
+    assert!(engine.has_new_frame());
+    let frame = engine.get_frame().unwrap();
+    assert_eq!(frame.pixel_format, PixelFormat::Rgba8);
+    assert_eq!((frame.width, frame.height, frame.stride), (16, 8, 64));
+    assert_eq!((frame.offset_x, frame.offset_y), (200, 100));
+    assert_eq!(&frame.data[..4], &[255, 0, 0, 255]);
+
+    // The second one is not served before its timestamp
+    assert!(!engine.has_new_frame());
+    assert!(engine.get_frame().is_none());
+
+    sleep(Duration::from_millis(160));
+    let frame = next_frame(&mut engine);
+    assert_eq!(
+        frame.into_packed(PixelFormat::Bgra8).unwrap()[..4],
+        [0, 255, 0, 255]
+    );
+
+    // Without looping playback holds on the last frame
+    sleep(Duration::from_millis(200));
+    assert!(engine.get_frame().is_none());
+
+    let stats = engine.stats();
+    assert_eq!(stats.frames_delivered, 2);
+    assert_eq!(stats.frames_dropped, 0);
+    assert!(stats.average_interval.unwrap() >= Duration::from_millis(150));
+}
+
+#[test]
+fn raw_dump_round_trips_and_crops_on_update_region() {
+    let dir = TempDir::new("raw");
+    let path = dir.0.join("capture.rfraw");
+
+    let mut first = solid_frame(20, 10, [1, 2, 3, 255]).with_offset(50, 60);
+    // Padded rows must survive the round trip
+    first.stride = 20 * 4 + 16;
+    first.data = (0..10)
+        .flat_map(|_| {
+            let mut row: Vec<u8> = (0..20).flat_map(|_| [1u8, 2, 3, 255]).collect();
+            row.extend_from_slice(&[0xEE; 16]);
+            row
+        })
+        .collect();
+
+    let mut writer = RawFrameWriter::create(&path).unwrap();
+    writer.write_frame(&first, Duration::ZERO).unwrap();
+    writer
+        .write_frame(
+            &solid_frame(20, 10, [9, 9, 9, 255]).with_offset(50, 60),
+            Duration::from_secs(30),
+        )
+        .unwrap();
+    writer.finish().unwrap();
+
+    let mut engine = ReplayCaptureEngine::open(&path).unwrap();
+    assert_eq!(engine.frame_count(), 2);
+    engine
+        .start(CaptureRect::new(50, 60, 20, 10), true, None)
+        .unwrap();
+
+    let frame = engine.get_frame().unwrap();
+    assert_eq!((frame.width, frame.height), (20, 10));
+    assert_eq!((frame.offset_x, frame.offset_y), (50, 60));
+    assert!(frame.data.chunks_exact(4).all(|px| px == [1, 2, 3, 255]));
+
+    // Moving the region re-serves the current frame cropped to the overlap
+    engine
+        .update_region(CaptureRect::new(45, 65, 10, 10))
+        .unwrap();
+    assert!(engine.has_new_frame());
+    let frame = engine.get_frame().unwrap();
+    assert_eq!((frame.width, frame.height), (5, 5));
+    assert_eq!((frame.offset_x, frame.offset_y), (50, 65));
+    assert_eq!(frame.stride, 20);
+
+    // No overlap, no frame
+    engine
+        .update_region(CaptureRect::new(0, 0, 10, 10))
+        .unwrap();
+    assert!(engine.get_frame().is_none());
+
+    engine.stop();
+    assert!(!engine.is_active());
+}
+
+#[test]
+fn raw_dump_keeps_pixel_format() {
+    let dir = TempDir::new("raw-nv12");
+    let path = dir.0.join("capture.rfraw");
+
+    // 4x2 NV12 with a padded luma stride of 6
+    let mut frame =
+        CaptureFrame::new(vec![0u8; 6 * 2 + 6], 4, 2).with_pixel_format(PixelFormat::Nv12);
+    frame.stride = 6;
+    frame.data[..6].copy_from_slice(&[16, 16, 16, 16, 0xAB, 0xAB]);
+
+    let mut writer = RawFrameWriter::create(&path).unwrap();
+    writer.write_frame(&frame, Duration::ZERO).unwrap();
+    // A buffer too short for its declared layout is refused
+    let short = CaptureFrame::new(vec![0u8; 4], 4, 2).with_pixel_format(PixelFormat::Nv12);
+    assert!(writer.write_frame(&short, Duration::ZERO).is_err());
+    writer.finish().unwrap();
+
+    let mut engine = ReplayCaptureEngine::open(&path).unwrap();
+    engine
+        .start(CaptureRect::new(0, 0, 4, 2), false, None)
+        .unwrap();
+    let frame = engine.get_frame().unwrap();
+    // Planar frames come back as BGRA after cropping
+    assert_eq!(frame.pixel_format, PixelFormat::Bgra8);
+    assert_eq!((frame.width, frame.height), (4, 2));
+}
+
+#[test]
+fn raw_writer_rejects_timestamps_going_backwards() {
+    let dir = TempDir::new("raw-backwards");
+    let frame = solid_frame(2, 2, [0, 0, 0, 255]);
+
+    let mut writer = RawFrameWriter::create(dir.0.join("capture.rfraw")).unwrap();
+    writer
+        .write_frame(&frame, Duration::from_millis(20))
+        .unwrap();
+    // Equal timestamps are fine, earlier ones are not
+    writer
+        .write_frame(&frame, Duration::from_millis(20))
+        .unwrap();
+    assert!(writer
+        .write_frame(&frame, Duration::from_millis(10))
+        .is_err());
+    writer.finish().unwrap();
+}
+
+#[test]
+fn looping_restarts_from_first_frame() {
+    let dir = TempDir::new("loop");
+    write_png(&dir.0.join("a.png"), 4, 4, [10, 10, 10, 255]);
+    write_png(&dir.0.join("b.png"), 4, 4, [20, 20, 20, 255]);
+
+    let mut engine = ReplayCaptureEngine::open(&dir.0)
+        .unwrap()
+        .with_frame_rate(20)
+        .with_looping(true)
+        .with_anchor(ReplayAnchor::Recorded);
+    assert_eq!(engine.duration(), Duration::from_millis(100));
+
+    engine
+        .start(CaptureRect::new(0, 0, 4, 4), false, None)
+        .unwrap();
+    let mut seen = Vec::new();
+    while seen.len() < 3 {
+        seen.push(next_frame(&mut engine).data[0]);
+    }
+    // a, b, then a again from the next pass
+    assert_eq!(seen, vec![10, 20, 10]);
+}
+
+#[test]
+fn slow_consumer_counts_skipped_frames_as_dropped() {
+    let dir = TempDir::new("drops");
+    for i in 0..4 {
+        write_png(&dir.0.join(format!("{}.png", i)), 2, 2, [i * 10, 0, 0, 255]);
+    }
+
+    let mut engine = ReplayCaptureEngine::open(&dir.0)
+        .unwrap()
+        .with_frame_rate(100);
+    engine
+        .start(CaptureRect::new(0, 0, 2, 2), false, None)
+        .unwrap();
+
+    // Everything is due by now, only the last frame is delivered
+    sleep(Duration::from_millis(60));
+    let frame = engine.get_frame().unwrap();
+    assert_eq!(frame.data[0], 30);
+    assert_eq!((frame.sequence, frame.dropped_frames), (0, 3));
+    // The timestamp reflects when the frame was recorded, not when it was read
+    assert!(frame.captured_at.elapsed() >= Duration::from_millis(25));
+    assert_eq!(engine.stats().frames_dropped, 3);
+}
+
+#[test]
+fn open_rejects_missing_and_empty_sources() {
+    let dir = TempDir::new("empty");
+    assert!(ReplayCaptureEngine::open(&dir.0).is_err());
+    assert!(ReplayCaptureEngine::open(dir.0.join("missing.rfraw")).is_err());
+
+    // Timestamps must cover every frame exactly once
+    write_png(&dir.0.join("a.png"), 4, 4, [0, 0, 0, 255]);
+    fs::write(dir.0.join("timestamps.txt"), "0\n10\n").unwrap();
+    assert!(ReplayCaptureEngine::open(&dir.0).is_err());
+}
@@ -235,10 +235,19 @@ function App() {
           }
           break;
         case "zoom_in":
-          await applyUiZoom(uiZoom + UI_ZOOM_STEP, true);
+          // While capturing, the zoom shortcuts drive the follow-cursor capture zoom
+          if (isCapturing) {
+            await invoke("capture_zoom_in");
+          } else {
+            await applyUiZoom(uiZoom + UI_ZOOM_STEP, true);
+          }
           break;
         case "zoom_out":
-          await applyUiZoom(uiZoom - UI_ZOOM_STEP, true);
+          if (isCapturing) {
+            await invoke("capture_zoom_out");
+          } else {
+            await applyUiZoom(uiZoom - UI_ZOOM_STEP, true);
+          }
           break;
         default:
           break;